@@ -0,0 +1,324 @@
+
+using namespace System.Management.Automation
+using namespace System.Management.Automation.Language
+
+Register-ArgumentCompleter -Native -CommandName 'digest_tool' -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $commandElements = $commandAst.CommandElements
+    $command = @(
+        'digest_tool'
+        for ($i = 1; $i -lt $commandElements.Count; $i++) {
+            $element = $commandElements[$i]
+            if ($element -isnot [StringConstantExpressionAst] -or
+                $element.StringConstantType -ne [StringConstantType]::BareWord -or
+                $element.Value.StartsWith('-') -or
+                $element.Value -eq $wordToComplete) {
+                break
+        }
+        $element.Value
+    }) -join ';'
+
+    $completions = @(switch ($command) {
+        'digest_tool' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('-V', 'V', [CompletionResultType]::ParameterName, 'Print version information')
+            [CompletionResult]::new('--version', 'version', [CompletionResultType]::ParameterName, 'Print version information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            [CompletionResult]::new('hash', 'hash', [CompletionResultType]::ParameterValue, 'ファイル、ディレクトリ、文字列のハッシュ値を計算します。')
+            [CompletionResult]::new('check', 'check', [CompletionResultType]::ParameterValue, 'チェックサムファイル(GNU形式、BSD形式、SFV形式、hashdeep形式)に記載されたファイルを検証します。パスはチェックサムファイルのあるディレクトリからの相対パスとして扱います。--sidecarを指定すると、ファイルと同じディレクトリにあるチェックサムファイル(foo.iso.sha256、SHA256SUMS、.md5、.sha512など)を探して検証します。')
+            [CompletionResult]::new('manifest', 'manifest', [CompletionResultType]::ParameterValue, 'ファイルのチェックサムファイルを作ります。ディレクトリは再帰的にたどります。')
+            [CompletionResult]::new('compare', 'compare', [CompletionResultType]::ParameterValue, '既知のハッシュ値の一覧(hashdeep形式、GNU形式、BSD形式)とファイルを突き合わせ、一致(MATCHED)、移動(MOVED)、新規(NEW)、変更(CHANGED)、欠落(MISSING)に分類します。')
+            [CompletionResult]::new('integrate', 'integrate', [CompletionResultType]::ParameterValue, 'エクスプローラーの右クリックメニューへの登録を管理します。(Windowsのみ)')
+            [CompletionResult]::new('gui', 'gui', [CompletionResultType]::ParameterValue, '右クリックメニューの追加と削除をする画面を開きます。(Windowsのみ)')
+            [CompletionResult]::new('convert', 'convert', [CompletionResultType]::ParameterValue, 'チェックサムファイル(GNU形式、BSD形式、SFV形式、hashdeep形式、JSON形式)を別の形式に書き換えます。ハッシュ値は計算し直しません。')
+            [CompletionResult]::new('tee', 'tee', [CompletionResultType]::ParameterValue, '標準入力を標準出力(またはファイル)にそのまま書き出しながらハッシュ値を計算します。ハッシュ値は標準エラー出力(またはファイル)に書き出します。')
+            [CompletionResult]::new('baseline', 'baseline', [CompletionResultType]::ParameterValue, 'ファイルのパス、サイズ、パーミッション、更新日時、ハッシュ値をベースラインファイルに記録し、後から変化を調べます。ベースラインファイルにはHMAC-SHA256を付けます。')
+            [CompletionResult]::new('xattr', 'xattr', [CompletionResultType]::ParameterValue, 'ハッシュ値と計算した時刻を、ファイルの拡張属性user.checksum.<アルゴリズム>に保存し、後から内容が壊れていないか確かめます。')
+            [CompletionResult]::new('watch', 'watch', [CompletionResultType]::ParameterValue, 'ファイルやディレクトリの変更を監視し、書き込みが落ち着いたらハッシュ値を計算し直して、変更前と変更後のハッシュ値を書き出します。Ctrl+Cで終了します。')
+            [CompletionResult]::new('config', 'config', [CompletionResultType]::ParameterValue, '設定ファイルとプロファイルから決まった設定を、TOML形式で表示します。')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'digest_tool;hash' {
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--text', 'text', [CompletionResultType]::ParameterName, 'ファイルの代わりに文字列のハッシュ値を計算します。')
+            [CompletionResult]::new('--encoding', 'encoding', [CompletionResultType]::ParameterName, '--textの文字列をバイト列にするときのエンコーディング デフォルト(utf8)')
+            [CompletionResult]::new('--newline', 'newline', [CompletionResultType]::ParameterName, '--textの文字列の末尾に付け足す改行 省略すると付け足しません。')
+            [CompletionResult]::new('--tree_metadata', 'tree_metadata', [CompletionResultType]::ParameterName, 'ディレクトリのツリーハッシュに含めるメタデータ exec: 実行ビット symlink: シンボリックリンクのリンク先 カンマ区切りで複数指定できます。')
+            [CompletionResult]::new('--decompress', 'decompress', [CompletionResultType]::ParameterName, '入力ファイルを展開してからハッシュ値を計算します。形式を省略すると先頭のバイト列から判別します。')
+            [CompletionResult]::new('--normalize', 'normalize', [CompletionResultType]::ParameterName, 'テキストファイルとして正規化してからハッシュ値を計算します。例: eol=lf,bom=strip,unicode=nfc 指定できる項目: eol=lf|crlf, bom=strip|keep, unicode=nfc|nfd|nfkc|nfkd')
+            [CompletionResult]::new('--expect', 'expect', [CompletionResultType]::ParameterName, '計算したハッシュ値を期待値と比べ、MATCHかMISMATCHを表示します。一致しなければ終了コードは1です。16進数(大文字小文字どちらでも)、base64、sha256:<hex>やsha256-<base64>のような接頭辞付きの形で指定できます。アルゴリズムは接頭辞、-d、期待値の長さの順に決めます。')
+            [CompletionResult]::new('--archive', 'archive', [CompletionResultType]::ParameterName, '入力ファイルをアーカイブとして扱い、展開せずにメンバーごとのハッシュ値を計算します。形式を省略するとファイル名から推定します。')
+            [CompletionResult]::new('--format', 'format', [CompletionResultType]::ParameterName, '計算結果の出力形式 json、ndjson(1ファイル1行)、csvはスクリプトから読むための形式です。デフォルト(text)')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;check' {
+            [CompletionResult]::new('--sidecar', 'sidecar', [CompletionResultType]::ParameterName, '同じディレクトリにあるチェックサムファイルで検証するファイル ディレクトリを指定すると、その下のすべてのチェックサムファイルを検証します。複数指定できます。')
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'GNU形式の行のハッシュアルゴリズム 省略するとファイル名とハッシュ値の長さから推定します。')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'GNU形式の行のハッシュアルゴリズム 省略するとファイル名とハッシュ値の長さから推定します。')
+            [CompletionResult]::new('--normalize', 'normalize', [CompletionResultType]::ParameterName, 'テキストファイルとして正規化してからハッシュ値を計算します。例: eol=lf,bom=strip,unicode=nfc 指定できる項目: eol=lf|crlf, bom=strip|keep, unicode=nfc|nfd|nfkc|nfkd')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--report', 'report', [CompletionResultType]::ParameterName, 'ファイルごとの結果と合計をまとめたレポートを書き出すファイル 拡張子で形式(HTML、Markdown、JUnit XML)を決めます。')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;manifest' {
+            [CompletionResult]::new('--format', 'format', [CompletionResultType]::ParameterName, 'チェックサムファイルの形式 gnu: sha256sumと同じ形式(アルゴリズムは1種類) bsd: BSDタグ形式 sfv: SFV形式(crc32) hashdeep: hashdeepの形式(デフォルトはmd5,sha256) json: JSON形式 デフォルト(gnu)')
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--base_dir', 'base_dir', [CompletionResultType]::ParameterName, 'チェックサムファイルに書く(--audit では突き合わせる)相対パスの基準のディレクトリ デフォルトはカレントディレクトリ')
+            [CompletionResult]::new('-o', 'o', [CompletionResultType]::ParameterName, 'チェックサムファイルの出力先 省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--output_file', 'output_file', [CompletionResultType]::ParameterName, 'チェックサムファイルの出力先 省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--normalize', 'normalize', [CompletionResultType]::ParameterName, 'テキストファイルとして正規化してからハッシュ値を計算します。例: eol=lf,bom=strip,unicode=nfc 指定できる項目: eol=lf|crlf, bom=strip|keep, unicode=nfc|nfd|nfkc|nfkd')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--verify_cache', 'verify_cache', [CompletionResultType]::ParameterName, 'キャッシュにあるファイルのうち、指定した割合(パーセント)を無作為に選んで計算し直し、キャッシュと違えば警告する 例: 1')
+            [CompletionResult]::new('--verify-cache', 'verify-cache', [CompletionResultType]::ParameterName, 'キャッシュにあるファイルのうち、指定した割合(パーセント)を無作為に選んで計算し直し、キャッシュと違えば警告する 例: 1')
+            [CompletionResult]::new('--report', 'report', [CompletionResultType]::ParameterName, 'ファイルごとの結果と合計をまとめたレポートを書き出すファイル 拡張子で形式(HTML、Markdown、JUnit XML)を決めます。')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('--no_cache', 'no_cache', [CompletionResultType]::ParameterName, 'ハッシュ値のキャッシュを読み書きせずに、すべてのファイルを読み込む')
+            [CompletionResult]::new('--no-cache', 'no-cache', [CompletionResultType]::ParameterName, 'ハッシュ値のキャッシュを読み書きせずに、すべてのファイルを読み込む')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;compare' {
+            [CompletionResult]::new('--base_dir', 'base_dir', [CompletionResultType]::ParameterName, 'チェックサムファイルに書く(--audit では突き合わせる)相対パスの基準のディレクトリ デフォルトはカレントディレクトリ')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--report', 'report', [CompletionResultType]::ParameterName, 'ファイルごとの結果と合計をまとめたレポートを書き出すファイル 拡張子で形式(HTML、Markdown、JUnit XML)を決めます。')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;integrate' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            [CompletionResult]::new('install', 'install', [CompletionResultType]::ParameterValue, '右クリックメニューにハッシュ値の計算を追加します。')
+            [CompletionResult]::new('remove', 'remove', [CompletionResultType]::ParameterValue, '右クリックメニューからハッシュ値の計算を削除します。')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'digest_tool;integrate;install' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;integrate;remove' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;integrate;help' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;gui' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;convert' {
+            [CompletionResult]::new('--to', 'to', [CompletionResultType]::ParameterName, '書き換え後の形式')
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'GNU形式の行のハッシュアルゴリズム 省略するとファイル名とハッシュ値の長さから推定します。')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'GNU形式の行のハッシュアルゴリズム 省略するとファイル名とハッシュ値の長さから推定します。')
+            [CompletionResult]::new('-o', 'o', [CompletionResultType]::ParameterName, '書き換えたチェックサムファイルの出力先 省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--output_file', 'output_file', [CompletionResultType]::ParameterName, '書き換えたチェックサムファイルの出力先 省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;tee' {
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズムを選択できます。複数指定できます。デフォルト(sha2_256)')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズムを選択できます。複数指定できます。デフォルト(sha2_256)')
+            [CompletionResult]::new('-o', 'o', [CompletionResultType]::ParameterName, 'データの出力先ファイル。省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--output_file', 'output_file', [CompletionResultType]::ParameterName, 'データの出力先ファイル。省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--digest_file', 'digest_file', [CompletionResultType]::ParameterName, 'ハッシュ値の出力先ファイル。省略すると標準エラー出力に書き出します。')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;baseline' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            [CompletionResult]::new('create', 'create', [CompletionResultType]::ParameterValue, 'ファイルとディレクトリの下のファイルを記録したベースラインファイルを作ります。')
+            [CompletionResult]::new('compare', 'compare', [CompletionResultType]::ParameterValue, 'ベースラインファイルと実際のファイルを比べ、追加(ADDED)、削除(REMOVED)、変更(MODIFIED)、メタデータだけの変化(METADATA)に分類します。')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'digest_tool;baseline;create' {
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('-o', 'o', [CompletionResultType]::ParameterName, 'ベースラインファイルの出力先 省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--output_file', 'output_file', [CompletionResultType]::ParameterName, 'ベースラインファイルの出力先 省略すると標準出力に書き出します。')
+            [CompletionResult]::new('--key_file', 'key_file', [CompletionResultType]::ParameterName, 'MACの鍵のファイル 省略すると環境変数DIGEST_TOOL_BASELINE_KEYの値を鍵にします。')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;baseline;compare' {
+            [CompletionResult]::new('--key_file', 'key_file', [CompletionResultType]::ParameterName, 'MACの鍵のファイル 省略すると環境変数DIGEST_TOOL_BASELINE_KEYの値を鍵にします。')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;baseline;help' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;xattr' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            [CompletionResult]::new('write', 'write', [CompletionResultType]::ParameterValue, 'ファイルとディレクトリの下のファイルのハッシュ値を計算し、拡張属性に書きます。')
+            [CompletionResult]::new('read', 'read', [CompletionResultType]::ParameterValue, 'ファイルの拡張属性に保存したハッシュ値を表示します。')
+            [CompletionResult]::new('scrub', 'scrub', [CompletionResultType]::ParameterValue, 'ハッシュ値を計算し直して拡張属性に保存したものと比べ、一致(OK)、破損(CORRUPT)、保存後に更新(OUTDATED)、保存なし(MISSING)、拡張属性に非対応(UNSUPPORTED)に分類します。')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'digest_tool;xattr;write' {
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;xattr;read' {
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム 複数指定できます。デフォルト(保存されているすべてのアルゴリズム)')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム 複数指定できます。デフォルト(保存されているすべてのアルゴリズム)')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;xattr;scrub' {
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム 複数指定できます。デフォルト(保存されているすべてのアルゴリズム)')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム 複数指定できます。デフォルト(保存されているすべてのアルゴリズム)')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;xattr;help' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;watch' {
+            [CompletionResult]::new('-d', 'd', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--digest', 'digest', [CompletionResultType]::ParameterName, 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。')
+            [CompletionResult]::new('--debounce', 'debounce', [CompletionResultType]::ParameterName, '最後の変更からこの時間(ミリ秒)変更がなければ、書き込みが落ち着いたとみなします。デフォルト(500)')
+            [CompletionResult]::new('--format', 'format', [CompletionResultType]::ParameterName, '変更の出力形式 ndjsonは1件ごとに1行のJSONを書き出します。デフォルト(text)')
+            [CompletionResult]::new('--exclude', 'exclude', [CompletionResultType]::ParameterName, 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*')
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;config' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('-h', 'h', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--help', 'help', [CompletionResultType]::ParameterName, 'Print help information')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+        'digest_tool;help' {
+            [CompletionResult]::new('--lang', 'lang', [CompletionResultType]::ParameterName, 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。')
+            [CompletionResult]::new('--profile', 'profile', [CompletionResultType]::ParameterName, '設定ファイルのプロファイル([profile.<NAME>])を使います。')
+            [CompletionResult]::new('--buffer_size', 'buffer_size', [CompletionResultType]::ParameterName, 'ファイルを読み込むバッファのバイト数 デフォルト(8192)')
+            [CompletionResult]::new('--pause', 'pause', [CompletionResultType]::ParameterName, '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。')
+            break
+        }
+    })
+
+    $completions.Where{ $_.CompletionText -like "$wordToComplete*" } |
+        Sort-Object -Property ListItemText
+}
@@ -0,0 +1,188 @@
+complete -c digest_tool -n "__fish_use_subcommand" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_use_subcommand" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_use_subcommand" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_use_subcommand" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_use_subcommand" -s V -l version -d 'Print version information'
+complete -c digest_tool -n "__fish_use_subcommand" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "hash" -d 'ファイル、ディレクトリ、文字列のハッシュ値を計算します。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "check" -d 'チェックサムファイル(GNU形式、BSD形式、SFV形式、hashdeep形式)に記載されたファイルを検証します。パスはチェックサムファイルのあるディレクトリからの相対パスとして扱います。--sidecarを指定すると、ファイルと同じディレクトリにあるチェックサムファイル(foo.iso.sha256、SHA256SUMS、.md5、.sha512など)を探して検証します。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "manifest" -d 'ファイルのチェックサムファイルを作ります。ディレクトリは再帰的にたどります。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "compare" -d '既知のハッシュ値の一覧(hashdeep形式、GNU形式、BSD形式)とファイルを突き合わせ、一致(MATCHED)、移動(MOVED)、新規(NEW)、変更(CHANGED)、欠落(MISSING)に分類します。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "integrate" -d 'エクスプローラーの右クリックメニューへの登録を管理します。(Windowsのみ)'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "gui" -d '右クリックメニューの追加と削除をする画面を開きます。(Windowsのみ)'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "convert" -d 'チェックサムファイル(GNU形式、BSD形式、SFV形式、hashdeep形式、JSON形式)を別の形式に書き換えます。ハッシュ値は計算し直しません。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "tee" -d '標準入力を標準出力(またはファイル)にそのまま書き出しながらハッシュ値を計算します。ハッシュ値は標準エラー出力(またはファイル)に書き出します。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "baseline" -d 'ファイルのパス、サイズ、パーミッション、更新日時、ハッシュ値をベースラインファイルに記録し、後から変化を調べます。ベースラインファイルにはHMAC-SHA256を付けます。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "xattr" -d 'ハッシュ値と計算した時刻を、ファイルの拡張属性user.checksum.<アルゴリズム>に保存し、後から内容が壊れていないか確かめます。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "watch" -d 'ファイルやディレクトリの変更を監視し、書き込みが落ち着いたらハッシュ値を計算し直して、変更前と変更後のハッシュ値を書き出します。Ctrl+Cで終了します。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "config" -d '設定ファイルとプロファイルから決まった設定を、TOML形式で表示します。'
+complete -c digest_tool -n "__fish_use_subcommand" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -s d -l digest -d 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l text -d 'ファイルの代わりに文字列のハッシュ値を計算します。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l encoding -d '--textの文字列をバイト列にするときのエンコーディング デフォルト(utf8)' -r -f -a "{utf8	,utf16le	,utf16be	,shift_jis	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l newline -d '--textの文字列の末尾に付け足す改行 省略すると付け足しません。' -r -f -a "{lf	,crlf	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l tree_metadata -d 'ディレクトリのツリーハッシュに含めるメタデータ exec: 実行ビット symlink: シンボリックリンクのリンク先 カンマ区切りで複数指定できます。' -r -f -a "{exec	,symlink	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l decompress -d '入力ファイルを展開してからハッシュ値を計算します。形式を省略すると先頭のバイト列から判別します。' -r -f -a "{auto	,gzip	,bzip2	,xz	,zstd	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l normalize -d 'テキストファイルとして正規化してからハッシュ値を計算します。例: eol=lf,bom=strip,unicode=nfc 指定できる項目: eol=lf|crlf, bom=strip|keep, unicode=nfc|nfd|nfkc|nfkd' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l expect -d '計算したハッシュ値を期待値と比べ、MATCHかMISMATCHを表示します。一致しなければ終了コードは1です。16進数(大文字小文字どちらでも)、base64、sha256:<hex>やsha256-<base64>のような接頭辞付きの形で指定できます。アルゴリズムは接頭辞、-d、期待値の長さの順に決めます。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l archive -d '入力ファイルをアーカイブとして扱い、展開せずにメンバーごとのハッシュ値を計算します。形式を省略するとファイル名から推定します。' -r -f -a "{zip	,tar	,tar.gz	,tar.xz	,tar.zst	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l format -d '計算結果の出力形式 json、ndjson(1ファイル1行)、csvはスクリプトから読むための形式です。デフォルト(text)' -r -f -a "{text	,json	,ndjson	,csv	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from hash" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l sidecar -d '同じディレクトリにあるチェックサムファイルで検証するファイル ディレクトリを指定すると、その下のすべてのチェックサムファイルを検証します。複数指定できます。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -s d -l digest -d 'GNU形式の行のハッシュアルゴリズム 省略するとファイル名とハッシュ値の長さから推定します。' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l normalize -d 'テキストファイルとして正規化してからハッシュ値を計算します。例: eol=lf,bom=strip,unicode=nfc 指定できる項目: eol=lf|crlf, bom=strip|keep, unicode=nfc|nfd|nfkc|nfkd' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l report -d 'ファイルごとの結果と合計をまとめたレポートを書き出すファイル 拡張子で形式(HTML、Markdown、JUnit XML)を決めます。' -r -F
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from check" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l format -d 'チェックサムファイルの形式 gnu: sha256sumと同じ形式(アルゴリズムは1種類) bsd: BSDタグ形式 sfv: SFV形式(crc32) hashdeep: hashdeepの形式(デフォルトはmd5,sha256) json: JSON形式 デフォルト(gnu)' -r -f -a "{gnu	,bsd	,sfv	,hashdeep	,json	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -s d -l digest -d 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l base_dir -d 'チェックサムファイルに書く(--audit では突き合わせる)相対パスの基準のディレクトリ デフォルトはカレントディレクトリ' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -s o -l output_file -d 'チェックサムファイルの出力先 省略すると標準出力に書き出します。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l normalize -d 'テキストファイルとして正規化してからハッシュ値を計算します。例: eol=lf,bom=strip,unicode=nfc 指定できる項目: eol=lf|crlf, bom=strip|keep, unicode=nfc|nfd|nfkc|nfkd' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l verify_cache -l verify-cache -d 'キャッシュにあるファイルのうち、指定した割合(パーセント)を無作為に選んで計算し直し、キャッシュと違えば警告する 例: 1' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l report -d 'ファイルごとの結果と合計をまとめたレポートを書き出すファイル 拡張子で形式(HTML、Markdown、JUnit XML)を決めます。' -r -F
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l no_cache -l no-cache -d 'ハッシュ値のキャッシュを読み書きせずに、すべてのファイルを読み込む'
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from manifest" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -l base_dir -d 'チェックサムファイルに書く(--audit では突き合わせる)相対パスの基準のディレクトリ デフォルトはカレントディレクトリ' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -l report -d 'ファイルごとの結果と合計をまとめたレポートを書き出すファイル 拡張子で形式(HTML、Markdown、JUnit XML)を決めます。' -r -F
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from compare" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "install" -d '右クリックメニューにハッシュ値の計算を追加します。'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "remove" -d '右クリックメニューからハッシュ値の計算を削除します。'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and not __fish_seen_subcommand_from install; and not __fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from install" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from install" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from install" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from install" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from install" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from remove" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from remove" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from remove" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from remove" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from remove" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from help" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from help" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from help" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from integrate; and __fish_seen_subcommand_from help" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from gui" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from gui" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from gui" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from gui" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from gui" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -l to -d '書き換え後の形式' -r -f -a "{gnu	,bsd	,sfv	,hashdeep	,json	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -s d -l digest -d 'GNU形式の行のハッシュアルゴリズム 省略するとファイル名とハッシュ値の長さから推定します。' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -s o -l output_file -d '書き換えたチェックサムファイルの出力先 省略すると標準出力に書き出します。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from convert" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -s d -l digest -d 'ハッシュアルゴリズムを選択できます。複数指定できます。デフォルト(sha2_256)' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -s o -l output_file -d 'データの出力先ファイル。省略すると標準出力に書き出します。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -l digest_file -d 'ハッシュ値の出力先ファイル。省略すると標準エラー出力に書き出します。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from tee" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -f -a "create" -d 'ファイルとディレクトリの下のファイルを記録したベースラインファイルを作ります。'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -f -a "compare" -d 'ベースラインファイルと実際のファイルを比べ、追加(ADDED)、削除(REMOVED)、変更(MODIFIED)、メタデータだけの変化(METADATA)に分類します。'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and not __fish_seen_subcommand_from create; and not __fish_seen_subcommand_from compare; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -s d -l digest -d 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -s o -l output_file -d 'ベースラインファイルの出力先 省略すると標準出力に書き出します。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -l key_file -d 'MACの鍵のファイル 省略すると環境変数DIGEST_TOOL_BASELINE_KEYの値を鍵にします。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from create" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from compare" -l key_file -d 'MACの鍵のファイル 省略すると環境変数DIGEST_TOOL_BASELINE_KEYの値を鍵にします。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from compare" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from compare" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from compare" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from compare" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from compare" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from help" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from help" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from help" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from baseline; and __fish_seen_subcommand_from help" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -f -a "write" -d 'ファイルとディレクトリの下のファイルのハッシュ値を計算し、拡張属性に書きます。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -f -a "read" -d 'ファイルの拡張属性に保存したハッシュ値を表示します。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -f -a "scrub" -d 'ハッシュ値を計算し直して拡張属性に保存したものと比べ、一致(OK)、破損(CORRUPT)、保存後に更新(OUTDATED)、保存なし(MISSING)、拡張属性に非対応(UNSUPPORTED)に分類します。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and not __fish_seen_subcommand_from write; and not __fish_seen_subcommand_from read; and not __fish_seen_subcommand_from scrub; and not __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from write" -s d -l digest -d 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from write" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from write" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from write" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from write" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from write" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from write" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from read" -s d -l digest -d 'ハッシュアルゴリズム 複数指定できます。デフォルト(保存されているすべてのアルゴリズム)' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from read" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from read" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from read" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from read" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from read" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from read" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from scrub" -s d -l digest -d 'ハッシュアルゴリズム 複数指定できます。デフォルト(保存されているすべてのアルゴリズム)' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from scrub" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from scrub" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from scrub" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from scrub" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from scrub" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from scrub" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from help" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from help" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from help" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from xattr; and __fish_seen_subcommand_from help" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -s d -l digest -d 'ハッシュアルゴリズム デフォルト(sha2_256 設定ファイルで変えられます) manifest、tee、xattr writeでは複数指定できます。' -r -f -a "{sha2_256	,sha2_512	,sha3_256	,sha3_512	,crc32	,md5	,sha1	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -l debounce -d '最後の変更からこの時間(ミリ秒)変更がなければ、書き込みが落ち着いたとみなします。デフォルト(500)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -l format -d '変更の出力形式 ndjsonは1件ごとに1行のJSONを書き出します。デフォルト(text)' -r -f -a "{text	,ndjson	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -l exclude -d 'ディレクトリの下のファイルを集めるときに除くパターン 名前か、ディレクトリからの相対パスと比べます。複数指定できます。例: .git, *.tmp, build/*' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from watch" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from config" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from config" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from config" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from config" -s h -l help -d 'Print help information'
+complete -c digest_tool -n "__fish_seen_subcommand_from config" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
+complete -c digest_tool -n "__fish_seen_subcommand_from help" -l lang -d 'メッセージの言語 ja、en 省略すると環境変数LC_ALL、LC_MESSAGES、LANGから決めます。' -r -f -a "{ja	,en	}"
+complete -c digest_tool -n "__fish_seen_subcommand_from help" -l profile -d '設定ファイルのプロファイル([profile.<NAME>])を使います。' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from help" -l buffer_size -d 'ファイルを読み込むバッファのバイト数 デフォルト(8192)' -r
+complete -c digest_tool -n "__fish_seen_subcommand_from help" -l pause -d '終了前にEnterキーが押されるまで待ちます。コンテクストメニューから起動したときに結果を読むために使います。'
@@ -0,0 +1,257 @@
+//! # アーカイブモジュール
+//! zip、tar(.gz/.xz/.zst)のメンバーを展開せずに読み込み、メンバーごとのハッシュ値を求めます。
+//! ディスクには何も書き込みません。
+//!
+//! アーカイブのタイムスタンプやメンバーの並び順に左右されない内容ハッシュ値も求められます。
+//! 内容ハッシュ値は、通常ファイルのメンバーをパスのバイト列順に並べ、以下の形に直列化したもののハッシュ値です。
+//! ```text
+//! "DTARCH1\n"
+//! メンバーごとに u32be(len(path)) path u64be(size) u32be(len(digest)) digest
+//! ```
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::decompress::{self, Compression};
+use crate::hash;
+use crate::multi_file;
+
+/// 直列化の先頭に付ける形式名
+const CONTENT_DIGEST_HEADER: &[u8] = b"DTARCH1\n";
+
+/// アーカイブの形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// CLI引数で指定する名前からアーカイブの形式を取得します。
+    pub fn from_name(name: &str) -> Option<ArchiveFormat> {
+        match name {
+            "zip" => Some(ArchiveFormat::Zip),
+            "tar" => Some(ArchiveFormat::Tar),
+            "tar.gz" => Some(ArchiveFormat::TarGz),
+            "tar.xz" => Some(ArchiveFormat::TarXz),
+            "tar.zst" => Some(ArchiveFormat::TarZst),
+            _ => None,
+        }
+    }
+
+    /// CLI引数で指定する名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarXz => "tar.xz",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+
+    /// ファイル名の拡張子からアーカイブの形式を推定します。
+    pub fn detect(path: &std::path::Path) -> Option<ArchiveFormat> {
+        let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+        let suffixes = [
+            (".zip", ArchiveFormat::Zip),
+            (".tar", ArchiveFormat::Tar),
+            (".tar.gz", ArchiveFormat::TarGz),
+            (".tgz", ArchiveFormat::TarGz),
+            (".tar.xz", ArchiveFormat::TarXz),
+            (".txz", ArchiveFormat::TarXz),
+            (".tar.zst", ArchiveFormat::TarZst),
+            (".tzst", ArchiveFormat::TarZst),
+        ];
+        suffixes
+            .iter()
+            .find(|(suffix, _)| file_name.ends_with(suffix))
+            .map(|(_, archive_format)| *archive_format)
+    }
+}
+
+/// アーカイブのメンバー
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveMember {
+    /// アーカイブ内のパス
+    pub path: String,
+    /// 展開後のサイズ
+    pub size: u64,
+    pub hash_value: Vec<u8>,
+}
+
+/// # アーカイブのハッシュ関数
+/// アーカイブの通常ファイルのメンバーを順に読み込み、メンバーごとのハッシュ値を返却します。
+/// ディレクトリやシンボリックリンクなどのメンバーは含みません。
+/// ## 引数
+/// - アーカイブのパス
+/// - アーカイブの形式
+/// - ハッシュアルゴリズム
+/// - プログレスバー アーカイブファイルを読み込んだバイト数だけ進めます
+/// ## 返り値
+/// Result型 std::ioのError あるいは、メンバーの配列(アーカイブ内の順番)
+pub fn digest_archive(
+    archive_path: &std::path::Path,
+    archive_format: ArchiveFormat,
+    digest_algorithm: DigestAlgorithm,
+    progress_bar: &indicatif::ProgressBar,
+) -> Result<Vec<ArchiveMember>, std::io::Error> {
+    let archive_file = std::fs::File::open(archive_path)?;
+    let compression = match archive_format {
+        ArchiveFormat::Zip => {
+            return digest_zip(archive_file, digest_algorithm, progress_bar);
+        }
+        ArchiveFormat::Tar => None,
+        ArchiveFormat::TarGz => Some(Compression::Gzip),
+        ArchiveFormat::TarXz => Some(Compression::Xz),
+        ArchiveFormat::TarZst => Some(Compression::Zstd),
+    };
+    let reader = std::io::BufReader::with_capacity(
+        multi_file::buffer_size(),
+        progress_bar.wrap_read(archive_file),
+    );
+    match compression {
+        Some(compression) => {
+            let (decoder, _) = decompress::decoder(reader, compression)?;
+            digest_tar(decoder, digest_algorithm)
+        }
+        None => digest_tar(reader, digest_algorithm),
+    }
+}
+
+/// tarのメンバーのハッシュ値を返却します。
+fn digest_tar(
+    reader: impl std::io::Read,
+    digest_algorithm: DigestAlgorithm,
+) -> Result<Vec<ArchiveMember>, std::io::Error> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = normalize_member_path(&String::from_utf8_lossy(&entry.path_bytes()));
+        let (size, hash_value) = hash::digest(
+            &mut entry,
+            digest_algorithm,
+            indicatif::ProgressBar::hidden(),
+        )?;
+        members.push(ArchiveMember {
+            path,
+            size,
+            hash_value,
+        });
+    }
+    Ok(members)
+}
+
+/// zipのメンバーのハッシュ値を返却します。
+fn digest_zip(
+    archive_file: std::fs::File,
+    digest_algorithm: DigestAlgorithm,
+    progress_bar: &indicatif::ProgressBar,
+) -> Result<Vec<ArchiveMember>, std::io::Error> {
+    let mut archive = zip::ZipArchive::new(std::io::BufReader::with_capacity(
+        multi_file::buffer_size(),
+        archive_file,
+    ))?;
+    let mut members = Vec::new();
+    for index in 0..archive.len() {
+        let mut member = archive.by_index(index)?;
+        progress_bar.inc(member.compressed_size());
+        if member.is_dir() {
+            continue;
+        }
+        let path = normalize_member_path(member.name());
+        let (size, hash_value) = hash::digest(
+            &mut member,
+            digest_algorithm,
+            indicatif::ProgressBar::hidden(),
+        )?;
+        members.push(ArchiveMember {
+            path,
+            size,
+            hash_value,
+        });
+    }
+    Ok(members)
+}
+
+/// `./`で始まるパスとそうでないパスを同じものとして扱う
+fn normalize_member_path(path: &str) -> String {
+    let mut path = path;
+    while let Some(stripped) = path.strip_prefix("./") {
+        path = stripped;
+    }
+    path.to_string()
+}
+
+/// # 内容ハッシュ関数
+/// メンバーの並び順やアーカイブのタイムスタンプに左右されない、アーカイブの内容のハッシュ値を返却します。
+pub fn content_digest(members: &[ArchiveMember], digest_algorithm: DigestAlgorithm) -> Vec<u8> {
+    let mut members: Vec<&ArchiveMember> = members.iter().collect();
+    members.sort_by(|a, b| {
+        a.path
+            .as_bytes()
+            .cmp(b.path.as_bytes())
+            .then_with(|| a.hash_value.cmp(&b.hash_value))
+    });
+
+    let mut serialized = CONTENT_DIGEST_HEADER.to_vec();
+    for member in members {
+        serialized.extend_from_slice(&(member.path.len() as u32).to_be_bytes());
+        serialized.extend_from_slice(member.path.as_bytes());
+        serialized.extend_from_slice(&member.size.to_be_bytes());
+        serialized.extend_from_slice(&(member.hash_value.len() as u32).to_be_bytes());
+        serialized.extend_from_slice(&member.hash_value);
+    }
+    hash::digest_bytes(&serialized, digest_algorithm)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 拡張子から形式を推定できる
+    #[test]
+    fn ok_detect() {
+        let detect = |name: &str| ArchiveFormat::detect(std::path::Path::new(name));
+        assert_eq!(Some(ArchiveFormat::Zip), detect("bundle.ZIP"));
+        assert_eq!(Some(ArchiveFormat::Tar), detect("bundle.tar"));
+        assert_eq!(Some(ArchiveFormat::TarGz), detect("bundle.tar.gz"));
+        assert_eq!(Some(ArchiveFormat::TarXz), detect("bundle.txz"));
+        assert_eq!(Some(ArchiveFormat::TarZst), detect("bundle.tar.zst"));
+        assert_eq!(None, detect("bundle.iso"));
+    }
+
+    /// 並び順が違っても内容ハッシュ値は同じになる
+    #[test]
+    fn ok_content_digest_ignores_order() {
+        let tar_a = build_tar(&[("./a.txt", b"a"), ("dir/b.txt", b"bb")]);
+        let tar_b = build_tar(&[("dir/b.txt", b"bb"), ("a.txt", b"a")]);
+        let members_a = digest_tar(&tar_a[..], DigestAlgorithm::Sha2_256).unwrap();
+        let members_b = digest_tar(&tar_b[..], DigestAlgorithm::Sha2_256).unwrap();
+
+        assert_eq!("a.txt", members_a[0].path);
+        assert_eq!(2, members_a[1].size);
+        assert_eq!(
+            content_digest(&members_a, DigestAlgorithm::Sha2_256),
+            content_digest(&members_b, DigestAlgorithm::Sha2_256)
+        );
+    }
+
+    /// メモリ上にtarを作る
+    fn build_tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+}
@@ -0,0 +1,272 @@
+//! # 監査モジュール
+//! 既知のハッシュ値の一覧と、実際のファイルのハッシュ値を突き合わせて分類します(hashdeepの監査モードと同じ考え方)。
+//! - 一致(MATCHED): 同じパスで、内容も同じ
+//! - 変更(CHANGED): 同じパスだが、内容が違う
+//! - 移動(MOVED): 既知のパスにはないが、入力にないパスの既知のファイルと内容が同じ
+//! - 新規(NEW): 既知のファイルのどれとも一致しない
+//! - 欠落(MISSING): 既知のファイルのうち、どの入力ファイルとも対応しなかった
+//!
+//! 内容が同じかどうかは、サイズ(両方にあれば)と、両方にあるすべてのハッシュアルゴリズムのハッシュ値で判断します。
+
+use crate::checksum_file::ChecksumEntry;
+use std::collections::{HashMap, HashSet};
+
+/// 監査結果の種類
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditStatus {
+    Matched,
+    Moved,
+    New,
+    Changed,
+    Missing,
+}
+
+impl AuditStatus {
+    /// 結果の表示に使う名前
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuditStatus::Matched => "MATCHED",
+            AuditStatus::Moved => "MOVED",
+            AuditStatus::New => "NEW",
+            AuditStatus::Changed => "CHANGED",
+            AuditStatus::Missing => "MISSING",
+        }
+    }
+}
+
+/// 1ファイル分の監査結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditResult {
+    /// 入力ファイルのパス 欠落の場合は既知のパス
+    pub path: String,
+    pub status: AuditStatus,
+    /// 移動の場合の既知のパス
+    pub known_path: Option<String>,
+}
+
+/// 監査結果の件数
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AuditSummary {
+    pub matched: usize,
+    pub moved: usize,
+    pub new: usize,
+    pub changed: usize,
+    pub missing: usize,
+}
+
+impl AuditSummary {
+    pub fn add(&mut self, status: AuditStatus) {
+        match status {
+            AuditStatus::Matched => self.matched += 1,
+            AuditStatus::Moved => self.moved += 1,
+            AuditStatus::New => self.new += 1,
+            AuditStatus::Changed => self.changed += 1,
+            AuditStatus::Missing => self.missing += 1,
+        }
+    }
+
+    /// すべて一致し、欠落もないか
+    pub fn is_success(&self) -> bool {
+        self.moved == 0 && self.new == 0 && self.changed == 0 && self.missing == 0
+    }
+}
+
+/// 2つの記載の内容が同じか
+fn same_content(a: &ChecksumEntry, b: &ChecksumEntry) -> bool {
+    if let (Some(a_size), Some(b_size)) = (a.size, b.size) {
+        if a_size != b_size {
+            return false;
+        }
+    }
+    let mut compared = false;
+    for (digest_algorithm, hash_value) in a.digests.iter() {
+        if let Some((_, other)) = b
+            .digests
+            .iter()
+            .find(|(other, _)| other == digest_algorithm)
+        {
+            if hash_value != other {
+                return false;
+            }
+            compared = true;
+        }
+    }
+    compared
+}
+
+/// # 監査関数
+/// 数十万件の一覧でも使えるように、パスとハッシュ値の索引を一度だけ作ってから突き合わせます。
+/// ## 引数
+/// - 既知のファイルの記載
+/// - 入力ファイルの記載 パスは既知のファイルと同じ基準の相対パスにしておきます
+/// ## 返り値
+/// 入力ファイルの順の結果のあとに、欠落した既知のファイルの結果を並べたもの
+pub fn audit(known: &[ChecksumEntry], files: &[ChecksumEntry]) -> Vec<AuditResult> {
+    // 同じパスが何度も書かれていれば、最初の記載を使う
+    let mut known_paths: HashMap<&str, usize> = HashMap::new();
+    // ハッシュ値から、その値を持つ既知のファイル 添字の小さい順
+    let mut known_digests: HashMap<(&'static str, &[u8]), Vec<usize>> = HashMap::new();
+    for (index, entry) in known.iter().enumerate() {
+        known_paths.entry(entry.path.as_str()).or_insert(index);
+        for (digest_algorithm, hash_value) in entry.digests.iter() {
+            known_digests
+                .entry((digest_algorithm.name(), hash_value.as_slice()))
+                .or_default()
+                .push(index);
+        }
+    }
+    let file_paths: HashSet<&str> = files.iter().map(|file| file.path.as_str()).collect();
+
+    let mut used = vec![false; known.len()];
+    let mut results = Vec::new();
+    for file in files {
+        let result = match known_paths.get(file.path.as_str()) {
+            Some(&index) => {
+                used[index] = true;
+                let status = if same_content(&known[index], file) {
+                    AuditStatus::Matched
+                } else {
+                    AuditStatus::Changed
+                };
+                AuditResult {
+                    path: file.path.clone(),
+                    status,
+                    known_path: None,
+                }
+            }
+            None => {
+                // 入力に同じパスがある既知のファイルと、ほかの入力の移動元になった既知のファイルは、移動元にならない
+                let moved_from = file
+                    .digests
+                    .iter()
+                    .filter_map(|(digest_algorithm, hash_value)| {
+                        known_digests.get(&(digest_algorithm.name(), hash_value.as_slice()))
+                    })
+                    .flat_map(|indexes| indexes.iter().copied())
+                    .filter(|&index| {
+                        !used[index]
+                            && !file_paths.contains(known[index].path.as_str())
+                            && same_content(&known[index], file)
+                    })
+                    .min();
+                match moved_from {
+                    Some(index) => {
+                        used[index] = true;
+                        AuditResult {
+                            path: file.path.clone(),
+                            status: AuditStatus::Moved,
+                            known_path: Some(known[index].path.clone()),
+                        }
+                    }
+                    None => AuditResult {
+                        path: file.path.clone(),
+                        status: AuditStatus::New,
+                        known_path: None,
+                    },
+                }
+            }
+        };
+        results.push(result);
+    }
+
+    for (entry, used) in known.iter().zip(used) {
+        if !used {
+            results.push(AuditResult {
+                path: entry.path.clone(),
+                status: AuditStatus::Missing,
+                known_path: None,
+            });
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli_arg_accepter::DigestAlgorithm;
+
+    fn entry(path: &str, content: &[u8]) -> ChecksumEntry {
+        ChecksumEntry {
+            path: path.to_string(),
+            size: Some(content.len() as u64),
+            digests: vec![(
+                DigestAlgorithm::Md5,
+                crate::hash::digest_bytes(content, DigestAlgorithm::Md5),
+            )],
+        }
+    }
+
+    /// 一致、移動、新規、変更、欠落に分類できる
+    #[test]
+    fn ok_audit() {
+        let known = vec![
+            entry("same.txt", b"same"),
+            entry("old_name.txt", b"moved"),
+            entry("edited.txt", b"before"),
+            entry("deleted.txt", b"deleted"),
+        ];
+        let files = vec![
+            entry("same.txt", b"same"),
+            entry("new_name.txt", b"moved"),
+            entry("edited.txt", b"after"),
+            entry("added.txt", b"added"),
+        ];
+
+        let results = audit(&known, &files);
+        let statuses: Vec<(&str, AuditStatus)> = results
+            .iter()
+            .map(|result| (result.path.as_str(), result.status))
+            .collect();
+        assert_eq!(
+            vec![
+                ("same.txt", AuditStatus::Matched),
+                ("new_name.txt", AuditStatus::Moved),
+                ("edited.txt", AuditStatus::Changed),
+                ("added.txt", AuditStatus::New),
+                ("deleted.txt", AuditStatus::Missing),
+            ],
+            statuses
+        );
+    }
+
+    /// 同じ内容の新しいファイルが2つあっても、1つの既知のファイルから移動したのは1つだけ
+    #[test]
+    fn ok_audit_moved_once() {
+        let known = vec![entry("old.txt", b"copy")];
+        let files = vec![entry("first.txt", b"copy"), entry("second.txt", b"copy")];
+        let results = audit(&known, &files);
+        assert_eq!(
+            vec![
+                AuditResult {
+                    path: "first.txt".to_string(),
+                    status: AuditStatus::Moved,
+                    known_path: Some("old.txt".to_string()),
+                },
+                AuditResult {
+                    path: "second.txt".to_string(),
+                    status: AuditStatus::New,
+                    known_path: None,
+                },
+            ],
+            results
+        );
+    }
+
+    /// 多くのファイルでも、すぐに突き合わせられる
+    #[test]
+    fn ok_audit_many() {
+        let count = 20_000;
+        let known: Vec<ChecksumEntry> = (0..count)
+            .map(|i| entry(&format!("old/{}.txt", i), i.to_string().as_bytes()))
+            .collect();
+        let files: Vec<ChecksumEntry> = (0..count)
+            .map(|i| entry(&format!("new/{}.txt", i), i.to_string().as_bytes()))
+            .collect();
+        let results = audit(&known, &files);
+        assert_eq!(count, results.len());
+        assert!(results
+            .iter()
+            .all(|result| result.status == AuditStatus::Moved));
+    }
+}
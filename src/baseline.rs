@@ -0,0 +1,576 @@
+//! # ベースラインモジュール
+//! ディレクトリの下のファイルのパス、サイズ、パーミッション、更新日時、ハッシュ値をベースラインファイルに記録し、
+//! 後から実際のファイルと比べて、変化を分類します(tripwireと同じ考え方)。
+//! - 追加(ADDED): ベースラインにないファイル
+//! - 削除(REMOVED): ベースラインにあるが、なくなったファイル
+//! - 変更(MODIFIED): サイズかハッシュ値が変わったファイル
+//! - メタデータ(METADATA): 内容は同じだが、パーミッションか更新日時が変わったファイル
+//!
+//! ベースラインファイルにはHMAC-SHA256を付けて、書き換えられたら分かるようにします。
+//! 項目の意味は`docs/baseline.md`にまとめています。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use crate::multi_file;
+use hmac::{Hmac, Mac, NewMac};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// ベースラインファイルの形式の版
+pub const BASELINE_VERSION: u32 = 1;
+/// ベースラインファイルに付けるMACのアルゴリズム
+pub const MAC_ALGORITHM: &str = "hmac-sha256";
+
+/// # 1ファイル分の記録
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// ベースラインを作ったときのカレントディレクトリからの`/`区切りのパス
+    pub path: String,
+    pub size: u64,
+    /// パーミッション Unixは`st_mode`の下位12ビット、Windowsは読み取り専用なら0o444、それ以外は0o644
+    pub mode: u32,
+    /// 更新日時 UTCのRFC 3339形式 取得できなければnull
+    pub modified: Option<String>,
+    /// 小文字の16進数のハッシュ値
+    pub digest: String,
+}
+
+/// # ベースライン
+/// MACはこの構造体をJSONにしたバイト列に付けます。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    /// 作った時刻 RFC 3339形式
+    pub created: String,
+    /// ハッシュアルゴリズムの名前 `-d`と同じ
+    pub algorithm: String,
+    /// 入力されたファイルとディレクトリのパス 比べるときも同じパスから集めます
+    pub roots: Vec<String>,
+    /// 除外したパターン 比べるときも同じパターンで除外します
+    pub exclude: Vec<String>,
+    /// パス順
+    pub entries: Vec<BaselineEntry>,
+}
+
+/// # ベースラインファイル
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineFile {
+    pub baseline: Baseline,
+    /// MACのアルゴリズム
+    pub mac_algorithm: String,
+    /// 小文字の16進数のMAC
+    pub mac: String,
+}
+
+impl BaselineFile {
+    /// # ベースラインにMACを付ける関数
+    pub fn sign(baseline: Baseline, key: &[u8]) -> BaselineFile {
+        let mac = hash::to_hex(&mac(&baseline, key).finalize().into_bytes());
+        BaselineFile {
+            baseline,
+            mac_algorithm: MAC_ALGORITHM.to_string(),
+            mac,
+        }
+    }
+
+    /// # MACを確かめる関数
+    /// ## 返り値
+    /// ベースラインが書き換えられていなければtrue
+    pub fn verify(&self, key: &[u8]) -> bool {
+        if self.mac_algorithm != MAC_ALGORITHM {
+            return false;
+        }
+        match hash::from_hex(&self.mac) {
+            Some(expected) => mac(&self.baseline, key).verify(&expected).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// ベースラインをJSONにしたバイト列のMAC
+fn mac(baseline: &Baseline, key: &[u8]) -> Hmac<sha2::Sha256> {
+    // HMACはどんな長さの鍵も受け付ける
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMACの鍵");
+    mac.update(&serde_json::to_vec(baseline).unwrap_or_default());
+    mac
+}
+
+/// # 実際のファイルの記録
+pub struct Snapshot {
+    /// パス順の記録
+    pub entries: Vec<BaselineEntry>,
+    /// 読めなかったファイルかディレクトリの`/`区切りのパスと、理由 パス順
+    pub unreadable: Vec<(String, std::io::Error)>,
+}
+
+/// # ファイルを記録する関数
+/// 入力されたファイルと、ディレクトリの下のファイルを、パス順に記録します。
+/// 読めないファイルやディレクトリがあっても止めずに、`unreadable`に入れて残りを記録し続けます。
+/// 改ざんを調べるためなので、キャッシュは使わずに、いつも中身を読んでハッシュ値を計算します。
+/// ## 引数
+/// - 入力されたファイルとディレクトリのパス
+/// - ハッシュアルゴリズム
+/// - 除外するパターン
+/// ## 返り値
+/// 記録と、読めなかったパス
+pub fn snapshot(
+    roots: &[PathBuf],
+    digest_algorithm: DigestAlgorithm,
+    exclude: &[glob::Pattern],
+) -> Snapshot {
+    let mut unreadable_files = Vec::new();
+    let files = multi_file::collect_files_skipping(roots, exclude, &mut unreadable_files);
+    let mut entries = Vec::new();
+    for file in files {
+        match record(&file, digest_algorithm) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => unreadable_files.push((file, e)),
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries.dedup_by(|a, b| a.path == b.path);
+    let mut unreadable: Vec<(String, std::io::Error)> = unreadable_files
+        .into_iter()
+        .map(|(file, e)| (display_path(&file), e))
+        .collect();
+    unreadable.sort_by(|a, b| a.0.cmp(&b.0));
+    unreadable.dedup_by(|a, b| a.0 == b.0);
+    Snapshot {
+        entries,
+        unreadable,
+    }
+}
+
+/// 1ファイルを読んで記録する
+fn record(file: &Path, digest_algorithm: DigestAlgorithm) -> Result<BaselineEntry, std::io::Error> {
+    let metadata = std::fs::metadata(file)?;
+    let mut reader =
+        std::io::BufReader::with_capacity(multi_file::buffer_size(), std::fs::File::open(file)?);
+    let (size, hash_values) = hash::multi_digest(
+        &mut reader,
+        &[digest_algorithm],
+        indicatif::ProgressBar::hidden(),
+    )?;
+    Ok(BaselineEntry {
+        path: multi_file::relative_path(file, Path::new("."))?,
+        size,
+        mode: mode(&metadata),
+        modified: metadata.modified().ok().map(|modified| {
+            chrono::DateTime::<chrono::Utc>::from(modified)
+                .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+        }),
+        digest: hash_values
+            .first()
+            .map(|(_, hash_value)| hash::to_hex(hash_value))
+            .unwrap_or_default(),
+    })
+}
+
+/// 記録と同じ形のパス 相対パスにできなければ、そのままのパス
+fn display_path(path: &Path) -> String {
+    multi_file::relative_path(path, Path::new("."))
+        .unwrap_or_else(|_| path.to_string_lossy().replace('\\', "/"))
+}
+
+#[cfg(unix)]
+fn mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn mode(metadata: &std::fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+/// 変化の種類
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DriftKind {
+    Added,
+    Removed,
+    Modified,
+    /// 内容は同じだが、パーミッションか更新日時が変わった
+    Metadata,
+    /// 読めなかったので、変わったかどうか分からない
+    Unreadable,
+}
+
+impl DriftKind {
+    /// 結果の表示に使う名前
+    pub fn label(&self) -> &'static str {
+        match self {
+            DriftKind::Added => "ADDED",
+            DriftKind::Removed => "REMOVED",
+            DriftKind::Modified => "MODIFIED",
+            DriftKind::Metadata => "METADATA",
+            DriftKind::Unreadable => "UNREADABLE",
+        }
+    }
+}
+
+/// 1ファイル分の変化
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    pub path: String,
+    pub kind: DriftKind,
+    /// 変わった項目と、変わる前と後の値 (`mode`、`modified`、`size`、`digest`)
+    pub changes: Vec<(&'static str, String, String)>,
+    /// 読めなかった理由
+    pub error: Option<String>,
+}
+
+/// 変化の件数
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DriftSummary {
+    pub unchanged: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub modified: usize,
+    pub metadata: usize,
+    pub unreadable: usize,
+}
+
+impl DriftSummary {
+    /// 変化がないか
+    pub fn is_success(&self) -> bool {
+        self.added == 0
+            && self.removed == 0
+            && self.modified == 0
+            && self.metadata == 0
+            && self.unreadable == 0
+    }
+}
+
+/// # ベースラインと実際のファイルを比べる関数
+/// ## 引数
+/// - ベースラインの記録
+/// - 実際のファイルの記録
+/// - 読めなかったファイルかディレクトリのパスと、理由 ベースラインにあるファイルは、削除ではなく読めなかったものとして数えます
+/// ## 返り値
+/// パス順の変化と、件数
+pub fn compare(
+    baseline: &[BaselineEntry],
+    live: &[BaselineEntry],
+    unreadable: &[(String, std::io::Error)],
+) -> (Vec<Drift>, DriftSummary) {
+    let live_entries: BTreeMap<&str, &BaselineEntry> = live
+        .iter()
+        .map(|live_entry| (live_entry.path.as_str(), live_entry))
+        .collect();
+    let baseline_entries: BTreeMap<&str, &BaselineEntry> = baseline
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry))
+        .collect();
+    let mut drifts = Vec::new();
+    let mut summary = DriftSummary::default();
+    for entry in baseline.iter() {
+        let live_entry = match live_entries.get(entry.path.as_str()) {
+            Some(live_entry) => live_entry,
+            None => {
+                let error = unreadable
+                    .iter()
+                    .find(|(path, _)| covers(path, &entry.path))
+                    .map(|(_, e)| e.to_string());
+                let kind = match error {
+                    Some(_) => {
+                        summary.unreadable += 1;
+                        DriftKind::Unreadable
+                    }
+                    None => {
+                        summary.removed += 1;
+                        DriftKind::Removed
+                    }
+                };
+                drifts.push(Drift {
+                    path: entry.path.clone(),
+                    kind,
+                    changes: Vec::new(),
+                    error,
+                });
+                continue;
+            }
+        };
+        let mut changes = Vec::new();
+        if entry.size != live_entry.size {
+            changes.push(("size", entry.size.to_string(), live_entry.size.to_string()));
+        }
+        if entry.digest != live_entry.digest {
+            changes.push(("digest", entry.digest.clone(), live_entry.digest.clone()));
+        }
+        let kind = if changes.is_empty() {
+            DriftKind::Metadata
+        } else {
+            DriftKind::Modified
+        };
+        if entry.mode != live_entry.mode {
+            changes.push((
+                "mode",
+                format!("{:o}", entry.mode),
+                format!("{:o}", live_entry.mode),
+            ));
+        }
+        if entry.modified != live_entry.modified {
+            changes.push((
+                "modified",
+                entry.modified.clone().unwrap_or_default(),
+                live_entry.modified.clone().unwrap_or_default(),
+            ));
+        }
+        if changes.is_empty() {
+            summary.unchanged += 1;
+            continue;
+        }
+        match kind {
+            DriftKind::Modified => summary.modified += 1,
+            _ => summary.metadata += 1,
+        }
+        drifts.push(Drift {
+            path: entry.path.clone(),
+            kind,
+            changes,
+            error: None,
+        });
+    }
+    for live_entry in live.iter() {
+        if !baseline_entries.contains_key(live_entry.path.as_str()) {
+            summary.added += 1;
+            drifts.push(Drift {
+                path: live_entry.path.clone(),
+                kind: DriftKind::Added,
+                changes: Vec::new(),
+                error: None,
+            });
+        }
+    }
+    // ベースラインにないファイルと、ベースラインのファイルを含まないディレクトリは、それだけで書き出す
+    for (path, e) in unreadable {
+        if !baseline.iter().any(|entry| covers(path, &entry.path)) {
+            summary.unreadable += 1;
+            drifts.push(Drift {
+                path: path.clone(),
+                kind: DriftKind::Unreadable,
+                changes: Vec::new(),
+                error: Some(e.to_string()),
+            });
+        }
+    }
+    drifts.sort_by(|a, b| a.path.cmp(&b.path));
+    (drifts, summary)
+}
+
+/// 読めなかったパスが、そのファイルか、そのファイルを含むディレクトリか
+fn covers(unreadable_path: &str, path: &str) -> bool {
+    path == unreadable_path
+        || path
+            .strip_prefix(unreadable_path)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(path: &str, size: u64, mode: u32, modified: &str, digest: &str) -> BaselineEntry {
+        BaselineEntry {
+            path: path.to_string(),
+            size,
+            mode,
+            modified: Some(modified.to_string()),
+            digest: digest.to_string(),
+        }
+    }
+
+    fn baseline() -> Baseline {
+        Baseline {
+            version: BASELINE_VERSION,
+            created: "2024-05-01T10:00:00+09:00".to_string(),
+            algorithm: "sha2_256".to_string(),
+            roots: vec!["dist".to_string()],
+            exclude: vec!["*.tmp".to_string()],
+            entries: vec![entry("dist/a.txt", 1, 0o644, "2024-05-01T00:00:00Z", "aa")],
+        }
+    }
+
+    /// 同じ鍵なら確かめられる
+    #[test]
+    fn ok_verify() {
+        let baseline_file = BaselineFile::sign(baseline(), b"secret");
+        assert_eq!(MAC_ALGORITHM, baseline_file.mac_algorithm);
+        assert_eq!(64, baseline_file.mac.len());
+        assert!(baseline_file.verify(b"secret"));
+
+        // JSONにして読み直しても確かめられる
+        let json = serde_json::to_string_pretty(&baseline_file).unwrap();
+        let read: BaselineFile = serde_json::from_str(&json).unwrap();
+        assert!(read.verify(b"secret"));
+    }
+
+    /// 鍵が違うか、書き換えられていれば確かめられない
+    #[test]
+    fn ng_verify() {
+        let baseline_file = BaselineFile::sign(baseline(), b"secret");
+        assert!(!baseline_file.verify(b"other"));
+
+        let mut tampered = baseline_file.clone();
+        tampered.baseline.entries[0].digest = "bb".to_string();
+        assert!(!tampered.verify(b"secret"));
+
+        let mut tampered = baseline_file.clone();
+        tampered.baseline.exclude.push("*.txt".to_string());
+        assert!(!tampered.verify(b"secret"));
+
+        let mut tampered = baseline_file;
+        tampered.mac = "zz".to_string();
+        assert!(!tampered.verify(b"secret"));
+    }
+
+    /// 追加、削除、変更、メタデータだけの変化に分類する
+    #[test]
+    fn ok_compare() {
+        let time = "2024-05-01T00:00:00Z";
+        let later = "2024-05-02T00:00:00Z";
+        let baseline = vec![
+            entry("a.txt", 1, 0o644, time, "aa"),
+            entry("b.txt", 1, 0o644, time, "bb"),
+            entry("c.txt", 1, 0o644, time, "cc"),
+            entry("d.sh", 1, 0o644, time, "dd"),
+        ];
+        let live = vec![
+            entry("a.txt", 1, 0o644, time, "aa"),
+            entry("c.txt", 2, 0o644, later, "c2"),
+            entry("d.sh", 1, 0o755, time, "dd"),
+            entry("e.txt", 1, 0o644, time, "ee"),
+        ];
+        let (drifts, summary) = compare(&baseline, &live, &[]);
+        assert_eq!(
+            vec![
+                ("b.txt", DriftKind::Removed),
+                ("c.txt", DriftKind::Modified),
+                ("d.sh", DriftKind::Metadata),
+                ("e.txt", DriftKind::Added),
+            ],
+            drifts
+                .iter()
+                .map(|drift| (drift.path.as_str(), drift.kind))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["size", "digest", "modified"],
+            drifts[1]
+                .changes
+                .iter()
+                .map(|(name, _, _)| *name)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![("mode", "644".to_string(), "755".to_string())],
+            drifts[2].changes
+        );
+        assert_eq!(
+            DriftSummary {
+                unchanged: 1,
+                added: 1,
+                removed: 1,
+                modified: 1,
+                metadata: 1,
+                unreadable: 0,
+            },
+            summary
+        );
+        assert!(!summary.is_success());
+        assert!(compare(&baseline, &baseline, &[]).1.is_success());
+    }
+
+    /// 実際のファイルを記録する
+    #[test]
+    fn ok_snapshot() {
+        let root =
+            std::env::temp_dir().join(format!("digest_tool_baseline_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("sub/b.txt"), "b").unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        std::fs::write(root.join("c.tmp"), "c").unwrap();
+
+        let entries = snapshot(
+            std::slice::from_ref(&root),
+            DigestAlgorithm::Sha2_256,
+            &[glob::Pattern::new("*.tmp").unwrap()],
+        )
+        .entries;
+        assert_eq!(2, entries.len());
+        assert!(entries[0].path.ends_with("/a.txt"));
+        assert!(entries[1].path.ends_with("/sub/b.txt"));
+        assert_eq!(1, entries[0].size);
+        assert_eq!(
+            "ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb",
+            entries[0].digest
+        );
+        assert!(entries[0].modified.as_ref().unwrap().ends_with('Z'));
+
+        // 読めないパスがあっても、残りのファイルを記録する
+        let missing = root.join("missing.txt");
+        let snapshot = snapshot(
+            &[missing, root.join("a.txt")],
+            DigestAlgorithm::Sha2_256,
+            &[],
+        );
+        assert_eq!(1, snapshot.entries.len());
+        assert_eq!(1, snapshot.unreadable.len());
+        assert!(snapshot.unreadable[0].0.ends_with("/missing.txt"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// 読めなかったファイルは、削除ではなく読めなかったものとして数え、比べ続ける
+    #[test]
+    fn ok_compare_unreadable() {
+        let time = "2024-05-01T00:00:00Z";
+        let baseline = vec![
+            entry("a.txt", 1, 0o644, time, "aa"),
+            entry("b.txt", 1, 0o644, time, "bb"),
+            entry("locked/c.txt", 1, 0o644, time, "cc"),
+            entry("locked2.txt", 1, 0o644, time, "dd"),
+        ];
+        let live = vec![
+            entry("a.txt", 1, 0o644, time, "a2"),
+            entry("locked2.txt", 1, 0o644, time, "dd"),
+        ];
+        let denied = || std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let unreadable = vec![
+            ("b.txt".to_string(), denied()),
+            ("locked".to_string(), denied()),
+            ("new.txt".to_string(), denied()),
+        ];
+        let (drifts, summary) = compare(&baseline, &live, &unreadable);
+        assert_eq!(
+            vec![
+                ("a.txt", DriftKind::Modified),
+                ("b.txt", DriftKind::Unreadable),
+                ("locked/c.txt", DriftKind::Unreadable),
+                ("new.txt", DriftKind::Unreadable),
+            ],
+            drifts
+                .iter()
+                .map(|drift| (drift.path.as_str(), drift.kind))
+                .collect::<Vec<_>>()
+        );
+        assert!(drifts[1].error.is_some());
+        assert_eq!(
+            DriftSummary {
+                unchanged: 1,
+                added: 0,
+                removed: 0,
+                modified: 1,
+                metadata: 0,
+                unreadable: 3,
+            },
+            summary
+        );
+        assert!(!summary.is_success());
+    }
+}
@@ -0,0 +1,598 @@
+//! # ハッシュ値のキャッシュモジュール
+//! ファイルの識別子(デバイスとiノード)、サイズ、更新日時、変更日時、ハッシュアルゴリズムをキーに、
+//! 計算したハッシュ値をディスクに保存します。次に実行したときは、変わっていないファイルを読み込まずに済みます。
+//! 置き場所と仕組みは`docs/cache.md`にまとめています。
+//!
+//! キャッシュファイルは1行に1件の記録を、タブ区切りで書きます。
+//! `<アルゴリズム>\t<サイズ>\t<更新日時>\t<変更日時>\t<使った日時>\t<ハッシュ値>\t<識別子>`
+//! 更新日時と変更日時はUNIXエポックからのナノ秒、使った日時は秒です。
+//! 長い間使わなかった記録は、書き込むときに捨てます。
+//!
+//! 読むときはロックしません。書くときは`<キャッシュファイル>.lock`を排他ロックしてから、
+//! ほかのプロセスが書いた記録を読み直して加え、一時ファイルに書いてから置き換えます。
+//! 読んでいるプロセスが、書きかけのファイルを読むことはありません。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use fs2::FileExt;
+use log::debug;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, SystemTime};
+
+/// キャッシュファイルの1行目 形式を変えたら版を上げ、古いキャッシュは読まずに作り直します
+const FILE_HEADER: &str = "digest_tool cache 2";
+
+/// 計算を始めたときから、この時間内に更新されたファイルは記録しない
+/// 日時の精度が粗いファイルシステムでは、同じ日時のまま書き換えられることがあるため
+const RACY_DURATION: Duration = Duration::from_secs(2);
+
+/// この時間使わなかった記録は、書き込むときに捨てる なくなったファイルや移したファイルの記録が残り続けないように
+const PRUNE_AFTER: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// キャッシュから読んだ記録の使った日時を、この時間より古ければ新しくする 読むたびにキャッシュファイルを書かないように
+const TOUCH_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 起動時に開いたキャッシュ 開いていなければキャッシュを使わない
+static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+/// # ファイルの状態
+/// これが変わらなければ、内容も変わっていないとみなします。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stamp {
+    /// ファイルの識別子 Unixは`<デバイス>:<iノード>`、それ以外は絶対パス
+    id: String,
+    size: u64,
+    /// 更新日時 UNIXエポックからのナノ秒
+    modified: i128,
+    /// 変更日時(ctime) UNIXエポックからのナノ秒 取得できない環境では0
+    changed: i128,
+}
+
+impl Stamp {
+    /// # ファイルの状態を取得する関数
+    #[cfg(unix)]
+    pub fn of(path: &Path) -> Result<Stamp, std::io::Error> {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = std::fs::metadata(path)?;
+        Ok(Stamp {
+            id: format!("{}:{}", metadata.dev(), metadata.ino()),
+            size: metadata.len(),
+            modified: metadata.mtime() as i128 * 1_000_000_000 + metadata.mtime_nsec() as i128,
+            changed: metadata.ctime() as i128 * 1_000_000_000 + metadata.ctime_nsec() as i128,
+        })
+    }
+
+    /// # ファイルの状態を取得する関数
+    /// iノードとctimeがないので、絶対パスと更新日時を使います。
+    #[cfg(not(unix))]
+    pub fn of(path: &Path) -> Result<Stamp, std::io::Error> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Stamp {
+            id: crate::multi_file::absolute_path(path)?
+                .to_string_lossy()
+                .into_owned(),
+            size: metadata.len(),
+            modified: metadata.modified().map(nanos).unwrap_or_default(),
+            changed: 0,
+        })
+    }
+
+    /// 計算を始めたときに近い日時に更新されていて、記録しても信用できないか
+    fn is_racy(&self, started: SystemTime) -> bool {
+        let threshold = nanos(started - RACY_DURATION);
+        self.modified >= threshold || self.changed >= threshold
+    }
+}
+
+/// UNIXエポックからのナノ秒
+fn nanos(time: SystemTime) -> i128 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(duration) => duration.as_nanos() as i128,
+        Err(e) => -(e.duration().as_nanos() as i128),
+    }
+}
+
+/// 記録のキー ファイルの識別子とアルゴリズムの名前 1つのファイルにはアルゴリズムごとに最新の記録だけを残す
+type Key = (String, &'static str);
+
+/// 記録 ファイルの状態とハッシュ値
+#[derive(Debug, Clone, PartialEq)]
+struct Record {
+    stamp: Stamp,
+    hash_value: Vec<u8>,
+    /// 最後に計算したか、キャッシュから読んだ日時 UNIXエポックからの秒
+    used: i64,
+}
+
+/// UNIXエポックからの秒
+fn seconds(time: SystemTime) -> i64 {
+    (nanos(time) / 1_000_000_000) as i64
+}
+
+/// # キャッシュ
+#[derive(Debug)]
+pub struct Cache {
+    path: PathBuf,
+    records: BTreeMap<Key, Record>,
+    /// このプロセスで計算した記録 保存するときに、ほかのプロセスが書いた記録に加える
+    updated: BTreeMap<Key, Record>,
+    /// キャッシュから読んだハッシュ値を計算し直す割合 パーセント
+    verify_percent: Option<f64>,
+    hits: usize,
+    verified: usize,
+    mismatched: usize,
+}
+
+impl Cache {
+    /// # キャッシュファイルを開く関数
+    /// ファイルがなければ、空のキャッシュにします。形式が違う行は読み飛ばします。
+    pub fn open(path: PathBuf, verify_percent: Option<f64>) -> Result<Cache, std::io::Error> {
+        let records = read(&path)?;
+        debug!("キャッシュ: {}件 {}", records.len(), path.display());
+        Ok(Cache {
+            path,
+            records,
+            updated: BTreeMap::new(),
+            verify_percent,
+            hits: 0,
+            verified: 0,
+            mismatched: 0,
+        })
+    }
+
+    /// すべてのアルゴリズムの記録があり、ファイルの状態が同じなら、記録したハッシュ値
+    fn lookup(
+        &self,
+        stamp: &Stamp,
+        digest_algorithms: &[DigestAlgorithm],
+    ) -> Option<Vec<(DigestAlgorithm, Vec<u8>)>> {
+        digest_algorithms
+            .iter()
+            .map(|digest_algorithm| {
+                self.records
+                    .get(&(stamp.id.clone(), digest_algorithm.name()))
+                    .filter(|record| record.stamp == *stamp)
+                    .map(|record| (*digest_algorithm, record.hash_value.clone()))
+            })
+            .collect()
+    }
+
+    fn store(&mut self, stamp: &Stamp, hash_values: &[(DigestAlgorithm, Vec<u8>)]) {
+        let used = seconds(SystemTime::now());
+        for (digest_algorithm, hash_value) in hash_values {
+            let key = (stamp.id.clone(), digest_algorithm.name());
+            let record = Record {
+                stamp: stamp.clone(),
+                hash_value: hash_value.clone(),
+                used,
+            };
+            self.records.insert(key.clone(), record.clone());
+            self.updated.insert(key, record);
+        }
+    }
+
+    /// キャッシュから読んだ記録の使った日時を新しくして、捨てられないようにする
+    fn touch(&mut self, stamp: &Stamp, digest_algorithms: &[DigestAlgorithm]) {
+        let now = SystemTime::now();
+        let threshold = seconds(now - TOUCH_INTERVAL);
+        for digest_algorithm in digest_algorithms {
+            let key = (stamp.id.clone(), digest_algorithm.name());
+            if let Some(record) = self
+                .records
+                .get_mut(&key)
+                .filter(|record| record.used < threshold)
+            {
+                record.used = seconds(now);
+                self.updated.insert(key, record.clone());
+            }
+        }
+    }
+
+    /// キャッシュから読んだハッシュ値を、計算し直して確かめるか
+    fn should_verify(&self) -> bool {
+        self.verify_percent
+            .is_some_and(|percent| fastrand::f64() * 100.0 < percent)
+    }
+
+    /// # キャッシュファイルに書き込む関数
+    /// ほかのプロセスが同時に書いても記録を失わないように、ロックしてから読み直して加えます。
+    /// 長い間使わなかった記録は、ここで捨てます。
+    pub fn save(&mut self) -> Result<(), std::io::Error> {
+        if self.updated.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let lock_file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(suffixed(&self.path, ".lock"))?;
+        lock_file.lock_exclusive()?;
+
+        let mut records = read(&self.path)?;
+        records.append(&mut self.updated);
+        let threshold = seconds(SystemTime::now() - PRUNE_AFTER);
+        records.retain(|_, record| record.used >= threshold);
+        let temp_path = suffixed(&self.path, &format!(".{}.tmp", std::process::id()));
+        let result = std::fs::write(&temp_path, format(&records))
+            .and_then(|_| std::fs::rename(&temp_path, &self.path));
+        if result.is_err() {
+            std::fs::remove_file(&temp_path).ok();
+        }
+        debug!("キャッシュ: {}件を書き込みました", records.len());
+        self.records = records;
+        lock_file.unlock()?;
+        result
+    }
+}
+
+/// pathの名前の後に文字列を付けたパス
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// キャッシュファイルを読む ファイルがなければ空
+fn read(path: &Path) -> Result<BTreeMap<Key, Record>, std::io::Error> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(parse(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        // 壊れて文字列として読めないキャッシュは、作り直す
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => Ok(BTreeMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// # キャッシュファイルの中身を読む関数
+/// 1行目が違えば、すべて読み飛ばします。
+fn parse(content: &str) -> BTreeMap<Key, Record> {
+    let mut lines = content.lines();
+    if lines.next() != Some(FILE_HEADER) {
+        return BTreeMap::new();
+    }
+    lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(7, '\t').collect();
+            match fields[..] {
+                [algorithm, size, modified, changed, used, hash_value, id] if !id.is_empty() => {
+                    let digest_algorithm = DigestAlgorithm::from_name(algorithm)?;
+                    let hash_value = hash::from_hex(hash_value).filter(|hash_value| {
+                        hash_value.len() == digest_algorithm.digest_length()
+                    })?;
+                    let stamp = Stamp {
+                        id: id.to_string(),
+                        size: size.parse().ok()?,
+                        modified: modified.parse().ok()?,
+                        changed: changed.parse().ok()?,
+                    };
+                    Some((
+                        (id.to_string(), digest_algorithm.name()),
+                        Record {
+                            stamp,
+                            hash_value,
+                            used: used.parse().ok()?,
+                        },
+                    ))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// キャッシュファイルの中身を書き出す
+fn format(records: &BTreeMap<Key, Record>) -> String {
+    let mut content = format!("{}\n", FILE_HEADER);
+    for ((_, algorithm), record) in records {
+        content.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            algorithm,
+            record.stamp.size,
+            record.stamp.modified,
+            record.stamp.changed,
+            record.used,
+            hash::to_hex(&record.hash_value),
+            record.stamp.id
+        ));
+    }
+    content
+}
+
+/// # キャッシュファイルのデフォルトのパス
+/// Windowsは`%LOCALAPPDATA%\digest_tool\cache`、
+/// それ以外は`$XDG_CACHE_HOME/digest_tool/cache`(未設定なら`~/.cache/digest_tool/cache`)
+#[cfg(target_os = "windows")]
+pub fn default_path() -> Option<PathBuf> {
+    std::env::var_os("LOCALAPPDATA")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join("digest_tool").join("cache"))
+}
+
+/// # キャッシュファイルのデフォルトのパス
+/// Windowsは`%LOCALAPPDATA%\digest_tool\cache`、
+/// それ以外は`$XDG_CACHE_HOME/digest_tool/cache`(未設定なら`~/.cache/digest_tool/cache`)
+#[cfg(not(target_os = "windows"))]
+pub fn default_path() -> Option<PathBuf> {
+    let cache_dir = match std::env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            PathBuf::from(std::env::var_os("HOME").filter(|dir| !dir.is_empty())?).join(".cache")
+        }
+    };
+    Some(cache_dir.join("digest_tool").join("cache"))
+}
+
+/// # キャッシュを開く関数
+/// 起動時に一度だけ呼びます。呼ばなければキャッシュを使いません。
+/// キャッシュは計算を省くためのものなので、読めなくても警告だけにして、キャッシュなしで続けます。
+pub fn init(path: PathBuf, verify_percent: Option<f64>) {
+    match Cache::open(path, verify_percent) {
+        Ok(cache) => {
+            CACHE.set(Mutex::new(cache)).ok();
+        }
+        Err(e) => eprintln!("{}", t!("cache.open_failed", error = e)),
+    }
+}
+
+/// # キャッシュを保存する関数
+/// 終了前に呼びます。計算し直して確かめた件数も表示します。
+/// 保存できなくても、計算した結果は正しいので、警告だけにします。
+pub fn finish() {
+    let mut cache = match CACHE.get() {
+        Some(cache) => lock(cache),
+        None => return,
+    };
+    debug!(
+        "キャッシュ: {}件をキャッシュから読みました。{}件を計算し直しました。",
+        cache.hits, cache.verified
+    );
+    if cache.verified > 0 {
+        eprintln!(
+            "{}",
+            t!(
+                "cache.verify_summary",
+                verified = cache.verified,
+                mismatched = cache.mismatched
+            )
+        );
+    }
+    if let Err(e) = cache.save() {
+        eprintln!("{}", t!("cache.save_failed", error = e));
+    }
+}
+
+/// ほかのスレッドが書き込み中に止まっても、記録はそのまま使える
+fn lock(cache: &Mutex<Cache>) -> MutexGuard<'_, Cache> {
+    cache.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// # キャッシュを使ってハッシュ値を計算する関数
+/// キャッシュを開いていなければ、そのまま計算します。
+/// ファイルの状態が記録と同じならファイルを読まずに記録を返し、違えば計算して記録します。
+/// `--verify_cache`の割合で、記録があっても計算し直し、記録と違えば警告します。
+/// ## 引数
+/// - ファイルのパス
+/// - ハッシュアルゴリズム
+/// - プログレスバー 記録を返したときは完了にします
+/// - ハッシュ値を計算する関数
+/// ## 返り値
+/// Result型 std::ioのError あるいは、読み込んだバイト数とアルゴリズムごとのハッシュ値
+#[allow(clippy::type_complexity)]
+pub fn hash_file(
+    path: &Path,
+    digest_algorithms: &[DigestAlgorithm],
+    progress_bar: &indicatif::ProgressBar,
+    compute: impl FnOnce() -> Result<(u64, Vec<(DigestAlgorithm, Vec<u8>)>), std::io::Error>,
+) -> Result<(u64, Vec<(DigestAlgorithm, Vec<u8>)>), std::io::Error> {
+    let cache = match CACHE.get() {
+        Some(cache) => cache,
+        None => return compute(),
+    };
+    let stamp = match Stamp::of(path) {
+        Ok(stamp) => stamp,
+        Err(_) => return compute(),
+    };
+    let started = SystemTime::now();
+
+    let cached = {
+        let mut cache = lock(cache);
+        match cache.lookup(&stamp, digest_algorithms) {
+            Some(hash_values) if !cache.should_verify() => {
+                cache.hits += 1;
+                cache.touch(&stamp, digest_algorithms);
+                progress_bar.finish();
+                return Ok((stamp.size, hash_values));
+            }
+            cached => cached,
+        }
+    };
+
+    let (size, hash_values) = compute()?;
+    // 計算している間に書き換えられたファイルは記録しない
+    if Stamp::of(path).is_ok_and(|after| after == stamp) && !stamp.is_racy(started) {
+        let mut cache = lock(cache);
+        if let Some(cached) = cached {
+            cache.verified += 1;
+            if cached != hash_values {
+                cache.mismatched += 1;
+                eprintln!("{}", t!("cache.verify_mismatch", path = path.display()));
+            }
+        }
+        cache.store(&stamp, &hash_values);
+    }
+    Ok((size, hash_values))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("digest_tool_cache_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn stamp(id: &str, size: u64) -> Stamp {
+        Stamp {
+            id: id.to_string(),
+            size,
+            modified: 1_700_000_000_123_456_789,
+            changed: 1_700_000_000_123_456_789,
+        }
+    }
+
+    /// 書き出した記録をそのまま読める
+    #[test]
+    fn ok_parse() {
+        let mut records = BTreeMap::new();
+        records.insert(
+            ("2049:12".to_string(), "md5"),
+            Record {
+                stamp: stamp("2049:12", 3),
+                hash_value: vec![0xab; 16],
+                used: 1_700_000_000,
+            },
+        );
+        records.insert(
+            ("C:\\data\\a\tb.txt".to_string(), "sha2_256"),
+            Record {
+                stamp: stamp("C:\\data\\a\tb.txt", 0),
+                hash_value: vec![0; 32],
+                used: 1_700_000_000,
+            },
+        );
+        assert_eq!(records, parse(&format(&records)));
+    }
+
+    /// 1行目が違うファイルは読まず、形式が違う行は読み飛ばす
+    #[test]
+    fn ng_parse() {
+        // 使った日時がない以前の版も読まない
+        assert!(parse(&format!(
+            "digest_tool cache 1\nmd5\t3\t0\t0\t{}\t1:2\n",
+            "00".repeat(16)
+        ))
+        .is_empty());
+        let records = parse(&format!(
+            "{}\nmd5\t3\t0\t0\t0\t00\t1:2\nmd6\t3\t0\t0\t0\t{}\t1:2\nmd5\tx\t0\t0\t0\t{}\t1:2\nmd5\t3\t0\t0\t0\t{}\t1:3\n",
+            FILE_HEADER,
+            "00".repeat(16),
+            "00".repeat(16),
+            "00".repeat(16)
+        ));
+        assert_eq!(1, records.len());
+        assert!(records.contains_key(&("1:3".to_string(), "md5")));
+    }
+
+    /// ファイルの状態が同じで、すべてのアルゴリズムの記録があるときだけ記録を返す
+    #[test]
+    fn ok_lookup() {
+        let dir = temp_dir("lookup");
+        let mut cache = Cache::open(dir.join("cache"), None).unwrap();
+        let hash_values = vec![(DigestAlgorithm::Md5, vec![1; 16])];
+        cache.store(&stamp("1:2", 3), &hash_values);
+
+        assert_eq!(
+            Some(hash_values),
+            cache.lookup(&stamp("1:2", 3), &[DigestAlgorithm::Md5])
+        );
+        assert_eq!(
+            None,
+            cache.lookup(&stamp("1:2", 4), &[DigestAlgorithm::Md5])
+        );
+        assert_eq!(
+            None,
+            cache.lookup(
+                &stamp("1:2", 3),
+                &[DigestAlgorithm::Md5, DigestAlgorithm::Sha1]
+            )
+        );
+        let mut touched = stamp("1:2", 3);
+        touched.changed += 1;
+        assert_eq!(None, cache.lookup(&touched, &[DigestAlgorithm::Md5]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 同じキャッシュファイルに別々に書いても、両方の記録が残る
+    #[test]
+    fn ok_save_merge() {
+        let dir = temp_dir("merge");
+        let path = dir.join("sub").join("cache");
+        let mut first = Cache::open(path.clone(), None).unwrap();
+        let mut second = Cache::open(path.clone(), None).unwrap();
+        first.store(&stamp("1:2", 3), &[(DigestAlgorithm::Md5, vec![1; 16])]);
+        second.store(&stamp("1:3", 3), &[(DigestAlgorithm::Md5, vec![2; 16])]);
+        first.save().unwrap();
+        second.save().unwrap();
+
+        let cache = Cache::open(path, None).unwrap();
+        assert_eq!(2, cache.records.len());
+        assert!(cache
+            .lookup(&stamp("1:2", 3), &[DigestAlgorithm::Md5])
+            .is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 長い間使わなかった記録は、書き込むときに捨てる
+    #[test]
+    fn ok_save_prune() {
+        let dir = temp_dir("prune");
+        let path = dir.join("cache");
+        let mut cache = Cache::open(path.clone(), None).unwrap();
+        cache.store(&stamp("1:2", 3), &[(DigestAlgorithm::Md5, vec![1; 16])]);
+        let stale = seconds(SystemTime::now() - PRUNE_AFTER - Duration::from_secs(60));
+        cache.updated.insert(
+            ("1:3".to_string(), "md5"),
+            Record {
+                stamp: stamp("1:3", 3),
+                hash_value: vec![2; 16],
+                used: stale,
+            },
+        );
+        cache.save().unwrap();
+
+        let mut cache = Cache::open(path, None).unwrap();
+        assert_eq!(1, cache.records.len());
+        assert!(cache.records.contains_key(&("1:2".to_string(), "md5")));
+
+        // 読んだ記録は、使った日時を新しくして残す
+        let key = ("1:2".to_string(), "md5");
+        cache.records.get_mut(&key).unwrap().used = stale;
+        cache.touch(&stamp("1:2", 3), &[DigestAlgorithm::Md5]);
+        assert!(cache.updated[&key].used > stale);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 計算を始めたときに近い日時に更新されたファイルは信用しない
+    #[test]
+    fn ok_is_racy() {
+        let started = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_010);
+        assert!(!stamp("1:2", 3).is_racy(started));
+        assert!(stamp("1:2", 3).is_racy(started - Duration::from_secs(9)));
+    }
+
+    /// 同じファイルは同じ状態になり、書き換えると状態が変わる
+    #[test]
+    fn ok_stamp() {
+        let dir = temp_dir("stamp");
+        let path = dir.join("a.txt");
+        std::fs::write(&path, b"abc").unwrap();
+        let before = Stamp::of(&path).unwrap();
+        assert_eq!(before, Stamp::of(&path).unwrap());
+        assert_eq!(3, before.size);
+        std::fs::write(&path, b"abcd").unwrap();
+        assert_ne!(before, Stamp::of(&path).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
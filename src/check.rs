@@ -0,0 +1,177 @@
+//! # 検証モジュール
+//! チェックサムファイルに記載されたファイルのハッシュ値を計算し、記載されたハッシュ値と一致するか確かめます。
+
+use crate::checksum_file::ChecksumEntry;
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::multi_file::hash_file;
+use crate::text_normalize::TextNormalization;
+
+/// 検証結果の種類
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckStatus {
+    /// ハッシュ値が一致した
+    Ok,
+    /// ハッシュ値が一致しなかった、あるいはファイルを読み込めなかった
+    Failed,
+    /// ファイルが見つからなかった
+    Missing,
+}
+
+impl CheckStatus {
+    /// 結果の表示に使う名前
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Failed => "FAILED",
+            CheckStatus::Missing => "MISSING",
+        }
+    }
+}
+
+/// 1ファイル、1ハッシュアルゴリズム分の検証結果
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// チェックサムファイルに書かれたままのパス
+    pub path: String,
+    pub digest_algorithm: DigestAlgorithm,
+    pub status: CheckStatus,
+    pub expected: Vec<u8>,
+    /// 読み込んだバイト数 ファイルを読み込めなかった場合はNone
+    pub size: Option<u64>,
+    /// 計算したハッシュ値 ファイルを読み込めなかった場合はNone
+    pub actual: Option<Vec<u8>>,
+    /// ファイルを読み込めなかった理由
+    pub error: Option<String>,
+}
+
+/// 検証結果の件数
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CheckSummary {
+    pub ok: usize,
+    pub failed: usize,
+    pub missing: usize,
+}
+
+impl CheckSummary {
+    pub fn add(&mut self, status: CheckStatus) {
+        match status {
+            CheckStatus::Ok => self.ok += 1,
+            CheckStatus::Failed => self.failed += 1,
+            CheckStatus::Missing => self.missing += 1,
+        }
+    }
+
+    /// すべて一致したか
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.missing == 0
+    }
+}
+
+/// # 検証関数
+/// チェックサムファイルの1ファイル分の記載を検証します。
+/// ファイルは一度だけ読み込み、記載されたすべてのハッシュアルゴリズムで確かめます。
+/// ## 引数
+/// - チェックサムファイルの記載
+/// - 相対パスの基準になるディレクトリ
+/// - テキスト正規化
+/// - プログレスバー
+/// ## 返り値
+/// ハッシュアルゴリズムごとの検証結果
+pub fn check_entry(
+    entry: &ChecksumEntry,
+    base_dir: &std::path::Path,
+    text_normalization: Option<&TextNormalization>,
+    progress_bar: indicatif::ProgressBar,
+) -> Vec<CheckResult> {
+    let path = base_dir.join(&entry.path);
+    let digest_algorithms: Vec<DigestAlgorithm> = entry
+        .digests
+        .iter()
+        .map(|(digest_algorithm, _)| *digest_algorithm)
+        .collect();
+
+    let hash_result = match std::fs::metadata(&path) {
+        Ok(metadata) if entry.size.is_some_and(|size| size != metadata.len()) => {
+            // サイズが違えばハッシュ値を計算するまでもない
+            Err((
+                CheckStatus::Failed,
+                t!("check.size_mismatch", size = metadata.len()),
+            ))
+        }
+        Ok(metadata) => {
+            progress_bar.set_length(metadata.len());
+            hash_file(&path, &digest_algorithms, text_normalization, progress_bar)
+                .map_err(|e| (CheckStatus::Failed, e.to_string()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err((CheckStatus::Missing, e.to_string()))
+        }
+        Err(e) => Err((CheckStatus::Failed, e.to_string())),
+    };
+
+    entry
+        .digests
+        .iter()
+        .map(|(digest_algorithm, expected)| match &hash_result {
+            Ok((size, hash_values)) => {
+                let actual = hash_values
+                    .iter()
+                    .find(|(algorithm, _)| algorithm == digest_algorithm)
+                    .map(|(_, hash_value)| hash_value.clone());
+                let status = if actual.as_ref() == Some(expected) {
+                    CheckStatus::Ok
+                } else {
+                    CheckStatus::Failed
+                };
+                CheckResult {
+                    path: entry.path.clone(),
+                    digest_algorithm: *digest_algorithm,
+                    status,
+                    expected: expected.clone(),
+                    size: Some(*size),
+                    actual,
+                    error: None,
+                }
+            }
+            Err((status, error)) => CheckResult {
+                path: entry.path.clone(),
+                digest_algorithm: *digest_algorithm,
+                status: *status,
+                expected: expected.clone(),
+                size: None,
+                actual: None,
+                error: Some(error.clone()),
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash;
+
+    /// 一致、不一致、見つからないファイルを判定できる
+    #[test]
+    fn ok_check_entry() {
+        let base_dir =
+            std::env::temp_dir().join(format!("digest_tool_check_{}", std::process::id()));
+        std::fs::create_dir_all(&base_dir).unwrap();
+        std::fs::write(base_dir.join("empty.txt"), b"").unwrap();
+        let empty_sha256 = hash::digest_bytes(b"", DigestAlgorithm::Sha2_256);
+
+        let check = |path: &str, hash_value: &[u8]| {
+            let entry = ChecksumEntry {
+                path: path.to_string(),
+                size: None,
+                digests: vec![(DigestAlgorithm::Sha2_256, hash_value.to_vec())],
+            };
+            check_entry(&entry, &base_dir, None, indicatif::ProgressBar::hidden())[0].status
+        };
+        assert_eq!(CheckStatus::Ok, check("empty.txt", &empty_sha256));
+        assert_eq!(CheckStatus::Failed, check("empty.txt", &[0; 32]));
+        assert_eq!(CheckStatus::Missing, check("missing.txt", &empty_sha256));
+
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+}
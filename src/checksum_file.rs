@@ -0,0 +1,706 @@
+//! # チェックサムファイルモジュール
+//! `SHA256SUMS`のようなチェックサムファイルを読み書きします。
+//! 以下の形式の行を扱えます。
+//! - GNU形式 `<hash>  <path>` (バイナリモードは`<hash> *<path>`)
+//! - BSD形式 `SHA256 (<path>) = <hash>`
+//! - SFV形式 `<path> <CRC32>`
+//! - hashdeep形式 (1行目が`%%%% HASHDEEP-1.0`のファイル 詳しくはhashdeepモジュール)
+//! - JSON形式 `[{"path": "...", "size": 5, "algorithm": "sha2_256", "digest": "..."}]` (sizeは省略可)
+//!
+//! GNU形式とBSD形式は、パスに改行やバックスラッシュを含む行は先頭に`\`を付けてエスケープします(GNU coreutilsと同じ)。
+//! `#`と`;`で始まる行はコメントとして読み飛ばしますが、`# normalize: <SPEC>`はテキスト正規化の指定として扱います。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use crate::hashdeep;
+use crate::text_normalize::TextNormalization;
+use serde::{Deserialize, Serialize};
+
+/// テキスト正規化の指定を書くコメントの接頭辞
+pub const NORMALIZE_COMMENT_PREFIX: &str = "# normalize:";
+
+/// チェックサムファイルの形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChecksumFormat {
+    Gnu,
+    Bsd,
+    Sfv,
+    Hashdeep,
+    Json,
+}
+
+impl ChecksumFormat {
+    /// CLI引数で指定する名前から形式を取得します。
+    pub fn from_name(name: &str) -> Option<ChecksumFormat> {
+        match name {
+            "gnu" => Some(ChecksumFormat::Gnu),
+            "bsd" => Some(ChecksumFormat::Bsd),
+            "sfv" => Some(ChecksumFormat::Sfv),
+            "hashdeep" => Some(ChecksumFormat::Hashdeep),
+            "json" => Some(ChecksumFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// チェックサムファイルの1ファイル分の記載
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecksumEntry {
+    /// チェックサムファイルに書かれたままのパス
+    pub path: String,
+    /// ファイルサイズ 形式によっては記載されない
+    pub size: Option<u64>,
+    /// (ハッシュアルゴリズム, ハッシュ値)の配列
+    pub digests: Vec<(DigestAlgorithm, Vec<u8>)>,
+}
+
+/// 読み込んだチェックサムファイル
+#[derive(Debug, Default)]
+pub struct ChecksumFile {
+    pub entries: Vec<ChecksumEntry>,
+    /// 形式が誤っていて読み込めなかった行の数
+    pub malformed_lines: usize,
+    /// `# normalize:`コメントで指定されたテキスト正規化
+    pub text_normalization: Option<TextNormalization>,
+}
+
+/// # チェックサムファイルの読み込み関数
+/// ## 引数
+/// - チェックサムファイルの内容
+/// - GNU形式の行のハッシュアルゴリズム Noneのときはハッシュ値の長さから推定します
+/// ## 返り値
+/// 読み込んだチェックサムファイル
+pub fn parse(content: &str, digest_algorithm: Option<DigestAlgorithm>) -> ChecksumFile {
+    if let Some(checksum_file) = hashdeep::parse(content) {
+        return checksum_file;
+    }
+    // `[Group] a.txt`のような名前で始まるSFV形式もあるので、JSONとして読めたときだけJSON形式とみなす
+    if let Ok(values) = serde_json::from_str::<Vec<serde_json::Value>>(content) {
+        return parse_json(values);
+    }
+    let mut checksum_file = ChecksumFile::default();
+    for line in content.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(spec) = line.strip_prefix(NORMALIZE_COMMENT_PREFIX) {
+            if spec.trim().is_empty() {
+                continue;
+            }
+            match spec.trim().parse::<TextNormalization>() {
+                Ok(text_normalization) if text_normalization.is_noop() => {}
+                Ok(text_normalization) => {
+                    checksum_file.text_normalization = Some(text_normalization)
+                }
+                Err(_) => checksum_file.malformed_lines += 1,
+            }
+            continue;
+        }
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        let entry = parse_bsd_line(line)
+            .or_else(|| parse_gnu_line(line, digest_algorithm))
+            .or_else(|| parse_sfv_line(line));
+        match entry {
+            Some(entry) => checksum_file.entries.push(entry),
+            None => checksum_file.malformed_lines += 1,
+        }
+    }
+    checksum_file
+}
+
+/// BSD形式の行を読み込みます。
+fn parse_bsd_line(line: &str) -> Option<ChecksumEntry> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let path_start = line.find(" (")?;
+    let path_end = line.rfind(") = ")?;
+    if path_end < path_start + 2 {
+        return None;
+    }
+    let digest_algorithm = DigestAlgorithm::from_bsd_tag(&line[..path_start])?;
+    let hash_value = hash::from_hex(&line[path_end + 4..])?;
+    if hash_value.len() != digest_algorithm.digest_length() {
+        return None;
+    }
+    let path = &line[path_start + 2..path_end];
+    let path = if escaped {
+        unescape_path(path)?
+    } else {
+        path.to_string()
+    };
+    Some(ChecksumEntry {
+        path,
+        size: None,
+        digests: vec![(digest_algorithm, hash_value)],
+    })
+}
+
+/// GNU形式の行を読み込みます。
+fn parse_gnu_line(line: &str, digest_algorithm: Option<DigestAlgorithm>) -> Option<ChecksumEntry> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let separator = line.find(' ')?;
+    let hash_value = hash::from_hex(&line[..separator])?;
+    // 区切りの空白の次はモードを表す文字 ' 'はテキスト、'*'はバイナリ
+    let rest = &line[separator + 1..];
+    let path = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    if path.is_empty() {
+        return None;
+    }
+    let digest_algorithm =
+        digest_algorithm.or_else(|| DigestAlgorithm::from_digest_length(hash_value.len()))?;
+    if hash_value.len() != digest_algorithm.digest_length() {
+        return None;
+    }
+    let path = if escaped {
+        unescape_path(path)?
+    } else {
+        path.to_string()
+    };
+    Some(ChecksumEntry {
+        path,
+        size: None,
+        digests: vec![(digest_algorithm, hash_value)],
+    })
+}
+
+/// SFV形式の行を読み込みます。パスとCRC32の間は最後の空白で区切ります。
+fn parse_sfv_line(line: &str) -> Option<ChecksumEntry> {
+    let separator = line.rfind(' ')?;
+    let path = line[..separator].trim_end();
+    let crc = &line[separator + 1..];
+    if path.is_empty() || crc.len() != 8 {
+        return None;
+    }
+    let hash_value = hash::from_hex(crc)?;
+    Some(ChecksumEntry {
+        path: path.to_string(),
+        size: None,
+        digests: vec![(DigestAlgorithm::Crc32, hash_value)],
+    })
+}
+
+/// JSON形式の1ハッシュ値分の記載
+#[derive(Serialize, Deserialize)]
+struct JsonRecord {
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    /// `-d`で指定する名前
+    algorithm: String,
+    /// 小文字の16進数
+    digest: String,
+}
+
+/// JSON形式のチェックサムファイルを読み込みます。読み込めない記載は形式の誤りとして数えます。
+fn parse_json(values: Vec<serde_json::Value>) -> ChecksumFile {
+    let mut checksum_file = ChecksumFile::default();
+    for value in values {
+        let entry = serde_json::from_value::<JsonRecord>(value)
+            .ok()
+            .and_then(|record| {
+                let digest_algorithm = DigestAlgorithm::from_name(&record.algorithm)?;
+                let hash_value = hash::from_hex(&record.digest)?;
+                if hash_value.len() != digest_algorithm.digest_length() || record.path.is_empty() {
+                    return None;
+                }
+                Some(ChecksumEntry {
+                    path: record.path,
+                    size: record.size,
+                    digests: vec![(digest_algorithm, hash_value)],
+                })
+            });
+        match entry {
+            Some(entry) => checksum_file.entries.push(entry),
+            None => checksum_file.malformed_lines += 1,
+        }
+    }
+    checksum_file
+}
+
+/// # チェックサムファイルを作る関数
+/// 記載を指定された形式のファイルの内容にします。ハッシュ値を計算し直すことはありません。
+/// ## 引数
+/// - 記載 同じパスの記載が複数あってもかまいません
+/// - 形式
+/// - テキスト正規化 GNU形式とBSD形式だけ`# normalize:`コメントとして書けます
+/// ## 返り値
+/// Result型 その形式で表せない場合はエラーメッセージ あるいは、ファイルの内容
+pub fn format_file(
+    entries: &[ChecksumEntry],
+    format: ChecksumFormat,
+    text_normalization: Option<&TextNormalization>,
+) -> Result<String, String> {
+    let mut content = String::new();
+    // 何も変えない指定は、空の`# normalize:`にならないように書かない
+    let text_normalization =
+        text_normalization.filter(|text_normalization| !text_normalization.is_noop());
+    match (format, text_normalization) {
+        (ChecksumFormat::Gnu, Some(text_normalization))
+        | (ChecksumFormat::Bsd, Some(text_normalization)) => content.push_str(&format!(
+            "{} {}\n",
+            NORMALIZE_COMMENT_PREFIX, text_normalization
+        )),
+        (_, Some(_)) => return Err(t!("manifest.normalization_unsupported")),
+        (_, None) => {}
+    }
+
+    match format {
+        ChecksumFormat::Json => {
+            let records: Vec<JsonRecord> = entries
+                .iter()
+                .flat_map(|entry| {
+                    entry
+                        .digests
+                        .iter()
+                        .map(move |(digest_algorithm, hash_value)| JsonRecord {
+                            path: entry.path.clone(),
+                            size: entry.size,
+                            algorithm: digest_algorithm.name().to_string(),
+                            digest: hash::to_hex(hash_value),
+                        })
+                })
+                .collect();
+            content.push_str(&serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?);
+            content.push('\n');
+        }
+        ChecksumFormat::Hashdeep => {
+            // hashdeep形式は1ファイル1行で、すべての行に同じアルゴリズムのハッシュ値が必要
+            let entries = merge_entries(entries);
+            let digest_algorithms: Vec<DigestAlgorithm> = match entries.first() {
+                Some(entry) => entry
+                    .digests
+                    .iter()
+                    .map(|(algorithm, _)| *algorithm)
+                    .collect(),
+                None => vec![DigestAlgorithm::Md5, DigestAlgorithm::Sha2_256],
+            };
+            let invoked_from = std::env::current_dir()
+                .map(|dir| dir.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let command_line: Vec<String> = std::env::args().collect();
+            content.push_str(&hashdeep::header(
+                &digest_algorithms,
+                &invoked_from,
+                &command_line.join(" "),
+            )?);
+            for entry in entries.iter() {
+                let same_algorithms = entry
+                    .digests
+                    .iter()
+                    .map(|(algorithm, _)| *algorithm)
+                    .eq(digest_algorithms.iter().copied());
+                if !same_algorithms {
+                    return Err(t!("hashdeep.same_algorithms_required", path = entry.path));
+                }
+                content.push_str(&format_entry(entry, format)?);
+            }
+        }
+        ChecksumFormat::Gnu => {
+            // GNU形式の行にはアルゴリズムを書かないので、sha256sum -cなどは1種類のハッシュ値しか読めない
+            let mut digest_algorithms = entries
+                .iter()
+                .flat_map(|entry| entry.digests.iter().map(|(algorithm, _)| *algorithm));
+            if let Some(first) = digest_algorithms.next() {
+                if digest_algorithms.any(|algorithm| algorithm != first) {
+                    return Err(t!("manifest.gnu_single_algorithm"));
+                }
+                gnu_algorithm(first)?;
+            }
+            for entry in entries.iter() {
+                content.push_str(&format_entry(entry, format)?);
+            }
+        }
+        _ => {
+            for entry in entries.iter() {
+                content.push_str(&format_entry(entry, format)?);
+            }
+        }
+    }
+    Ok(content)
+}
+
+/// # GNU形式に書けるアルゴリズムか確かめる関数
+/// GNU形式の行にはアルゴリズムを書かないので、読むときはハッシュ値の長さから推定します。
+/// 推定し直せないアルゴリズム(SHA-3など、同じ長さのSHA-2と区別できないもの)は、書いても検証できません。
+/// ## 返り値
+/// Result型 書けなければエラーメッセージ
+pub fn gnu_algorithm(digest_algorithm: DigestAlgorithm) -> Result<(), String> {
+    if DigestAlgorithm::from_digest_length(digest_algorithm.digest_length())
+        == Some(digest_algorithm)
+    {
+        Ok(())
+    } else {
+        Err(t!(
+            "manifest.gnu_ambiguous_algorithm",
+            algorithm = digest_algorithm.bsd_tag()
+        ))
+    }
+}
+
+/// 同じパスの記載を、最初に現れた位置に1つにまとめます。
+fn merge_entries(entries: &[ChecksumEntry]) -> Vec<ChecksumEntry> {
+    let mut merged: Vec<ChecksumEntry> = Vec::new();
+    for entry in entries {
+        match merged.iter_mut().find(|other| other.path == entry.path) {
+            Some(other) => {
+                other.size = other.size.or(entry.size);
+                for digest in entry.digests.iter() {
+                    if !other
+                        .digests
+                        .iter()
+                        .any(|(algorithm, _)| *algorithm == digest.0)
+                    {
+                        other.digests.push(digest.clone());
+                    }
+                }
+            }
+            None => merged.push(entry.clone()),
+        }
+    }
+    merged
+}
+
+/// # チェックサムファイルの行を作る関数
+/// 1ファイル分の記載を、指定された形式の行にします。ハッシュ値ごとに1行になります。
+/// hashdeep形式だけは、すべてのハッシュ値を1行にまとめます。
+/// ## 返り値
+/// Result型 その形式で表せない場合はエラーメッセージ あるいは、改行を含む行
+fn format_entry(entry: &ChecksumEntry, format: ChecksumFormat) -> Result<String, String> {
+    // エスケープした行は行頭に`\\`を付ける
+    let escaped = escape_path(&entry.path);
+    let (prefix, path) = match escaped.strip_prefix('\\') {
+        Some(path) if escaped != entry.path => ("\\", path),
+        _ => ("", escaped.as_str()),
+    };
+
+    let mut lines = String::new();
+    for (digest_algorithm, hash_value) in entry.digests.iter() {
+        let line = match format {
+            ChecksumFormat::Gnu => format!("{}{}  {}", prefix, hash::to_hex(hash_value), path),
+            ChecksumFormat::Bsd => format!(
+                "{}{} ({}) = {}",
+                prefix,
+                digest_algorithm.bsd_tag(),
+                path,
+                hash::to_hex(hash_value)
+            ),
+            ChecksumFormat::Sfv => {
+                if *digest_algorithm != DigestAlgorithm::Crc32 {
+                    return Err(t!("manifest.sfv_crc32_only"));
+                }
+                if entry.path.contains(['\n', '\r']) || entry.path.starts_with(';') {
+                    return Err(t!(
+                        "sfv.unrepresentable_path",
+                        path = format!("{:?}", entry.path)
+                    ));
+                }
+                format!("{} {}", entry.path, hash::to_hex(hash_value).to_uppercase())
+            }
+            // すべてのハッシュ値で1行
+            ChecksumFormat::Hashdeep => return hashdeep::format_line(entry),
+            ChecksumFormat::Json => unreachable!("JSON形式はformat_fileでまとめて書きます"),
+        };
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+/// パスに改行やバックスラッシュが含まれていればエスケープし、先頭に`\`を付けて返却します。
+/// GNU coreutilsが結果を表示するときと同じ形になります。
+pub fn escape_path(path: &str) -> String {
+    if !path.contains(['\\', '\n', '\r']) {
+        return path.to_string();
+    }
+    let escaped = path
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\\{}", escaped)
+}
+
+/// `\\`、`\n`、`\r`のエスケープを元に戻します。
+fn unescape_path(path: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+/// # ファイル名からハッシュアルゴリズムを推定する関数
+/// `SHA512SUMS`や`foo.iso.sha512`のようなファイル名から、GNU形式の行のハッシュアルゴリズムを推定します。
+pub fn digest_algorithm_from_file_name(path: &std::path::Path) -> Option<DigestAlgorithm> {
+    let file_name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+    let names = [
+        ("sha3-256", DigestAlgorithm::Sha3_256),
+        ("sha3_256", DigestAlgorithm::Sha3_256),
+        ("sha3-512", DigestAlgorithm::Sha3_512),
+        ("sha3_512", DigestAlgorithm::Sha3_512),
+        ("sha256", DigestAlgorithm::Sha2_256),
+        ("sha512", DigestAlgorithm::Sha2_512),
+        ("sha1", DigestAlgorithm::Sha1),
+        ("md5", DigestAlgorithm::Md5),
+    ];
+    names
+        .iter()
+        .find(|(name, _)| file_name.contains(name))
+        .map(|(_, digest_algorithm)| *digest_algorithm)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SHA256_EMPTY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    /// GNU形式とBSD形式が混ざっていても読み込める
+    #[test]
+    fn ok_parse() {
+        let content = format!(
+            "# comment\n{hash}  a.txt\n{hash} *bin/b.bin\nSHA256 (c (1).txt) = {hash}\n\\{hash}  new\\nline\\\\.txt\nbroken line\n",
+            hash = SHA256_EMPTY
+        );
+        let checksum_file = parse(&content, None);
+
+        let paths: Vec<&str> = checksum_file
+            .entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        assert_eq!(
+            vec!["a.txt", "bin/b.bin", "c (1).txt", "new\nline\\.txt"],
+            paths
+        );
+        assert_eq!(1, checksum_file.malformed_lines);
+        assert_eq!(
+            DigestAlgorithm::Sha2_256,
+            checksum_file.entries[2].digests[0].0
+        );
+    }
+
+    /// GNU形式のアルゴリズムは指定があればそれを使い、ハッシュ値の長さと合わなければ読み込まない
+    #[test]
+    fn ok_parse_with_algorithm() {
+        let content = format!("{}  a.txt\n", SHA256_EMPTY);
+        let checksum_file = parse(&content, Some(DigestAlgorithm::Sha3_256));
+        assert_eq!(
+            DigestAlgorithm::Sha3_256,
+            checksum_file.entries[0].digests[0].0
+        );
+
+        let checksum_file = parse(&content, Some(DigestAlgorithm::Sha2_512));
+        assert!(checksum_file.entries.is_empty());
+        assert_eq!(1, checksum_file.malformed_lines);
+    }
+
+    /// `[`で始まる名前のSFV形式は、JSON形式と間違えずに読み込める
+    #[test]
+    fn ok_parse_bracket_sfv() {
+        let content = "[Group] a.txt 352441C2\n; comment\n[Group] b.txt 352441C2\n";
+        let checksum_file = parse(content, None);
+        assert_eq!(0, checksum_file.malformed_lines);
+        assert_eq!(
+            vec!["[Group] a.txt", "[Group] b.txt"],
+            checksum_file
+                .entries
+                .iter()
+                .map(|entry| entry.path.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(
+            DigestAlgorithm::Crc32,
+            checksum_file.entries[0].digests[0].0
+        );
+    }
+
+    /// 正規化の指定コメントを読み込める
+    #[test]
+    fn ok_parse_normalize_comment() {
+        let content = format!("# normalize: eol=lf\n{}  a.txt\n", SHA256_EMPTY);
+        let checksum_file = parse(&content, None);
+        assert_eq!(
+            "eol=lf",
+            checksum_file.text_normalization.unwrap().to_string()
+        );
+    }
+
+    /// 何も変えない正規化の指定は書かず、空の指定は正規化しないものとして読む
+    #[test]
+    fn ok_noop_normalize_comment() {
+        let entries = vec![ChecksumEntry {
+            path: "a.txt".to_string(),
+            size: None,
+            digests: vec![(
+                DigestAlgorithm::Sha2_256,
+                hash::from_hex(SHA256_EMPTY).unwrap(),
+            )],
+        }];
+        let normalization: TextNormalization = "bom=keep".parse().unwrap();
+        let content = format_file(&entries, ChecksumFormat::Gnu, Some(&normalization)).unwrap();
+        assert!(!content.contains(NORMALIZE_COMMENT_PREFIX));
+
+        let content = format!("# normalize: \n{}  a.txt\n", SHA256_EMPTY);
+        let checksum_file = parse(&content, None);
+        assert_eq!(None, checksum_file.text_normalization);
+        assert_eq!(0, checksum_file.malformed_lines);
+    }
+
+    /// 書き出した行を読み込むと元に戻る
+    #[test]
+    fn ok_format_round_trip() {
+        let hash_value = hash::from_hex(SHA256_EMPTY).unwrap();
+        for path in ["a b.txt", "dir/new\nline\\.txt"].iter() {
+            for format in [ChecksumFormat::Gnu, ChecksumFormat::Bsd].iter() {
+                let entry = ChecksumEntry {
+                    path: path.to_string(),
+                    size: None,
+                    digests: vec![(DigestAlgorithm::Sha2_256, hash_value.clone())],
+                };
+                let line = format_entry(&entry, *format).unwrap();
+                assert_eq!(vec![entry], parse(&line, None).entries, "{}", line);
+            }
+        }
+
+        let entry = ChecksumEntry {
+            path: "dir/a b.bin".to_string(),
+            size: None,
+            digests: vec![(DigestAlgorithm::Crc32, vec![0x0a, 0xbc, 0xde, 0xf0])],
+        };
+        let line = format_entry(&entry, ChecksumFormat::Sfv).unwrap();
+        assert_eq!("dir/a b.bin 0ABCDEF0\n", line);
+        assert_eq!(vec![entry], parse(&line, None).entries);
+    }
+
+    /// どの形式に変換しても、読み込むと同じ記載に戻る
+    #[test]
+    fn ok_convert_round_trip() {
+        let entries = vec![
+            ChecksumEntry {
+                path: "dir/a,b \"c\".txt".to_string(),
+                size: Some(0),
+                digests: vec![
+                    (
+                        DigestAlgorithm::Md5,
+                        hash::digest_bytes(b"", DigestAlgorithm::Md5),
+                    ),
+                    (
+                        DigestAlgorithm::Sha2_256,
+                        hash::from_hex(SHA256_EMPTY).unwrap(),
+                    ),
+                ],
+            },
+            ChecksumEntry {
+                path: "back\\slash.txt".to_string(),
+                size: Some(3),
+                digests: vec![
+                    (
+                        DigestAlgorithm::Md5,
+                        hash::digest_bytes(b"abc", DigestAlgorithm::Md5),
+                    ),
+                    (
+                        DigestAlgorithm::Sha2_256,
+                        hash::digest_bytes(b"abc", DigestAlgorithm::Sha2_256),
+                    ),
+                ],
+            },
+        ];
+        // 1ハッシュ値1記載にしたもの サイズは形式によっては残らない
+        let split = |keep_size: bool| -> Vec<ChecksumEntry> {
+            entries
+                .iter()
+                .flat_map(|entry| {
+                    entry.digests.iter().map(move |digest| ChecksumEntry {
+                        path: entry.path.clone(),
+                        size: if keep_size { entry.size } else { None },
+                        digests: vec![digest.clone()],
+                    })
+                })
+                .collect()
+        };
+
+        let convert = |format: ChecksumFormat| {
+            let content = format_file(&entries, format, None).unwrap();
+            parse(&content, None).entries
+        };
+        assert_eq!(split(false), convert(ChecksumFormat::Bsd));
+        assert_eq!(split(true), convert(ChecksumFormat::Json));
+        assert_eq!(entries, convert(ChecksumFormat::Hashdeep));
+        // 1ハッシュ値1記載に分かれていても、hashdeep形式では1行にまとめる
+        let content = format_file(&split(true), ChecksumFormat::Hashdeep, None).unwrap();
+        assert_eq!(entries, parse(&content, None).entries);
+        // SFV形式にはCRC32しか書けない
+        assert!(format_file(&entries, ChecksumFormat::Sfv, None).is_err());
+        // GNU形式には1種類のアルゴリズムしか書けない
+        assert!(format_file(&entries, ChecksumFormat::Gnu, None).is_err());
+        let sha256_only: Vec<ChecksumEntry> = split(false)
+            .into_iter()
+            .filter(|entry| entry.digests[0].0 == DigestAlgorithm::Sha2_256)
+            .collect();
+        let content = format_file(&sha256_only, ChecksumFormat::Gnu, None).unwrap();
+        assert_eq!(sha256_only, parse(&content, None).entries);
+    }
+
+    /// GNU形式に書けたアルゴリズムは、長さから推定して同じアルゴリズムで読み直せる
+    #[test]
+    fn ok_gnu_round_trip() {
+        for digest_algorithm in DigestAlgorithm::ALL {
+            let entries = vec![ChecksumEntry {
+                path: "a.txt".to_string(),
+                size: None,
+                digests: vec![(
+                    digest_algorithm,
+                    hash::digest_bytes(b"abc", digest_algorithm),
+                )],
+            }];
+            match format_file(&entries, ChecksumFormat::Gnu, None) {
+                Ok(content) => assert_eq!(entries, parse(&content, None).entries),
+                Err(_) => assert!(matches!(
+                    digest_algorithm,
+                    DigestAlgorithm::Sha3_256 | DigestAlgorithm::Sha3_512
+                )),
+            }
+        }
+    }
+
+    /// エスケープしたパスを元に戻せる
+    #[test]
+    fn ok_escape_path() {
+        assert_eq!("a b.txt", escape_path("a b.txt"));
+        let escaped = escape_path("new\nline\\.txt");
+        assert_eq!("\\new\\nline\\\\.txt", escaped);
+        assert_eq!(
+            Some("new\nline\\.txt".to_string()),
+            unescape_path(&escaped[1..])
+        );
+    }
+
+    /// ファイル名からアルゴリズムを推定できる
+    #[test]
+    fn ok_digest_algorithm_from_file_name() {
+        let from = |name: &str| digest_algorithm_from_file_name(std::path::Path::new(name));
+        assert_eq!(Some(DigestAlgorithm::Sha2_512), from("SHA512SUMS"));
+        assert_eq!(Some(DigestAlgorithm::Sha2_256), from("foo.iso.sha256"));
+        assert_eq!(Some(DigestAlgorithm::Sha3_256), from("SHA3-256SUMS"));
+        assert_eq!(Some(DigestAlgorithm::Md5), from("foo.iso.md5"));
+        assert_eq!(None, from("CHECKSUMS"));
+    }
+}
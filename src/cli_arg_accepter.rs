@@ -1,14 +1,200 @@
 //! CLI引数を受け取るモジュール
 
 // Cli ArgumentParser
-use clap::*;
+use crate::archive::ArchiveFormat;
+use crate::checksum_file::ChecksumFormat;
+use crate::config::{self, Config};
+use crate::console;
+use crate::decompress::Compression;
+use crate::error::Error;
+use crate::expect::ExpectedDigest;
+use crate::generate::Target;
+use crate::i18n;
+use crate::output_format::OutputFormat;
+use crate::report::ReportFormat;
+use crate::text_input::{Newline, TextEncoding};
+use crate::text_normalize::TextNormalization;
+use crate::tree_digest::TreeDigestOption;
+use crate::watch;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{command, value_parser, Arg, ArgAction, ArgMatches, Command, ValueSource};
 use log::debug;
 
+/// ハッシュアルゴリズム 指定がないときはsha2_256
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum DigestAlgorithm {
+    #[default]
     Sha2_256,
     Sha2_512,
     Sha3_256,
     Sha3_512,
+    /// SFV形式で使う 改ざんの検出には使えません
+    Crc32,
+    /// hashdeepなど既存のツールとの互換性のため 改ざんの検出には使えません
+    Md5,
+    /// hashdeepなど既存のツールとの互換性のため 改ざんの検出には使えません
+    Sha1,
+}
+
+impl DigestAlgorithm {
+    /// すべてのハッシュアルゴリズム
+    pub const ALL: [DigestAlgorithm; 7] = [
+        DigestAlgorithm::Sha2_256,
+        DigestAlgorithm::Sha2_512,
+        DigestAlgorithm::Sha3_256,
+        DigestAlgorithm::Sha3_512,
+        DigestAlgorithm::Crc32,
+        DigestAlgorithm::Md5,
+        DigestAlgorithm::Sha1,
+    ];
+
+    /// CLI引数で指定する名前からハッシュアルゴリズムを取得します。
+    pub fn from_name(name: &str) -> Option<DigestAlgorithm> {
+        match name {
+            "sha2_256" => Some(DigestAlgorithm::Sha2_256),
+            "sha2_512" => Some(DigestAlgorithm::Sha2_512),
+            "sha3_256" => Some(DigestAlgorithm::Sha3_256),
+            "sha3_512" => Some(DigestAlgorithm::Sha3_512),
+            "crc32" => Some(DigestAlgorithm::Crc32),
+            "md5" => Some(DigestAlgorithm::Md5),
+            "sha1" => Some(DigestAlgorithm::Sha1),
+            _ => None,
+        }
+    }
+
+    /// CLI引数で指定する名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            DigestAlgorithm::Sha2_256 => "sha2_256",
+            DigestAlgorithm::Sha2_512 => "sha2_512",
+            DigestAlgorithm::Sha3_256 => "sha3_256",
+            DigestAlgorithm::Sha3_512 => "sha3_512",
+            DigestAlgorithm::Crc32 => "crc32",
+            DigestAlgorithm::Md5 => "md5",
+            DigestAlgorithm::Sha1 => "sha1",
+        }
+    }
+
+    /// BSD形式のタグ名からハッシュアルゴリズムを取得します。
+    pub fn from_bsd_tag(tag: &str) -> Option<DigestAlgorithm> {
+        match tag.to_ascii_uppercase().as_str() {
+            "SHA256" | "SHA2-256" => Some(DigestAlgorithm::Sha2_256),
+            "SHA512" | "SHA2-512" => Some(DigestAlgorithm::Sha2_512),
+            "SHA3-256" => Some(DigestAlgorithm::Sha3_256),
+            "SHA3-512" => Some(DigestAlgorithm::Sha3_512),
+            "CRC32" => Some(DigestAlgorithm::Crc32),
+            "MD5" => Some(DigestAlgorithm::Md5),
+            "SHA1" => Some(DigestAlgorithm::Sha1),
+            _ => None,
+        }
+    }
+
+    /// ハッシュ値のバイト数からハッシュアルゴリズムを推定します。
+    /// 同じ長さのアルゴリズムが複数ある場合は、SHA-2を優先します。
+    pub fn from_digest_length(length: usize) -> Option<DigestAlgorithm> {
+        match length {
+            4 => Some(DigestAlgorithm::Crc32),
+            16 => Some(DigestAlgorithm::Md5),
+            20 => Some(DigestAlgorithm::Sha1),
+            32 => Some(DigestAlgorithm::Sha2_256),
+            64 => Some(DigestAlgorithm::Sha2_512),
+            _ => None,
+        }
+    }
+
+    /// ハッシュ値のバイト数
+    pub fn digest_length(&self) -> usize {
+        match self {
+            DigestAlgorithm::Sha2_256 | DigestAlgorithm::Sha3_256 => 32,
+            DigestAlgorithm::Sha2_512 | DigestAlgorithm::Sha3_512 => 64,
+            DigestAlgorithm::Crc32 => 4,
+            DigestAlgorithm::Md5 => 16,
+            DigestAlgorithm::Sha1 => 20,
+        }
+    }
+
+    /// BSD形式(`SHA256 (file) = hash`)のタグ名
+    pub fn bsd_tag(&self) -> &'static str {
+        match self {
+            DigestAlgorithm::Sha2_256 => "SHA256",
+            DigestAlgorithm::Sha2_512 => "SHA512",
+            DigestAlgorithm::Sha3_256 => "SHA3-256",
+            DigestAlgorithm::Sha3_512 => "SHA3-512",
+            DigestAlgorithm::Crc32 => "CRC32",
+            DigestAlgorithm::Md5 => "MD5",
+            DigestAlgorithm::Sha1 => "SHA1",
+        }
+    }
+}
+
+/// ハッシュ値を計算する入力
+pub enum Input {
+    /// ファイルパス
+    File(String),
+    /// CLI引数で受け取った文字列
+    Text {
+        text: String,
+        encoding: TextEncoding,
+        newline: Option<Newline>,
+    },
+}
+
+/// 受け取ったCLI引数
+pub struct CliArg {
+    /// 入力 `-i`で指定した順に、ファイルパス、文字列を並べたもの
+    pub inputs: Vec<Input>,
+    /// 指定されたハッシュアルゴリズム 複数指定されたときは最初のもの 指定がなければNone
+    pub digest_algorithm: Option<DigestAlgorithm>,
+    /// 指定されたすべてのハッシュアルゴリズム チェックサムファイルを作るときに使う
+    pub digest_algorithms: Vec<DigestAlgorithm>,
+    /// `-d`の指定がないときのハッシュアルゴリズム 設定ファイルで変えられます
+    pub default_digest_algorithm: DigestAlgorithm,
+    pub tree_digest_option: TreeDigestOption,
+    /// 展開してからハッシュ値を計算する場合の圧縮形式
+    pub decompress: Option<Compression>,
+    /// ハッシュ値を計算する前のテキスト正規化
+    pub text_normalization: Option<TextNormalization>,
+    /// 計算したハッシュ値と比べる期待値
+    pub expected_digest: Option<ExpectedDigest>,
+    /// 計算結果の出力形式
+    pub output_format: OutputFormat,
+    /// 終了前にEnterキーを待つか
+    pub pause: bool,
+    /// ファイルを読み込むバッファのバイト数
+    pub buffer_size: usize,
+    /// ディレクトリの下のファイルを集めるときに除くパターン
+    pub exclude: Vec<glob::Pattern>,
+    /// ハッシュ値のキャッシュファイル キャッシュを使わないときはNone
+    pub cache_file: Option<std::path::PathBuf>,
+    /// キャッシュから読んだハッシュ値を計算し直す割合 パーセント
+    pub verify_cache: Option<f64>,
+    /// 結果をまとめたレポートの出力先 拡張子で形式を決める
+    pub report_file: Option<std::path::PathBuf>,
+    pub mode: Mode,
+}
+
+impl CliArg {
+    /// 入力やオプションを使わないモードのCLI引数
+    fn from_mode(mode: Mode) -> CliArg {
+        CliArg {
+            inputs: Vec::new(),
+            digest_algorithm: None,
+            digest_algorithms: Vec::new(),
+            default_digest_algorithm: DigestAlgorithm::default(),
+            tree_digest_option: TreeDigestOption::default(),
+            decompress: None,
+            text_normalization: None,
+            expected_digest: None,
+            output_format: OutputFormat::Text,
+            pause: false,
+            buffer_size: config::DEFAULT_BUFFER_SIZE,
+            exclude: Vec::new(),
+            cache_file: None,
+            verify_cache: None,
+            report_file: None,
+            mode,
+        }
+    }
 }
 
 pub enum Mode {
@@ -16,74 +202,1182 @@ pub enum Mode {
     SetUp,
     CleanUp,
     Gui,
+    /// アーカイブのメンバーごとのハッシュ値を計算する
+    /// 形式がNoneのときはファイル名から推定する
+    Archive {
+        archive_format: Option<ArchiveFormat>,
+    },
+    /// 入力ファイルのチェックサムファイルを作る
+    Manifest {
+        format: ChecksumFormat,
+        /// 相対パスの基準になるディレクトリ Noneのときはカレントディレクトリ
+        base_dir: Option<String>,
+        /// 出力先ファイル Noneのときは標準出力
+        output_file_path: Option<String>,
+    },
+    /// チェックサムファイルに記載されたファイルを検証する
+    Check {
+        checksum_file_path: String,
+    },
+    /// 入力ファイルの隣にあるチェックサムファイルを探して検証する
+    Verify,
+    /// 既知のハッシュ値の一覧と入力ファイルを突き合わせ、一致、移動、新規、変更、欠落に分類する
+    Audit {
+        known_file_path: String,
+        /// 相対パスの基準になるディレクトリ Noneのときはカレントディレクトリ
+        base_dir: Option<String>,
+    },
+    /// チェックサムファイルを別の形式に書き換える ハッシュ値は計算し直さない
+    Convert {
+        checksum_file_path: String,
+        format: ChecksumFormat,
+        /// 出力先ファイル Noneのときは標準出力
+        output_file_path: Option<String>,
+    },
+    /// 標準入力を出力にそのまま流しながらハッシュ値を計算する
+    Tee {
+        output_file_path: Option<String>,
+        digest_file_path: Option<String>,
+        digest_algorithms: Vec<DigestAlgorithm>,
+    },
+    /// 設定ファイルとプロファイルから決まった設定を表示する
+    Config {
+        config: Config,
+    },
+    /// ファイルのパス、サイズ、パーミッション、更新日時、ハッシュ値をベースラインファイルに記録する
+    BaselineCreate {
+        /// 出力先ファイル Noneのときは標準出力
+        output_file_path: Option<String>,
+        /// MACの鍵のファイル Noneのときは環境変数から読む
+        key_file_path: Option<String>,
+    },
+    /// ベースラインファイルと実際のファイルを比べ、追加、削除、変更、メタデータだけの変化に分類する
+    BaselineCompare {
+        baseline_file_path: String,
+        /// MACの鍵のファイル Noneのときは環境変数から読む
+        key_file_path: Option<String>,
+    },
+    /// ハッシュ値を計算して、ファイルの拡張属性に書く
+    XattrWrite,
+    /// ファイルの拡張属性に保存したハッシュ値を表示する
+    XattrRead,
+    /// ハッシュ値を計算し直し、拡張属性に保存したハッシュ値と比べる
+    XattrScrub,
+    /// ファイルやディレクトリの変更を監視し、落ち着いたらハッシュ値を計算し直す
+    Watch {
+        /// 書き込みが落ち着いたとみなすまでの時間
+        debounce: std::time::Duration,
+    },
+    /// CLI引数の定義から補完スクリプトかmanページを作る
+    Generate {
+        target: Target,
+        /// manページを作るサブコマンド Noneのときはdigest_tool自体
+        subcommand: Option<String>,
+        /// 出力先ファイル Noneのときは標準出力
+        output_file_path: Option<String>,
+    },
 }
 
-/// # CLI引数を受け取る関数
-pub fn accept_cli_arg() -> (Option<String>, DigestAlgorithm, Mode) {
-    let matches = app_from_crate!()
-    .arg(Arg::with_name("input_file")
-        .short("i")
-        .long("input_file")
-        .takes_value(true)
-        .value_name("FILE"))
-    .arg(Arg::with_name("digest")
-        .help("ハッシュアルゴリズムを選択できます。利用できるアルゴリズム: [sha2_256, sha2_512, sha3_256, sha3_512]デフォルト(sha2_256)")
-        .short("d")
+/// ハッシュアルゴリズムの名前
+const DIGEST_NAMES: [&str; 7] = [
+    "sha2_256", "sha2_512", "sha3_256", "sha3_512", "crc32", "md5", "sha1",
+];
+/// チェックサムファイルの形式の名前
+const CHECKSUM_FORMAT_NAMES: [&str; 5] = ["gnu", "bsd", "sfv", "hashdeep", "json"];
+/// 出力形式の名前
+const OUTPUT_FORMAT_NAMES: [&str; 4] = ["text", "json", "ndjson", "csv"];
+/// `--text`のエンコーディングの名前
+const ENCODING_NAMES: [&str; 4] = ["utf8", "utf16le", "utf16be", "shift_jis"];
+/// `--text`に付け足す改行の名前
+const NEWLINE_NAMES: [&str; 2] = ["lf", "crlf"];
+/// 圧縮形式の名前
+const COMPRESSION_NAMES: [&str; 5] = ["auto", "gzip", "bzip2", "xz", "zstd"];
+/// アーカイブの形式の名前
+const ARCHIVE_FORMAT_NAMES: [&str; 5] = ["zip", "tar", "tar.gz", "tar.xz", "tar.zst"];
+/// `watch`の出力形式の名前 変更は1件ずつ書き出すので、配列にまとめるjsonとcsvは使えません
+const WATCH_FORMAT_NAMES: [&str; 2] = ["text", "ndjson"];
+/// `generate`で作るものの名前
+const GENERATE_TARGET_NAMES: [&str; 5] = ["bash", "zsh", "fish", "powershell", "man"];
+
+/// # 名前から値を選ぶパーサー
+/// 名前の一覧はヘルプと補完に表示します。一覧の名前はすべてfrom_nameで読めるようにします。
+fn name_parser<T: Clone + Send + Sync + 'static>(
+    names: &'static [&'static str],
+    from_name: fn(&str) -> Option<T>,
+) -> impl TypedValueParser<Value = T> {
+    PossibleValuesParser::new(names)
+        .map(move |name| from_name(&name).unwrap_or_else(|| panic!("{}を読めません", name)))
+}
+
+fn input_file_arg() -> Arg<'static> {
+    Arg::new("input_file")
+        .help(i18n::static_message("help.input_file"))
+        .value_name("FILE")
+        .action(ArgAction::Append)
+        .multiple_values(true)
+        .value_parser(value_parser!(String))
+}
+
+fn digest_arg() -> Arg<'static> {
+    Arg::new("digest")
+        .help(i18n::static_message("help.digest"))
+        .short('d')
         .long("digest")
-        .multiple(false)
-        .takes_value(true))
-    .arg(Arg::with_name("setup")
-        .long("setup")
-        .takes_value(false))
-    .arg(Arg::with_name("clean_up")
-        .long("clean_up")
-        .takes_value(false))
-    .get_matches();
+        .value_name("ALGORITHM")
+        .action(ArgAction::Append)
+        .value_parser(name_parser(&DIGEST_NAMES, DigestAlgorithm::from_name))
+}
+
+fn text_arg() -> Arg<'static> {
+    Arg::new("text")
+        .help(i18n::static_message("help.text"))
+        .long("text")
+        .value_name("TEXT")
+        .value_parser(value_parser!(String))
+}
+
+fn encoding_arg() -> Arg<'static> {
+    Arg::new("encoding")
+        .help(i18n::static_message("help.encoding"))
+        .long("encoding")
+        .value_name("ENCODING")
+        .value_parser(name_parser(&ENCODING_NAMES, TextEncoding::from_name))
+        .requires("text")
+}
+
+fn newline_arg() -> Arg<'static> {
+    Arg::new("newline")
+        .help(i18n::static_message("help.newline"))
+        .long("newline")
+        .value_name("NEWLINE")
+        .value_parser(name_parser(&NEWLINE_NAMES, Newline::from_name))
+        .requires("text")
+}
 
+fn tree_metadata_arg() -> Arg<'static> {
+    Arg::new("tree_metadata")
+        .help(i18n::static_message("help.tree_metadata"))
+        .long("tree_metadata")
+        .action(ArgAction::Append)
+        .use_value_delimiter(true)
+        .value_parser(["exec", "symlink"])
+}
+
+fn decompress_arg() -> Arg<'static> {
+    Arg::new("decompress")
+        .help(i18n::static_message("help.decompress"))
+        .long("decompress")
+        .min_values(0)
+        .max_values(1)
+        .value_name("FORMAT")
+        .default_missing_value("auto")
+        .value_parser(name_parser(&COMPRESSION_NAMES, Compression::from_name))
+}
+
+fn normalize_arg() -> Arg<'static> {
+    Arg::new("normalize")
+        .help(i18n::static_message("help.normalize"))
+        .long("normalize")
+        .value_name("SPEC")
+        .value_parser(|spec: &str| spec.parse::<TextNormalization>())
+}
+
+fn expect_arg() -> Arg<'static> {
+    Arg::new("expect")
+        .help(i18n::static_message("help.expect"))
+        .long("expect")
+        .value_name("DIGEST")
+        .value_parser(|digest: &str| digest.parse::<ExpectedDigest>())
+}
+
+fn archive_arg() -> Arg<'static> {
+    Arg::new("archive")
+        .help(i18n::static_message("help.archive"))
+        .long("archive")
+        .min_values(0)
+        .max_values(1)
+        .value_name("FORMAT")
+        .value_parser(name_parser(&ARCHIVE_FORMAT_NAMES, ArchiveFormat::from_name))
+}
+
+fn output_format_arg() -> Arg<'static> {
+    Arg::new("format")
+        .help(i18n::static_message("help.format"))
+        .long("format")
+        .value_name("FORMAT")
+        .value_parser(name_parser(&OUTPUT_FORMAT_NAMES, OutputFormat::from_name))
+}
+
+fn base_dir_arg() -> Arg<'static> {
+    Arg::new("base_dir")
+        .help(i18n::static_message("help.base_dir"))
+        .long("base_dir")
+        .value_name("DIR")
+        .value_parser(value_parser!(String))
+}
+
+fn output_file_arg() -> Arg<'static> {
+    Arg::new("output_file")
+        .help(i18n::static_message("help.output_file"))
+        .short('o')
+        .long("output_file")
+        .value_name("FILE")
+        .value_parser(value_parser!(String))
+}
+
+fn key_file_arg() -> Arg<'static> {
+    Arg::new("key_file")
+        .help(i18n::static_message("help.baseline.key_file"))
+        .long("key_file")
+        .value_name("FILE")
+        .value_parser(value_parser!(String))
+}
+
+fn exclude_arg() -> Arg<'static> {
+    Arg::new("exclude")
+        .help(i18n::static_message("help.exclude"))
+        .long("exclude")
+        .value_name("PATTERN")
+        .action(ArgAction::Append)
+        .value_parser(|pattern: &str| {
+            glob::Pattern::new(pattern)
+                .map_err(|e| t!("cli.invalid_exclude", pattern = pattern, error = e))
+        })
+}
+
+/// ハッシュ値のキャッシュのオプション 検証するサブコマンドはキャッシュを使わないので、`manifest`だけに付ける
+fn cache_args() -> [Arg<'static>; 2] {
+    [
+        Arg::new("no_cache")
+            .help(i18n::static_message("help.no_cache"))
+            .long("no_cache")
+            .visible_alias("no-cache")
+            .action(ArgAction::SetTrue),
+        Arg::new("verify_cache")
+            .help(i18n::static_message("help.verify_cache"))
+            .long("verify_cache")
+            .visible_alias("verify-cache")
+            .value_name("PERCENT")
+            .value_parser(|percent: &str| match percent.parse::<f64>() {
+                Ok(percent) if percent > 0.0 && percent <= 100.0 => Ok(percent),
+                _ => Err(t!("cli.invalid_verify_cache")),
+            })
+            .conflicts_with("no_cache"),
+    ]
+}
+
+/// 結果をまとめたレポートのオプション
+fn report_arg() -> Arg<'static> {
+    Arg::new("report")
+        .help(i18n::static_message("help.report"))
+        .long("report")
+        .value_name("FILE")
+        .value_parser(|path: &str| {
+            let path = std::path::PathBuf::from(path);
+            match ReportFormat::from_path(&path) {
+                Some(_) => Ok(path),
+                None => Err(t!("cli.invalid_report", path = path.display())),
+            }
+        })
+}
+
+/// # CLIの定義
+/// ヘルプに表示する定義に、非推奨になった以前のフラグと`generate`を加えます。
+/// 以前のフラグはヘルプに表示しませんが、登録済みのコンテクストメニューから使えるように受け付けます。
+pub fn command() -> Command<'static> {
+    visible_command()
+        .args(legacy_args())
+        // パッケージを作るときに使うので、ヘルプには表示しない
+        .subcommand(
+            Command::new("generate")
+                .about(i18n::static_message("about.generate"))
+                .hide(true)
+                .arg(
+                    Arg::new("target")
+                        .help(i18n::static_message("help.generate.target"))
+                        .value_name("TARGET")
+                        .value_parser(name_parser(&GENERATE_TARGET_NAMES, Target::from_name))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("subcommand")
+                        .help(i18n::static_message("help.generate.subcommand"))
+                        .value_name("SUBCOMMAND")
+                        .value_parser(value_parser!(String)),
+                )
+                .arg(output_file_arg().help(i18n::static_message("help.generate.output_file"))),
+        )
+}
+
+/// # ヘルプに表示するCLIの定義
+/// サブコマンドごとのオプションを定義します。補完スクリプトとmanページはこの定義から作ります。
+pub fn visible_command() -> Command<'static> {
+    command!()
+        .about(i18n::static_message("about.digest_tool"))
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("pause")
+                .help(i18n::static_message("help.pause"))
+                .long("pause")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("lang")
+                .help(i18n::static_message("help.lang"))
+                .long("lang")
+                .value_name("LANG")
+                .value_parser(["ja", "en"])
+                .global(true),
+        )
+        .arg(
+            Arg::new("profile")
+                .help(i18n::static_message("help.profile"))
+                .long("profile")
+                .value_name("NAME")
+                .value_parser(value_parser!(String))
+                .global(true),
+        )
+        .arg(
+            Arg::new("buffer_size")
+                .help(i18n::static_message("help.buffer_size"))
+                .long("buffer_size")
+                .value_name("BYTES")
+                .value_parser(|size: &str| match size.parse::<usize>() {
+                    Ok(size) if size > 0 => Ok(size),
+                    _ => Err(t!("cli.invalid_buffer_size")),
+                })
+                .global(true),
+        )
+        .subcommand(
+            Command::new("hash")
+                .about(i18n::static_message("about.hash"))
+                .arg(input_file_arg())
+                .arg(digest_arg().action(ArgAction::Set))
+                .arg(text_arg().conflicts_with("input_file"))
+                .arg(encoding_arg())
+                .arg(newline_arg())
+                .arg(tree_metadata_arg())
+                // 値を省略できるので、後に続くファイルと区別できるように`=`で値を指定する
+                .arg(
+                    decompress_arg()
+                        .require_equals(true)
+                        .requires("input_file")
+                        .conflicts_with("archive"),
+                )
+                .arg(normalize_arg().conflicts_with("archive"))
+                .arg(expect_arg().conflicts_with("archive"))
+                .arg(archive_arg().require_equals(true).requires("input_file"))
+                .arg(output_format_arg().conflicts_with_all(&["archive", "expect"])),
+        )
+        .subcommand(
+            Command::new("check")
+                .about(i18n::static_message("about.check"))
+                .arg(
+                    Arg::new("checksum_file")
+                        .help(i18n::static_message("help.check.checksum_file"))
+                        .value_name("CHECKSUM_FILE")
+                        .value_parser(value_parser!(String))
+                        .required_unless_present("sidecar"),
+                )
+                .arg(
+                    Arg::new("sidecar")
+                        .help(i18n::static_message("help.check.sidecar"))
+                        .long("sidecar")
+                        .value_name("FILE")
+                        .action(ArgAction::Append)
+                        .value_parser(value_parser!(String))
+                        .conflicts_with_all(&["checksum_file", "digest"]),
+                )
+                .arg(
+                    digest_arg()
+                        .help(i18n::static_message("help.gnu_digest"))
+                        .action(ArgAction::Set),
+                )
+                .arg(normalize_arg())
+                .arg(exclude_arg().requires("sidecar"))
+                .arg(report_arg()),
+        )
+        .subcommand(
+            Command::new("manifest")
+                .about(i18n::static_message("about.manifest"))
+                .arg(input_file_arg().required(true))
+                .arg(
+                    Arg::new("format")
+                        .help(i18n::static_message("help.manifest.format"))
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(name_parser(
+                            &CHECKSUM_FORMAT_NAMES,
+                            ChecksumFormat::from_name,
+                        )),
+                )
+                .arg(digest_arg())
+                .arg(base_dir_arg())
+                .arg(output_file_arg())
+                .arg(normalize_arg())
+                .arg(exclude_arg())
+                .args(cache_args())
+                .arg(report_arg()),
+        )
+        .subcommand(
+            Command::new("compare")
+                .about(i18n::static_message("about.compare"))
+                .arg(
+                    Arg::new("known_file")
+                        .help(i18n::static_message("help.compare.known_file"))
+                        .value_name("KNOWN_FILE")
+                        .value_parser(value_parser!(String))
+                        .required(true),
+                )
+                .arg(input_file_arg().required(true))
+                .arg(base_dir_arg())
+                .arg(exclude_arg())
+                .arg(report_arg()),
+        )
+        .subcommand(
+            Command::new("integrate")
+                .about(i18n::static_message("about.integrate"))
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("install").about(i18n::static_message("about.integrate.install")),
+                )
+                .subcommand(
+                    Command::new("remove").about(i18n::static_message("about.integrate.remove")),
+                ),
+        )
+        .subcommand(Command::new("gui").about(i18n::static_message("about.gui")))
+        .subcommand(
+            Command::new("convert")
+                .about(i18n::static_message("about.convert"))
+                .arg(
+                    Arg::new("checksum_file")
+                        .help(i18n::static_message("help.convert.checksum_file"))
+                        .value_name("CHECKSUM_FILE")
+                        .value_parser(value_parser!(String))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("to")
+                        .help(i18n::static_message("help.convert.to"))
+                        .long("to")
+                        .value_name("FORMAT")
+                        .value_parser(name_parser(
+                            &CHECKSUM_FORMAT_NAMES,
+                            ChecksumFormat::from_name,
+                        ))
+                        .required(true),
+                )
+                .arg(
+                    digest_arg()
+                        .help(i18n::static_message("help.gnu_digest"))
+                        .action(ArgAction::Set),
+                )
+                .arg(output_file_arg().help(i18n::static_message("help.convert.output_file"))),
+        )
+        .subcommand(
+            Command::new("tee")
+                .about(i18n::static_message("about.tee"))
+                .arg(digest_arg().help(i18n::static_message("help.tee.digest")))
+                .arg(output_file_arg().help(i18n::static_message("help.tee.output_file")))
+                .arg(
+                    Arg::new("digest_file")
+                        .help(i18n::static_message("help.tee.digest_file"))
+                        .long("digest_file")
+                        .value_name("FILE")
+                        .value_parser(value_parser!(String)),
+                ),
+        )
+        .subcommand(
+            Command::new("baseline")
+                .about(i18n::static_message("about.baseline"))
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("create")
+                        .about(i18n::static_message("about.baseline.create"))
+                        .arg(input_file_arg().required(true))
+                        .arg(digest_arg().action(ArgAction::Set))
+                        .arg(
+                            output_file_arg()
+                                .help(i18n::static_message("help.baseline.output_file")),
+                        )
+                        .arg(key_file_arg())
+                        .arg(exclude_arg()),
+                )
+                .subcommand(
+                    Command::new("compare")
+                        .about(i18n::static_message("about.baseline.compare"))
+                        .arg(
+                            Arg::new("baseline_file")
+                                .help(i18n::static_message("help.baseline.baseline_file"))
+                                .value_name("BASELINE_FILE")
+                                .value_parser(value_parser!(String))
+                                .required(true),
+                        )
+                        .arg(key_file_arg()),
+                ),
+        )
+        .subcommand(
+            Command::new("xattr")
+                .about(i18n::static_message("about.xattr"))
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("write")
+                        .about(i18n::static_message("about.xattr.write"))
+                        .arg(input_file_arg().required(true))
+                        .arg(digest_arg())
+                        .arg(exclude_arg()),
+                )
+                .subcommand(
+                    Command::new("read")
+                        .about(i18n::static_message("about.xattr.read"))
+                        .arg(input_file_arg().required(true))
+                        .arg(digest_arg().help(i18n::static_message("help.xattr.digest")))
+                        .arg(exclude_arg()),
+                )
+                .subcommand(
+                    Command::new("scrub")
+                        .about(i18n::static_message("about.xattr.scrub"))
+                        .arg(input_file_arg().required(true))
+                        .arg(digest_arg().help(i18n::static_message("help.xattr.digest")))
+                        .arg(exclude_arg()),
+                ),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about(i18n::static_message("about.watch"))
+                .arg(
+                    input_file_arg()
+                        .help(i18n::static_message("help.watch.path"))
+                        .value_name("PATH")
+                        .required(true),
+                )
+                .arg(digest_arg().action(ArgAction::Set))
+                .arg(
+                    Arg::new("debounce")
+                        .help(i18n::static_message("help.watch.debounce"))
+                        .long("debounce")
+                        .value_name("MILLISECONDS")
+                        .value_parser(value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("format")
+                        .help(i18n::static_message("help.watch.format"))
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(name_parser(&WATCH_FORMAT_NAMES, OutputFormat::from_name)),
+                )
+                .arg(exclude_arg()),
+        )
+        .subcommand(Command::new("config").about(i18n::static_message("about.config")))
+}
+
+/// # 非推奨になった以前のフラグ
+/// サブコマンドがなかったころのフラグです。組み合わせの制限も以前と同じにします。
+fn legacy_args() -> Vec<Arg<'static>> {
+    let args = vec![
+        // 以前と同じく、`-i`1つにファイル1つ
+        input_file_arg()
+            .short('i')
+            .long("input_file")
+            .multiple_values(false),
+        text_arg().conflicts_with("input_file"),
+        encoding_arg(),
+        newline_arg(),
+        tree_metadata_arg(),
+        digest_arg(),
+        decompress_arg()
+            .requires("input_file")
+            .conflicts_with("archive"),
+        normalize_arg().conflicts_with("archive"),
+        expect_arg().conflicts_with("archive"),
+        archive_arg().requires("input_file"),
+        Arg::new("check")
+            .short('c')
+            .long("check")
+            .value_name("CHECKSUM_FILE")
+            .value_parser(value_parser!(String))
+            .conflicts_with_all(&["input_file", "text", "archive", "decompress", "expect"]),
+        Arg::new("manifest")
+            .long("manifest")
+            .value_name("FORMAT")
+            .value_parser(name_parser(
+                &CHECKSUM_FORMAT_NAMES,
+                ChecksumFormat::from_name,
+            ))
+            .requires("input_file")
+            .conflicts_with_all(&["text", "archive", "decompress", "check", "expect"]),
+        Arg::new("verify")
+            .long("verify")
+            .action(ArgAction::SetTrue)
+            .requires("input_file")
+            .conflicts_with_all(&[
+                "text",
+                "archive",
+                "decompress",
+                "check",
+                "manifest",
+                "expect",
+            ]),
+        Arg::new("audit")
+            .long("audit")
+            .value_name("KNOWN_FILE")
+            .value_parser(value_parser!(String))
+            .requires("input_file")
+            .conflicts_with_all(&[
+                "text",
+                "archive",
+                "decompress",
+                "check",
+                "manifest",
+                "normalize",
+                "expect",
+                "verify",
+            ]),
+        base_dir_arg(),
+        output_file_arg().requires("manifest"),
+        output_format_arg()
+            .conflicts_with_all(&["archive", "check", "manifest", "verify", "audit", "expect"]),
+        exclude_arg(),
+        Arg::new("setup")
+            .long("setup")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("clean_up"),
+        Arg::new("clean_up")
+            .long("clean_up")
+            .action(ArgAction::SetTrue),
+    ];
+    args.into_iter().map(|arg| arg.hide(true)).collect()
+}
+
+/// # CLI引数を受け取る関数
+/// ## 返り値
+/// Result型 引数が誤っていればError::InvalidArgument あるいは、CLI引数
+pub fn accept_cli_arg() -> std::result::Result<CliArg, Error> {
+    // エクスプローラーなどから引数なしで起動されたときは、以前と同じくGUIにする
     let arg_len = std::env::args().len();
     debug!("arg_len: {}", arg_len);
-    if arg_len == 1 {
-        return (None, DigestAlgorithm::Sha2_256, Mode::Gui);
-    }
-
-    let input_file_path = matches
-        .value_of_lossy("input_file")
-        .map(|file| file.to_string());
-
-    let digest_str = matches.value_of("digest");
-    debug!("{:?}", digest_str);
-    let digest_algorithm = match digest_str {
-        Some(algorithm) => match algorithm {
-            "sha2_256" => DigestAlgorithm::Sha2_256,
-            "sha2_512" => DigestAlgorithm::Sha2_512,
-            "sha3_256" => DigestAlgorithm::Sha3_256,
-            "sha3_512" => DigestAlgorithm::Sha3_512,
-            _ => {
-                println!("ハッシュアルゴリズムの入力が誤っています オプション-hで利用できるハッシュアルゴリズムを確認できます。");
-                println!("Enterキーを押すと終了します");
-                let mut word = String::new();
-                std::io::stdin().read_line(&mut word).ok();
-                std::process::exit(0);
+    if arg_len == 1 && console::owns_console() {
+        return Ok(CliArg::from_mode(Mode::Gui));
+    }
+
+    let matches = match command().try_get_matches() {
+        Ok(matches) => matches,
+        // ヘルプとバージョンの表示は失敗ではないので、clapにまかせる
+        Err(e)
+            if matches!(
+                e.kind(),
+                clap::ErrorKind::DisplayHelp | clap::ErrorKind::DisplayVersion
+            ) =>
+        {
+            e.exit()
+        }
+        Err(e) => return Err(Error::InvalidArgument(e.to_string().trim_end().to_string())),
+    };
+    if let Some(flag) = legacy_flag_with_subcommand(&matches) {
+        return Err(Error::InvalidArgument(t!(
+            "cli.legacy_flag_with_subcommand",
+            flag = flag
+        )));
+    }
+
+    // CLI引数で指定されなかった値は、設定ファイルから決める
+    // グローバルなオプションは、サブコマンドの後に指定されても読めるように、最も深いサブコマンドから読む
+    let mut leaf_matches = &matches;
+    while let Some((_, sub_matches)) = leaf_matches.subcommand() {
+        leaf_matches = sub_matches;
+    }
+    let config = config::load(get_one::<String>(leaf_matches, "profile").as_deref())?;
+
+    let mode = match matches.subcommand() {
+        Some(("hash", sub_matches)) => hash_mode(sub_matches),
+        Some(("check", sub_matches)) => match get_one::<String>(sub_matches, "checksum_file") {
+            Some(checksum_file_path) => Mode::Check { checksum_file_path },
+            None => Mode::Verify,
+        },
+        Some(("manifest", sub_matches)) => Mode::Manifest {
+            format: get_one(sub_matches, "format").unwrap_or(ChecksumFormat::Gnu),
+            base_dir: get_one(sub_matches, "base_dir"),
+            output_file_path: get_one(sub_matches, "output_file"),
+        },
+        Some(("compare", sub_matches)) => Mode::Audit {
+            known_file_path: get_one(sub_matches, "known_file").unwrap_or_default(),
+            base_dir: get_one(sub_matches, "base_dir"),
+        },
+        Some(("integrate", sub_matches)) => match sub_matches.subcommand_name() {
+            Some("remove") => Mode::CleanUp,
+            _ => Mode::SetUp,
+        },
+        Some(("gui", _)) => Mode::Gui,
+        Some(("convert", sub_matches)) => Mode::Convert {
+            checksum_file_path: get_one(sub_matches, "checksum_file").unwrap_or_default(),
+            format: get_one(sub_matches, "to").unwrap_or(ChecksumFormat::Gnu),
+            output_file_path: get_one(sub_matches, "output_file"),
+        },
+        Some(("tee", sub_matches)) => {
+            let mut digest_algorithms: Vec<DigestAlgorithm> = get_many(sub_matches, "digest");
+            if digest_algorithms.is_empty() {
+                digest_algorithms.push(config.digest_algorithm);
+            }
+            Mode::Tee {
+                output_file_path: get_one(sub_matches, "output_file"),
+                digest_file_path: get_one(sub_matches, "digest_file"),
+                digest_algorithms,
             }
+        }
+        Some(("baseline", sub_matches)) => match sub_matches.subcommand() {
+            Some(("compare", compare_matches)) => Mode::BaselineCompare {
+                baseline_file_path: get_one(compare_matches, "baseline_file").unwrap_or_default(),
+                key_file_path: get_one(compare_matches, "key_file"),
+            },
+            _ => Mode::BaselineCreate {
+                output_file_path: get_one(leaf_matches, "output_file"),
+                key_file_path: get_one(leaf_matches, "key_file"),
+            },
         },
-        None => DigestAlgorithm::Sha2_256,
+        Some(("xattr", sub_matches)) => match sub_matches.subcommand_name() {
+            Some("read") => Mode::XattrRead,
+            Some("scrub") => Mode::XattrScrub,
+            _ => Mode::XattrWrite,
+        },
+        Some(("watch", sub_matches)) => Mode::Watch {
+            debounce: std::time::Duration::from_millis(
+                get_one(sub_matches, "debounce").unwrap_or(watch::DEFAULT_DEBOUNCE_MILLIS),
+            ),
+        },
+        Some(("config", _)) => Mode::Config {
+            config: config.clone(),
+        },
+        Some(("generate", sub_matches)) => Mode::Generate {
+            target: get_one(sub_matches, "target").unwrap_or(Target::Man),
+            subcommand: get_one(sub_matches, "subcommand"),
+            output_file_path: get_one(sub_matches, "output_file"),
+        },
+        _ => {
+            let mode = legacy_mode(&matches);
+            eprintln!(
+                "{}",
+                t!("cli.deprecated_flags", command = mode.command_name())
+            );
+            mode
+        }
     };
+    debug!("{:?}", leaf_matches);
+    let cli_arg = cli_arg(leaf_matches, mode, &config);
+    check_cache_args(&cli_arg)?;
+    Ok(cli_arg)
+}
+
+/// # キャッシュのオプションを確かめる関数
+/// キャッシュを使わないときの`--verify_cache`は、何も確かめないので、黙って無視せずに誤りにします。
+fn check_cache_args(cli_arg: &CliArg) -> std::result::Result<(), Error> {
+    if cli_arg.verify_cache.is_some() && cli_arg.cache_file.is_none() {
+        return Err(Error::InvalidArgument(t!("cli.verify_cache_without_cache")));
+    }
+    Ok(())
+}
+
+/// `hash`サブコマンドのモード
+fn hash_mode(matches: &ArgMatches) -> Mode {
+    if matches.try_contains_id("archive").unwrap_or(false) {
+        Mode::Archive {
+            archive_format: get_one(matches, "archive"),
+        }
+    } else {
+        Mode::Digest
+    }
+}
 
-    let mode = match (
-        matches.occurrences_of("setup"),
-        matches.occurrences_of("clean_up"),
-    ) {
-        (0, 0) => Mode::Digest,
-        (_, 0) => Mode::SetUp,
-        (0, _) => Mode::CleanUp,
-        (_, _) => {
-            println!("セットアップとクリーンアップが同時に選択されています。");
-            println!("Enterキーを押すと終了します");
-            let mut word = String::new();
-            std::io::stdin().read_line(&mut word).ok();
-            std::process::exit(0);
+/// 以前のフラグのモード
+fn legacy_mode(matches: &ArgMatches) -> Mode {
+    if matches.get_flag("setup") {
+        Mode::SetUp
+    } else if matches.get_flag("clean_up") {
+        Mode::CleanUp
+    } else if let Some(format) = get_one(matches, "manifest") {
+        Mode::Manifest {
+            format,
+            base_dir: get_one(matches, "base_dir"),
+            output_file_path: get_one(matches, "output_file"),
         }
+    } else if matches.get_flag("verify") {
+        Mode::Verify
+    } else if let Some(known_file_path) = get_one(matches, "audit") {
+        Mode::Audit {
+            known_file_path,
+            base_dir: get_one(matches, "base_dir"),
+        }
+    } else if let Some(checksum_file_path) = get_one(matches, "check") {
+        Mode::Check { checksum_file_path }
+    } else {
+        hash_mode(matches)
+    }
+}
+
+impl Mode {
+    /// 以前のフラグの代わりに使うサブコマンド
+    fn command_name(&self) -> &'static str {
+        match self {
+            Mode::SetUp => "integrate install",
+            Mode::CleanUp => "integrate remove",
+            Mode::Manifest { .. } => "manifest",
+            Mode::Check { .. } | Mode::Verify => "check",
+            Mode::Audit { .. } => "compare",
+            Mode::Gui => "gui",
+            Mode::Convert { .. } => "convert",
+            Mode::Tee { .. } => "tee",
+            Mode::Config { .. } => "config",
+            Mode::BaselineCreate { .. } => "baseline create",
+            Mode::BaselineCompare { .. } => "baseline compare",
+            Mode::XattrWrite => "xattr write",
+            Mode::XattrRead => "xattr read",
+            Mode::XattrScrub => "xattr scrub",
+            Mode::Watch { .. } => "watch",
+            Mode::Generate { .. } => "generate",
+            Mode::Digest | Mode::Archive { .. } => "hash",
+        }
+    }
+}
+
+/// # サブコマンドと一緒に指定された以前のフラグを探す関数
+/// グローバルなオプションはサブコマンドの前にも書けるので、以前のフラグだけを調べます。
+/// ## 返り値
+/// 見つかったフラグの表記 なければNone
+fn legacy_flag_with_subcommand(matches: &ArgMatches) -> Option<String> {
+    matches.subcommand()?;
+    legacy_args()
+        .iter()
+        .find(|arg| matches.value_source(arg.get_id()) == Some(ValueSource::CommandLine))
+        .map(|arg| match (arg.get_long(), arg.get_short()) {
+            (Some(long), _) => format!("--{}", long),
+            (None, Some(short)) => format!("-{}", short),
+            (None, None) => arg.get_id().to_string(),
+        })
+}
+
+/// 定義されていない引数も、指定されなかったものとして読む
+fn get_one<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, id: &str) -> Option<T> {
+    matches.try_get_one::<T>(id).ok().flatten().cloned()
+}
+
+/// 定義されていない引数も、指定されなかったものとして読む
+fn get_many<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, id: &str) -> Vec<T> {
+    matches
+        .try_get_many::<T>(id)
+        .ok()
+        .flatten()
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default()
+}
+
+/// # モードとオプションからCLI引数を作る関数
+/// ## 引数
+/// - 最も深いサブコマンドの引数 以前のフラグの場合は最上位の引数
+/// - モード
+/// - 設定ファイルから決まった値
+fn cli_arg(matches: &ArgMatches, mode: Mode, config: &Config) -> CliArg {
+    // `--sidecar`のファイルも、`-i`と同じ入力として扱う
+    let mut inputs: Vec<Input> = get_many::<String>(matches, "input_file")
+        .into_iter()
+        .chain(get_many::<String>(matches, "sidecar"))
+        .map(Input::File)
+        .collect();
+    if let Some(text) = get_one::<String>(matches, "text") {
+        inputs.push(Input::Text {
+            text,
+            encoding: get_one(matches, "encoding").unwrap_or(config.encoding),
+            newline: get_one(matches, "newline"),
+        });
+    }
+
+    let tree_metadata: Vec<String> = get_many(matches, "tree_metadata");
+    let tree_digest_option = TreeDigestOption {
+        include_executable: tree_metadata.iter().any(|metadata| metadata == "exec"),
+        include_symlink_target: tree_metadata.iter().any(|metadata| metadata == "symlink"),
+    };
+
+    let digest_algorithms: Vec<DigestAlgorithm> = get_many(matches, "digest");
+    // `--exclude`を指定すると、設定ファイルのパターンは使わない
+    // 設定ファイルのパターンは読み込むときに確かめている
+    let exclude: Vec<glob::Pattern> = match matches.try_contains_id("exclude") {
+        Ok(true) => get_many(matches, "exclude"),
+        _ => config
+            .exclude
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .collect(),
     };
 
-    (input_file_path, digest_algorithm, mode)
+    CliArg {
+        inputs,
+        digest_algorithm: digest_algorithms.first().copied(),
+        digest_algorithms,
+        default_digest_algorithm: config.digest_algorithm,
+        tree_digest_option,
+        decompress: get_one(matches, "decompress"),
+        // `bom=keep`だけのような何も変えない指定は、指定しなかったことにする
+        text_normalization: get_one::<TextNormalization>(matches, "normalize")
+            .filter(|text_normalization| !text_normalization.is_noop()),
+        expected_digest: get_one(matches, "expect"),
+        output_format: get_one(matches, "format").unwrap_or(config.output_format),
+        pause: matches.get_flag("pause"),
+        buffer_size: get_one(matches, "buffer_size").unwrap_or(config.buffer_size),
+        exclude,
+        // 以前のフラグには`--no_cache`がないので、設定ファイルだけで決める
+        cache_file: if get_one::<bool>(matches, "no_cache").unwrap_or(false) || !config.cache {
+            None
+        } else {
+            config.cache_file.clone()
+        },
+        verify_cache: get_one(matches, "verify_cache"),
+        report_file: get_one(matches, "report"),
+        mode,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> ArgMatches {
+        command()
+            .try_get_matches_from(args)
+            .unwrap_or_else(|e| panic!("{:?}: {}", args, e))
+    }
+
+    /// 引数の定義に矛盾がない
+    #[test]
+    fn ok_command() {
+        command().debug_assert();
+    }
+
+    /// 名前の一覧はすべて値に変換できる
+    #[test]
+    fn ok_names() {
+        for name in DIGEST_NAMES.iter() {
+            assert!(DigestAlgorithm::from_name(name).is_some(), "{}", name);
+        }
+        assert_eq!(
+            DigestAlgorithm::ALL.map(|digest_algorithm| digest_algorithm.name()),
+            DIGEST_NAMES
+        );
+        for name in CHECKSUM_FORMAT_NAMES.iter() {
+            assert!(ChecksumFormat::from_name(name).is_some(), "{}", name);
+        }
+        for name in OUTPUT_FORMAT_NAMES.iter() {
+            assert!(OutputFormat::from_name(name).is_some(), "{}", name);
+        }
+        for name in ENCODING_NAMES.iter() {
+            assert!(TextEncoding::from_name(name).is_some(), "{}", name);
+        }
+        for name in NEWLINE_NAMES.iter() {
+            assert!(Newline::from_name(name).is_some(), "{}", name);
+        }
+        for name in COMPRESSION_NAMES.iter() {
+            assert!(Compression::from_name(name).is_some(), "{}", name);
+        }
+        for name in ARCHIVE_FORMAT_NAMES.iter() {
+            assert!(ArchiveFormat::from_name(name).is_some(), "{}", name);
+        }
+        for name in WATCH_FORMAT_NAMES.iter() {
+            assert!(OutputFormat::from_name(name).is_some(), "{}", name);
+        }
+        for name in GENERATE_TARGET_NAMES.iter() {
+            assert!(Target::from_name(name).is_some(), "{}", name);
+        }
+    }
+
+    /// サブコマンドのオプションを型付きで読む
+    #[test]
+    fn ok_subcommand() {
+        let matches = parse(&[
+            "digest_tool",
+            "hash",
+            "-d",
+            "md5",
+            "--decompress",
+            "a.gz",
+            "--pause",
+        ]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        let cli_arg = cli_arg(sub_matches, hash_mode(sub_matches), &Config::default());
+        assert!(matches!(cli_arg.mode, Mode::Digest));
+        assert_eq!(Some(DigestAlgorithm::Md5), cli_arg.digest_algorithm);
+        assert_eq!(Some(Compression::Auto), cli_arg.decompress);
+        assert!(cli_arg.pause);
+        assert_eq!(1, cli_arg.inputs.len());
+
+        let matches = parse(&["digest_tool", "hash", "a.txt", "b.txt", "-d", "md5"]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(
+            vec!["a.txt".to_string(), "b.txt".to_string()],
+            get_many::<String>(sub_matches, "input_file")
+        );
+
+        let matches = parse(&[
+            "digest_tool",
+            "hash",
+            "--text",
+            "x",
+            "--encoding",
+            "utf16le",
+            "--newline",
+            "crlf",
+        ]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(
+            Some(TextEncoding::Utf16Le),
+            get_one::<TextEncoding>(sub_matches, "encoding")
+        );
+        assert_eq!(
+            Some(Newline::CrLf),
+            get_one::<Newline>(sub_matches, "newline")
+        );
+        assert!(get_many::<String>(sub_matches, "input_file").is_empty());
+
+        let matches = parse(&["digest_tool", "check", "--sidecar", "a.iso"]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(None, get_one::<String>(sub_matches, "checksum_file"));
+        assert_eq!(
+            vec!["a.iso".to_string()],
+            get_many::<String>(sub_matches, "sidecar")
+        );
+    }
+
+    /// `--no_cache`を指定したときと、設定ファイルで使わないとしたときは、キャッシュを使わない
+    #[test]
+    fn ok_cache_args() {
+        // デフォルトでは使わない
+        let matches = parse(&["digest_tool", "manifest", "dir"]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(
+            None,
+            cli_arg(sub_matches, Mode::Verify, &Config::default()).cache_file
+        );
+
+        let config = Config {
+            cache: true,
+            cache_file: Some(std::path::PathBuf::from("cache")),
+            ..Config::default()
+        };
+        for flag in ["--verify_cache", "--verify-cache"] {
+            let matches = parse(&["digest_tool", "manifest", "dir", flag, "2.5"]);
+            let (_, sub_matches) = matches.subcommand().unwrap();
+            let cached = cli_arg(sub_matches, Mode::Verify, &config);
+            assert_eq!(Some(std::path::PathBuf::from("cache")), cached.cache_file);
+            assert_eq!(Some(2.5), cached.verify_cache);
+        }
+
+        for flag in ["--no_cache", "--no-cache"] {
+            let matches = parse(&["digest_tool", "manifest", "dir", flag]);
+            let (_, sub_matches) = matches.subcommand().unwrap();
+            assert_eq!(None, cli_arg(sub_matches, Mode::Verify, &config).cache_file);
+        }
+
+        // キャッシュを使わないときの`--verify_cache`は誤り
+        let matches = parse(&["digest_tool", "manifest", "dir", "--verify_cache", "50"]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert!(check_cache_args(&cli_arg(sub_matches, Mode::Verify, &config)).is_ok());
+        assert!(matches!(
+            check_cache_args(&cli_arg(sub_matches, Mode::Verify, &Config::default())),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        let matches = parse(&["digest_tool", "manifest", "dir"]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        let config = Config {
+            cache: false,
+            ..config
+        };
+        assert_eq!(None, cli_arg(sub_matches, Mode::Verify, &config).cache_file);
+    }
+
+    /// `--report`の出力先は、形式の分かる拡張子なら受け付ける
+    #[test]
+    fn ok_report_arg() {
+        let matches = parse(&["digest_tool", "compare", "K", "d", "--report", "r.HTML"]);
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(
+            Some(std::path::PathBuf::from("r.HTML")),
+            cli_arg(sub_matches, Mode::Verify, &Config::default()).report_file
+        );
+    }
+
+    /// 登録済みのコンテクストメニューのコマンドは、以前のフラグとして読める
+    #[test]
+    fn ok_legacy_flags() {
+        let matches = parse(&["digest_tool", "-i", "a.txt", "--pause", "-d", "sha3_512"]);
+        assert!(matches.subcommand().is_none());
+        let mode = legacy_mode(&matches);
+        assert_eq!("hash", mode.command_name());
+        let cli_arg = cli_arg(&matches, mode, &Config::default());
+        assert_eq!(Some(DigestAlgorithm::Sha3_512), cli_arg.digest_algorithm);
+        assert!(cli_arg.pause);
+
+        let matches = parse(&["digest_tool", "--manifest", "bsd", "-i", "dir"]);
+        assert_eq!("manifest", legacy_mode(&matches).command_name());
+        let matches = parse(&["digest_tool", "--clean_up"]);
+        assert_eq!("integrate remove", legacy_mode(&matches).command_name());
+    }
+
+    /// 組み合わせられないオプションは誤り
+    #[test]
+    fn ng_conflicts() {
+        for args in [
+            vec!["digest_tool", "hash", "a.txt", "--text", "abc"],
+            vec![
+                "digest_tool",
+                "hash",
+                "--archive",
+                "a.zip",
+                "--expect",
+                "00",
+            ],
+            vec!["digest_tool", "check", "SUMS", "--sidecar", "a.iso"],
+            vec![
+                "digest_tool",
+                "manifest",
+                "d",
+                "--no_cache",
+                "--verify_cache",
+                "1",
+            ],
+            // 検証するサブコマンドはキャッシュを使わない
+            vec!["digest_tool", "check", "SUMS", "--no_cache"],
+            vec!["digest_tool", "compare", "K", "d", "--verify_cache", "1"],
+            vec!["digest_tool", "baseline", "compare", "B", "--no-cache"],
+            vec!["digest_tool", "manifest", "d", "--verify_cache", "0"],
+            vec!["digest_tool", "manifest", "d", "--verify_cache", "101"],
+            vec!["digest_tool", "check", "SUMS", "--report", "report.txt"],
+            vec!["digest_tool", "--setup", "--clean_up"],
+        ]
+        .iter()
+        {
+            assert!(command().try_get_matches_from(args).is_err(), "{:?}", args);
+        }
+    }
+
+    /// グローバルなオプションはサブコマンドの前にも書ける
+    #[test]
+    fn ok_global_before_subcommand() {
+        let matches = parse(&["digest_tool", "--lang", "en", "--pause", "hash", "a.txt"]);
+        assert_eq!(None, legacy_flag_with_subcommand(&matches));
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert!(sub_matches.get_flag("pause"));
+    }
+
+    /// 以前のフラグはサブコマンドと一緒に使えない
+    #[test]
+    fn ng_legacy_flag_with_subcommand() {
+        let matches = parse(&["digest_tool", "-i", "a.txt", "hash", "b.txt"]);
+        assert_eq!(
+            Some("--input_file".to_string()),
+            legacy_flag_with_subcommand(&matches)
+        );
+        let matches = parse(&["digest_tool", "--setup", "integrate", "install"]);
+        assert_eq!(
+            Some("--setup".to_string()),
+            legacy_flag_with_subcommand(&matches)
+        );
+    }
 }
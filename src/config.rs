@@ -0,0 +1,417 @@
+//! # 設定ファイルモジュール
+//! CLI引数を省略したときの値を、TOML形式の設定ファイルから読み込みます。
+//! 書ける項目と例は`docs/config.md`にまとめています。
+//!
+//! 値は次の順に決めます。上にあるものほど優先します。
+//! 1. CLI引数
+//! 2. `--profile`で選んだプロファイル(`[profile.<名前>]`) プロジェクトの設定ファイルのものを優先します
+//! 3. プロジェクトの設定ファイル カレントディレクトリから親へたどって最初に見つかった`.digest_tool.toml`
+//! 4. ユーザーの設定ファイル
+//!    Windowsは`%APPDATA%\digest_tool\config.toml`、
+//!    それ以外は`$XDG_CONFIG_HOME/digest_tool/config.toml`(未設定なら`~/.config/digest_tool/config.toml`)
+//! 5. 組み込みのデフォルト値
+
+use crate::cache;
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::error::Error;
+use crate::output_format::OutputFormat;
+use crate::text_input::TextEncoding;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// プロジェクトの設定ファイルの名前
+pub const PROJECT_FILE_NAME: &str = ".digest_tool.toml";
+
+/// ファイルを読み込むバッファのデフォルトのバイト数
+pub const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// # 設定できる項目
+/// 設定ファイルの最上位と、プロファイルに書けます。書かれていない項目はNoneです。
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// ハッシュアルゴリズム `-d`と同じ名前
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    /// 出力形式 `--format`と同じ名前
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// `--text`のエンコーディング `--encoding`と同じ名前
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// ファイルを読み込むバッファのバイト数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffer_size: Option<usize>,
+    /// ディレクトリの下のファイルを集めるときに除くパターン
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+    /// ハッシュ値のキャッシュを使うか
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
+    /// キャッシュファイルのパス
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_file: Option<String>,
+}
+
+impl Settings {
+    /// otherに書かれている項目で上書きします。
+    fn merge(&mut self, other: &Settings) {
+        if other.digest.is_some() {
+            self.digest = other.digest.clone();
+        }
+        if other.format.is_some() {
+            self.format = other.format.clone();
+        }
+        if other.encoding.is_some() {
+            self.encoding = other.encoding.clone();
+        }
+        if other.buffer_size.is_some() {
+            self.buffer_size = other.buffer_size;
+        }
+        if other.exclude.is_some() {
+            self.exclude = other.exclude.clone();
+        }
+        if other.cache.is_some() {
+            self.cache = other.cache;
+        }
+        if other.cache_file.is_some() {
+            self.cache_file = other.cache_file.clone();
+        }
+    }
+}
+
+/// 設定ファイルの中身
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    digest: Option<String>,
+    format: Option<String>,
+    encoding: Option<String>,
+    buffer_size: Option<usize>,
+    exclude: Option<Vec<String>>,
+    cache: Option<bool>,
+    cache_file: Option<String>,
+    #[serde(default)]
+    profile: BTreeMap<String, Settings>,
+}
+
+impl ConfigFile {
+    /// 最上位に書かれた項目
+    fn settings(&self) -> Settings {
+        Settings {
+            digest: self.digest.clone(),
+            format: self.format.clone(),
+            encoding: self.encoding.clone(),
+            buffer_size: self.buffer_size,
+            exclude: self.exclude.clone(),
+            cache: self.cache,
+            cache_file: self.cache_file.clone(),
+        }
+    }
+}
+
+/// # 設定ファイルとプロファイルから決まった値
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// `-d`を省略したときのハッシュアルゴリズム
+    pub digest_algorithm: DigestAlgorithm,
+    /// `--format`を省略したときの出力形式
+    pub output_format: OutputFormat,
+    /// `--encoding`を省略したときのエンコーディング
+    pub encoding: TextEncoding,
+    /// `--buffer_size`を省略したときのバッファのバイト数
+    pub buffer_size: usize,
+    /// `--exclude`を省略したときに除くパターン
+    pub exclude: Vec<String>,
+    /// `--no_cache`を省略したときに、ハッシュ値のキャッシュを使うか デフォルトは使わない
+    pub cache: bool,
+    /// キャッシュファイルのパス ユーザーのキャッシュディレクトリが分からなければNone
+    pub cache_file: Option<PathBuf>,
+    /// 読み込んだ設定ファイル 優先度の低い順
+    pub sources: Vec<PathBuf>,
+    /// 選んだプロファイル
+    pub profile: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            digest_algorithm: DigestAlgorithm::default(),
+            output_format: OutputFormat::default(),
+            encoding: TextEncoding::Utf8,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            exclude: Vec::new(),
+            cache: false,
+            cache_file: cache::default_path(),
+            sources: Vec::new(),
+            profile: None,
+        }
+    }
+}
+
+impl Config {
+    /// 項目をすべて書いた設定
+    pub fn settings(&self) -> Settings {
+        Settings {
+            digest: Some(self.digest_algorithm.name().to_string()),
+            format: Some(self.output_format.name().to_string()),
+            encoding: Some(self.encoding.name().to_string()),
+            buffer_size: Some(self.buffer_size),
+            exclude: Some(self.exclude.clone()),
+            cache: Some(self.cache),
+            cache_file: self
+                .cache_file
+                .as_ref()
+                .map(|cache_file| cache_file.to_string_lossy().into_owned()),
+        }
+    }
+
+    /// # 設定をTOML形式で書き出す関数
+    /// 読み込んだファイルとプロファイルをコメントにして、そのまま設定ファイルとして使える形にします。
+    pub fn to_toml(&self) -> String {
+        let mut content = String::new();
+        if self.sources.is_empty() {
+            content.push_str(&format!("# {}\n", t!("config.no_source")));
+        }
+        for source in self.sources.iter() {
+            content.push_str(&format!(
+                "# {}\n",
+                t!("config.source", path = source.display())
+            ));
+        }
+        if let Some(profile) = self.profile.as_ref() {
+            content.push_str(&format!("# {}\n", t!("config.profile", name = profile)));
+        }
+        content.push_str(&toml::to_string(&self.settings()).unwrap_or_default());
+        content
+    }
+}
+
+/// # 設定を読み込む関数
+/// ユーザーとプロジェクトの設定ファイルを探して読み込みます。ファイルがなければデフォルト値を使います。
+/// ## 引数
+/// - `--profile`で選んだプロファイル
+/// ## 返り値
+/// Result型 読めない設定ファイルや誤った値、見つからないプロファイルはError あるいは、設定
+pub fn load(profile: Option<&str>) -> Result<Config, Error> {
+    let mut paths = Vec::new();
+    if let Some(user_file) = user_config_path() {
+        paths.push(user_file);
+    }
+    if let Some(project_file) = std::env::current_dir()
+        .ok()
+        .and_then(|dir| find_project_file(&dir))
+    {
+        // ホームディレクトリで実行したときなどに、同じファイルを二度読まない
+        if !paths.contains(&project_file) {
+            paths.push(project_file);
+        }
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::io(&t!("io.read_config", path = path.display()), e)),
+        };
+        files.push((path, content));
+    }
+    resolve(&files, profile)
+}
+
+/// # 設定ファイルの中身から設定を決める関数
+/// ## 引数
+/// - 設定ファイルのパスと中身 優先度の低い順
+/// - 選んだプロファイル
+fn resolve(files: &[(PathBuf, String)], profile: Option<&str>) -> Result<Config, Error> {
+    let mut config_files = Vec::new();
+    for (path, content) in files {
+        let config_file: ConfigFile = toml::from_str(content).map_err(|e| {
+            Error::InvalidArgument(t!("config.parse_failed", path = path.display(), error = e))
+        })?;
+        config_files.push(config_file);
+    }
+
+    let mut settings = Settings::default();
+    for config_file in config_files.iter() {
+        settings.merge(&config_file.settings());
+    }
+    if let Some(profile) = profile {
+        let profiles: Vec<&Settings> = config_files
+            .iter()
+            .filter_map(|config_file| config_file.profile.get(profile))
+            .collect();
+        if profiles.is_empty() {
+            return Err(Error::InvalidArgument(t!(
+                "config.unknown_profile",
+                name = profile
+            )));
+        }
+        for profile_settings in profiles {
+            settings.merge(profile_settings);
+        }
+    }
+
+    let invalid = |key: &str, value: &str| {
+        Error::InvalidArgument(t!("config.invalid_value", key = key, value = value))
+    };
+    let mut config = Config {
+        sources: files.iter().map(|(path, _)| path.clone()).collect(),
+        profile: profile.map(|profile| profile.to_string()),
+        ..Config::default()
+    };
+    if let Some(digest) = settings.digest.as_ref() {
+        config.digest_algorithm =
+            DigestAlgorithm::from_name(digest).ok_or_else(|| invalid("digest", digest))?;
+    }
+    if let Some(format) = settings.format.as_ref() {
+        config.output_format =
+            OutputFormat::from_name(format).ok_or_else(|| invalid("format", format))?;
+    }
+    if let Some(encoding) = settings.encoding.as_ref() {
+        config.encoding =
+            TextEncoding::from_name(encoding).ok_or_else(|| invalid("encoding", encoding))?;
+    }
+    if let Some(buffer_size) = settings.buffer_size {
+        if buffer_size == 0 {
+            return Err(invalid("buffer_size", "0"));
+        }
+        config.buffer_size = buffer_size;
+    }
+    if let Some(exclude) = settings.exclude {
+        for pattern in exclude.iter() {
+            glob::Pattern::new(pattern).map_err(|_| invalid("exclude", pattern))?;
+        }
+        config.exclude = exclude;
+    }
+    if let Some(cache) = settings.cache {
+        config.cache = cache;
+    }
+    if let Some(cache_file) = settings.cache_file {
+        if cache_file.is_empty() {
+            return Err(invalid("cache_file", ""));
+        }
+        config.cache_file = Some(PathBuf::from(cache_file));
+    }
+    Ok(config)
+}
+
+/// ユーザーの設定ファイルのパス
+#[cfg(target_os = "windows")]
+fn user_config_path() -> Option<PathBuf> {
+    std::env::var_os("APPDATA")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join("digest_tool").join("config.toml"))
+}
+
+/// ユーザーの設定ファイルのパス
+#[cfg(not(target_os = "windows"))]
+fn user_config_path() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            PathBuf::from(std::env::var_os("HOME").filter(|dir| !dir.is_empty())?).join(".config")
+        }
+    };
+    Some(config_dir.join("digest_tool").join("config.toml"))
+}
+
+/// dirから親へたどって、最初に見つかったプロジェクトの設定ファイル
+fn find_project_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn files(contents: &[&str]) -> Vec<(PathBuf, String)> {
+        contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| (PathBuf::from(format!("{}.toml", i)), content.to_string()))
+            .collect()
+    }
+
+    /// 後のファイルとプロファイルほど優先する
+    #[test]
+    fn ok_resolve() {
+        let files = files(&[
+            r#"
+digest = "sha2_512"
+format = "json"
+exclude = ["*.tmp"]
+
+[profile.release]
+digest = "sha3_512"
+buffer_size = 65536
+cache = true
+"#,
+            r#"
+format = "csv"
+
+[profile.release]
+exclude = [".git"]
+"#,
+        ]);
+        let config = resolve(&files, None).unwrap();
+        assert_eq!(DigestAlgorithm::Sha2_512, config.digest_algorithm);
+        assert_eq!(OutputFormat::Csv, config.output_format);
+        assert_eq!(DEFAULT_BUFFER_SIZE, config.buffer_size);
+        assert_eq!(vec!["*.tmp".to_string()], config.exclude);
+        assert!(!config.cache);
+
+        let config = resolve(&files, Some("release")).unwrap();
+        assert_eq!(DigestAlgorithm::Sha3_512, config.digest_algorithm);
+        assert_eq!(OutputFormat::Csv, config.output_format);
+        assert_eq!(65536, config.buffer_size);
+        assert_eq!(vec![".git".to_string()], config.exclude);
+        assert!(config.cache);
+    }
+
+    /// 設定ファイルがなければデフォルト値
+    #[test]
+    fn ok_resolve_default() {
+        assert_eq!(Config::default(), resolve(&[], None).unwrap());
+    }
+
+    /// 誤った値、知らない項目、見つからないプロファイルは引数の誤り
+    #[test]
+    fn ng_resolve() {
+        for content in [
+            r#"digest = "sha256""#,
+            r#"buffer_size = 0"#,
+            r#"exclude = ["[""]"#,
+            r#"cache = "yes""#,
+            r#"cache_file = """#,
+            r#"algorithm = "sha2_512""#,
+        ]
+        .iter()
+        {
+            let error = resolve(&files(&[content]), None).unwrap_err();
+            assert_eq!(2, error.exit_code(), "{}", content);
+        }
+        let error = resolve(&files(&[r#"digest = "md5""#]), Some("release")).unwrap_err();
+        assert_eq!(2, error.exit_code());
+    }
+
+    /// 書き出した設定をそのまま読み込める
+    #[test]
+    fn ok_to_toml() {
+        let config = resolve(
+            &files(&[r#"
+digest = "md5"
+exclude = ["target/*", "*.bak"]
+cache_file = "/var/cache/digest_tool/share"
+"#]),
+            None,
+        )
+        .unwrap();
+        let config_again = resolve(&files(&[&config.to_toml()]), None).unwrap();
+        assert_eq!(config.settings(), config_again.settings());
+    }
+}
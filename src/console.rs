@@ -0,0 +1,39 @@
+//! # コンソールモジュール
+//! 終了前にEnterキーを待つかどうかを決めます。
+//! スクリプトやCIから使うときに止まらないように、待つのは次の場合だけです。
+//! - `--pause`が指定された (コンテクストメニューのコマンドは`--pause`付きで登録します)
+//! - Windowsで、エクスプローラーなどから起動されて、このプロセスだけのコンソールが作られた
+
+/// # 終了待ちをするか判断する関数
+/// ## 引数
+/// - `--pause`が指定されたか
+pub fn should_pause(requested: bool) -> bool {
+    requested || owns_console()
+}
+
+/// # 終了待ち関数
+/// 結果を読めるように、Enterキーが押されるまで待ちます。案内は標準エラー出力に書き出します。
+pub fn wait_enter() {
+    eprintln!("{}", t!("console.press_enter"));
+    let mut word = String::new();
+    std::io::stdin().read_line(&mut word).ok();
+}
+
+/// このプロセスのためだけにコンソールが作られたか
+/// コマンドプロンプトから起動された場合は、コマンドプロンプトとコンソールを共有しています。
+#[cfg(target_os = "windows")]
+pub fn owns_console() -> bool {
+    let mut process_ids = [0u32; 2];
+    let count = unsafe {
+        winapi::um::wincon::GetConsoleProcessList(
+            process_ids.as_mut_ptr(),
+            process_ids.len() as u32,
+        )
+    };
+    count == 1
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn owns_console() -> bool {
+    false
+}
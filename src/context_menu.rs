@@ -26,7 +26,7 @@ pub fn set_to_context_menu() -> Result<(), std::io::Error> {
         r"DigestTool\shell\sha2_256\Command",
         r"'(default)'",
         &format!(
-            "'\"{}\" -i \"%V\"'",
+            "'\"{}\" hash --pause -- \"%V\"'",
             std::env::current_exe().unwrap().display()
         ),
     )?;
@@ -34,7 +34,7 @@ pub fn set_to_context_menu() -> Result<(), std::io::Error> {
         r"DigestTool\shell\sha2_512\Command",
         r"'(default)'",
         &format!(
-            "'\"{}\" -i \"%V\" -d sha2_512'",
+            "'\"{}\" hash --pause -d sha2_512 -- \"%V\"'",
             std::env::current_exe().unwrap().display()
         ),
     )?;
@@ -42,7 +42,7 @@ pub fn set_to_context_menu() -> Result<(), std::io::Error> {
         r"DigestTool\shell\sha3_256\Command",
         r"'(default)'",
         &format!(
-            "'\"{}\" -i \"%V\" -d sha3_256'",
+            "'\"{}\" hash --pause -d sha3_256 -- \"%V\"'",
             std::env::current_exe().unwrap().display()
         ),
     )?;
@@ -50,7 +50,7 @@ pub fn set_to_context_menu() -> Result<(), std::io::Error> {
         r"DigestTool\shell\sha3_512\Command",
         r"'(default)'",
         &format!(
-            "'\"{}\" -i \"%V\" -d sha3_512'",
+            "'\"{}\" hash --pause -d sha3_512 -- \"%V\"'",
             std::env::current_exe().unwrap().display()
         ),
     )?;
@@ -74,7 +74,7 @@ fn set_key_to_directory_background(post_path: &str) -> Result<(), std::io::Error
     if !result_set_key.status.success() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            "レジストリの編集ができませんでした",
+            t!("context_menu.registry_failed"),
         ));
     };
     Ok(())
@@ -102,7 +102,7 @@ fn set_property_to_directory_background(
     if !result_set_key.status.success() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            "レジストリの編集ができませんでした",
+            t!("context_menu.registry_failed"),
         ));
     }
     Ok(())
@@ -124,7 +124,7 @@ fn set_key_to_faile_menu(post_path: &str) -> Result<(), std::io::Error> {
     if !result_set_key.status.success() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            "レジストリの編集ができませんでした",
+            t!("context_menu.registry_failed"),
         ));
     };
     Ok(())
@@ -152,7 +152,7 @@ fn set_property_to_faile_menu(
     if !result_set_key.status.success() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            "レジストリの編集ができませんでした",
+            t!("context_menu.registry_failed"),
         ));
     }
     Ok(())
@@ -179,7 +179,7 @@ fn remove_key_from_dircetory_background(post_path: &str) -> Result<(), std::io::
     if !result_remove_key.status.success() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            "レジストリの編集ができませんでした",
+            t!("context_menu.registry_failed"),
         ));
     }
     Ok(())
@@ -200,7 +200,7 @@ fn remove_key_from_file_menu(post_path: &str) -> Result<(), std::io::Error> {
     if !result_remove_key.status.success() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            "レジストリの編集ができませんでした",
+            t!("context_menu.registry_failed"),
         ));
     }
     Ok(())
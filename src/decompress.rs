@@ -0,0 +1,116 @@
+//! # 展開モジュール
+//! 圧縮されたデータを展開しながら読み込むリーダーを作ります。
+//! 圧縮形式は指定するか、先頭のマジックナンバーから自動判別します。
+
+/// 圧縮形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    /// 先頭のマジックナンバーから判別する
+    Auto,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// CLI引数で指定する名前から圧縮形式を取得します。
+    pub fn from_name(name: &str) -> Option<Compression> {
+        match name {
+            "auto" => Some(Compression::Auto),
+            "gzip" => Some(Compression::Gzip),
+            "bzip2" => Some(Compression::Bzip2),
+            "xz" => Some(Compression::Xz),
+            "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// CLI引数で指定する名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::Auto => "auto",
+            Compression::Gzip => "gzip",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+/// # 圧縮形式の判別関数
+/// readerの先頭を読み進めずに覗き、マジックナンバーから圧縮形式を判別します。
+/// ## 返り値
+/// Result型 std::ioのError あるいは、圧縮形式 圧縮されていなければNone
+pub fn detect(reader: &mut impl std::io::BufRead) -> Result<Option<Compression>, std::io::Error> {
+    let head = reader.fill_buf()?;
+    let compression = if head.starts_with(&[0x1f, 0x8b]) {
+        Some(Compression::Gzip)
+    } else if head.starts_with(b"BZh") {
+        Some(Compression::Bzip2)
+    } else if head.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        Some(Compression::Xz)
+    } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Some(Compression::Zstd)
+    } else {
+        None
+    };
+    Ok(compression)
+}
+
+/// # 展開リーダーを作る関数
+/// ## 引数
+/// - 圧縮されたデータのreader
+/// - 圧縮形式 Autoのときは判別する
+/// ## 返り値
+/// Result型 std::ioのError あるいは、(展開後のデータのreader, 実際の圧縮形式)
+/// Autoで圧縮されていないと判別したときは、readerをそのまま返し、圧縮形式はNoneになります。
+pub fn decoder<'a, R: std::io::BufRead + 'a>(
+    mut reader: R,
+    compression: Compression,
+) -> Result<(Box<dyn std::io::Read + 'a>, Option<Compression>), std::io::Error> {
+    let compression = match compression {
+        Compression::Auto => detect(&mut reader)?,
+        compression => Some(compression),
+    };
+    let decoder: Box<dyn std::io::Read + 'a> = match compression {
+        Some(Compression::Gzip) => Box::new(flate2::bufread::MultiGzDecoder::new(reader)),
+        Some(Compression::Bzip2) => Box::new(bzip2::bufread::MultiBzDecoder::new(reader)),
+        Some(Compression::Xz) => Box::new(xz2::bufread::XzDecoder::new_multi_decoder(reader)),
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::with_buffer(reader)?),
+        Some(Compression::Auto) | None => Box::new(reader),
+    };
+    Ok((decoder, compression))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{Read, Write};
+
+    /// 自動判別で展開できる
+    #[test]
+    fn ok_auto_decoder() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"digest tool").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let (mut decoder, compression) = decoder(&compressed[..], Compression::Auto).unwrap();
+        let mut decompressed = Vec::new();
+        decoder.read_to_end(&mut decompressed).unwrap();
+
+        assert_eq!(Some(Compression::Gzip), compression);
+        assert_eq!(b"digest tool".to_vec(), decompressed);
+    }
+
+    /// 圧縮されていなければそのまま読み込む
+    #[test]
+    fn ok_auto_decoder_uncompressed() {
+        let (mut decoder, compression) = decoder(&b"plain"[..], Compression::Auto).unwrap();
+        let mut data = Vec::new();
+        decoder.read_to_end(&mut data).unwrap();
+
+        assert_eq!(None, compression);
+        assert_eq!(b"plain".to_vec(), data);
+    }
+}
@@ -0,0 +1,110 @@
+//! # エラーモジュール
+//! アプリ全体で使うエラーと、終了コードをまとめます。
+//! メッセージは標準エラー出力に書き出し、スクリプトからは終了コードで見分けられるようにします。
+//!
+//! | 終了コード | 意味 |
+//! |---|---|
+//! | 0 | 成功 |
+//! | 1 | 検証で一致しなかった (`check`、`compare`、`baseline compare`、`xattr scrub`、`--expect`) |
+//! | 2 | CLI引数が誤っている |
+//! | 3 | 入力が指定されていない、または見つからない |
+//! | 4 | 読み書きに失敗した |
+//! | 5 | コンテクストメニュー、GUI、拡張属性など、OSとの連携に失敗した |
+
+/// # エラー
+#[derive(Debug)]
+pub enum Error {
+    /// 検証で一致しなかった
+    Mismatch(String),
+    /// CLI引数が誤っている
+    InvalidArgument(String),
+    /// 入力が指定されていない、または見つからない
+    MissingInput(String),
+    /// 読み書きに失敗した
+    Io {
+        /// 何をしようとして失敗したか
+        context: String,
+        source: std::io::Error,
+    },
+    /// OSとの連携に失敗した
+    Platform(String),
+}
+
+impl Error {
+    /// # 入出力エラーを作る関数
+    /// ファイルが見つからないときは、入力が見つからないエラーにします。
+    pub fn io(context: &str, source: std::io::Error) -> Error {
+        if source.kind() == std::io::ErrorKind::NotFound {
+            Error::MissingInput(format!("{}: {}", context, source))
+        } else {
+            Error::Io {
+                context: context.to_string(),
+                source,
+            }
+        }
+    }
+
+    /// 終了コード
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Mismatch(_) => 1,
+            Error::InvalidArgument(_) => 2,
+            Error::MissingInput(_) => 3,
+            Error::Io { .. } => 4,
+            Error::Platform(_) => 5,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Mismatch(message)
+            | Error::InvalidArgument(message)
+            | Error::MissingInput(message)
+            | Error::Platform(message) => write!(f, "{}", message),
+            Error::Io { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 種類ごとに違う終了コードになる
+    #[test]
+    fn ok_exit_code() {
+        let errors = [
+            Error::Mismatch(String::new()),
+            Error::InvalidArgument(String::new()),
+            Error::MissingInput(String::new()),
+            Error::io(
+                "",
+                std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+            ),
+            Error::Platform(String::new()),
+        ];
+        let codes: Vec<i32> = errors.iter().map(|error| error.exit_code()).collect();
+        assert_eq!(vec![1, 2, 3, 4, 5], codes);
+    }
+
+    /// 見つからないファイルは入力のエラーにする
+    #[test]
+    fn ok_io_not_found() {
+        let error = Error::io(
+            "ファイルにアクセスできませんでした。",
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+        );
+        assert_eq!(3, error.exit_code());
+    }
+}
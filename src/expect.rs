@@ -0,0 +1,220 @@
+//! # 期待値モジュール
+//! ベンダーのページなどに載っているハッシュ値と、計算したハッシュ値を比べます。
+//! 期待値は以下の形で受け取れます。
+//! - 16進数 大文字と小文字のどちらでもかまいません
+//! - base64 (`+/`と`-_`のどちらでも、パディングの有無も問いません)
+//! - アルゴリズム名の接頭辞付き `sha256:<hex>`、`SHA256=<hex>`、`sha256-<base64>`(Subresource Integrity)など
+//!
+//! 接頭辞がなければ長さからアルゴリズムを推定します。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+
+/// 接頭辞に使えるアルゴリズム名 長いものから順に試します
+const PREFIXES: [(&str, DigestAlgorithm); 14] = [
+    ("sha3-256", DigestAlgorithm::Sha3_256),
+    ("sha3_256", DigestAlgorithm::Sha3_256),
+    ("sha3-512", DigestAlgorithm::Sha3_512),
+    ("sha3_512", DigestAlgorithm::Sha3_512),
+    ("sha2_256", DigestAlgorithm::Sha2_256),
+    ("sha2_512", DigestAlgorithm::Sha2_512),
+    ("sha-256", DigestAlgorithm::Sha2_256),
+    ("sha-512", DigestAlgorithm::Sha2_512),
+    ("sha256", DigestAlgorithm::Sha2_256),
+    ("sha512", DigestAlgorithm::Sha2_512),
+    ("crc32", DigestAlgorithm::Crc32),
+    ("sha-1", DigestAlgorithm::Sha1),
+    ("sha1", DigestAlgorithm::Sha1),
+    ("md5", DigestAlgorithm::Md5),
+];
+
+/// # 期待するハッシュ値
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedDigest {
+    /// 接頭辞で指定されたアルゴリズム
+    pub digest_algorithm: Option<DigestAlgorithm>,
+    pub hash_value: Vec<u8>,
+}
+
+impl std::str::FromStr for ExpectedDigest {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<ExpectedDigest, String> {
+        let text = text.trim();
+        let lower = text.to_ascii_lowercase();
+        let prefix = PREFIXES.iter().find_map(|(name, digest_algorithm)| {
+            let rest = lower.strip_prefix(name)?;
+            if rest.starts_with([':', '=', '-']) {
+                Some((name.len() + 1, *digest_algorithm))
+            } else {
+                None
+            }
+        });
+        let (body, digest_algorithm) = match prefix {
+            Some((length, digest_algorithm)) => (&text[length..], Some(digest_algorithm)),
+            None => (text, None),
+        };
+        let body: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+
+        // 16進数として読めて、長さがアルゴリズムに合えば16進数として扱う
+        let is_valid_length = |hash_value: &Vec<u8>| match digest_algorithm {
+            Some(digest_algorithm) => hash_value.len() == digest_algorithm.digest_length(),
+            None => DigestAlgorithm::from_digest_length(hash_value.len()).is_some(),
+        };
+        let hash_value = hash::from_hex(&body)
+            .filter(is_valid_length)
+            .or_else(|| from_base64(&body).filter(is_valid_length));
+        match hash_value {
+            Some(hash_value) => Ok(ExpectedDigest {
+                digest_algorithm,
+                hash_value,
+            }),
+            None => Err(t!("expect.unreadable", text = text)),
+        }
+    }
+}
+
+/// base64を読み込みます。URLセーフな文字とパディングの省略も受け付けます。
+fn from_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    [base64::STANDARD_NO_PAD, base64::URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|config| base64::decode_config(text, *config).ok())
+}
+
+/// # ハッシュアルゴリズムを決める関数
+/// 接頭辞、`-d`の指定、期待値の長さの順に決めます。
+/// 同じ長さのアルゴリズムが複数あるときは、SHA-2を選び、ほかの候補を返します。
+/// ## 返り値
+/// Result型 接頭辞や指定と長さが合わなければエラーメッセージ あるいは、(アルゴリズム, ほかの候補)
+pub fn resolve_algorithm(
+    expected: &ExpectedDigest,
+    specified: Option<DigestAlgorithm>,
+) -> Result<(DigestAlgorithm, Vec<DigestAlgorithm>), String> {
+    let length = expected.hash_value.len();
+    match (expected.digest_algorithm, specified) {
+        (Some(prefixed), Some(specified)) if prefixed != specified => Err(t!(
+            "expect.prefix_conflict",
+            prefix = prefixed.bsd_tag(),
+            specified = specified.bsd_tag()
+        )),
+        (Some(digest_algorithm), _) => Ok((digest_algorithm, Vec::new())),
+        (None, Some(specified)) if specified.digest_length() != length => Err(t!(
+            "expect.length_mismatch",
+            length = length,
+            algorithm = specified.bsd_tag()
+        )),
+        (None, Some(specified)) => Ok((specified, Vec::new())),
+        (None, None) => {
+            let digest_algorithm = DigestAlgorithm::from_digest_length(length)
+                .ok_or_else(|| t!("expect.unknown_length", length = length))?;
+            let others = DigestAlgorithm::ALL
+                .iter()
+                .copied()
+                .filter(|other| *other != digest_algorithm && other.digest_length() == length)
+                .collect();
+            Ok((digest_algorithm, others))
+        }
+    }
+}
+
+/// 比べる時間が、どこで違うかによらないように比べます。
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+        == 0
+}
+
+/// 2つのハッシュ値を16進数で並べたときに、違う文字の下に`^`を付けた行を返します。
+pub fn diff_markers(expected: &[u8], actual: &[u8]) -> String {
+    let expected = hash::to_hex(expected);
+    let actual = hash::to_hex(actual);
+    let length = expected.len().max(actual.len());
+    let markers: String = (0..length)
+        .map(|i| {
+            if expected.as_bytes().get(i) == actual.as_bytes().get(i) {
+                ' '
+            } else {
+                '^'
+            }
+        })
+        .collect();
+    markers.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SHA256_EMPTY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    /// 16進数、base64、接頭辞付きの期待値を読み込める
+    #[test]
+    fn ok_parse() {
+        let hash_value = hash::from_hex(SHA256_EMPTY).unwrap();
+        let expected = |digest_algorithm| ExpectedDigest {
+            digest_algorithm,
+            hash_value: hash_value.clone(),
+        };
+        let parse = |text: &str| text.parse::<ExpectedDigest>().unwrap();
+
+        assert_eq!(expected(None), parse(&SHA256_EMPTY.to_uppercase()));
+        assert_eq!(
+            expected(None),
+            parse("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")
+        );
+        assert_eq!(
+            expected(Some(DigestAlgorithm::Sha2_256)),
+            parse(&format!("sha256:{}", SHA256_EMPTY))
+        );
+        assert_eq!(
+            expected(Some(DigestAlgorithm::Sha2_256)),
+            parse("sha256-47DEQpj8HBSa-_TImW-5JCeuQeRkm5NMpJWZG3hSuFU")
+        );
+        assert_eq!(
+            expected(Some(DigestAlgorithm::Sha3_256)),
+            parse(&format!("SHA3-256={}", SHA256_EMPTY))
+        );
+    }
+
+    /// 長さが合わなければエラー
+    #[test]
+    fn ng_parse() {
+        assert!("abcd12".parse::<ExpectedDigest>().is_err());
+        assert!(format!("md5:{}", SHA256_EMPTY)
+            .parse::<ExpectedDigest>()
+            .is_err());
+        assert!("not a digest".parse::<ExpectedDigest>().is_err());
+        // 符号付きの数として読めても16進数ではない
+        assert!("md5:+a+b+c+d+e+f+0+1+2+3+4+5+6+7+8+9"
+            .parse::<ExpectedDigest>()
+            .is_err());
+    }
+
+    /// 同じ長さのアルゴリズムが複数あればSHA-2を選び、ほかの候補も返す
+    #[test]
+    fn ok_resolve_algorithm() {
+        let expected: ExpectedDigest = SHA256_EMPTY.parse().unwrap();
+        assert_eq!(
+            (DigestAlgorithm::Sha2_256, vec![DigestAlgorithm::Sha3_256]),
+            resolve_algorithm(&expected, None).unwrap()
+        );
+        assert_eq!(
+            (DigestAlgorithm::Sha3_256, Vec::new()),
+            resolve_algorithm(&expected, Some(DigestAlgorithm::Sha3_256)).unwrap()
+        );
+        assert!(resolve_algorithm(&expected, Some(DigestAlgorithm::Md5)).is_err());
+    }
+
+    /// 違う文字の位置に印を付ける
+    #[test]
+    fn ok_diff_markers() {
+        assert!(constant_time_eq(&[1, 2], &[1, 2]));
+        assert!(!constant_time_eq(&[1, 2], &[1, 3]));
+        assert_eq!("   ^", diff_markers(&[0x12, 0x34], &[0x12, 0x35]));
+    }
+}
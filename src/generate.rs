@@ -0,0 +1,190 @@
+//! # 補完スクリプトとmanページを作るモジュール
+//! `cli_arg_accepter::command()`の定義から、シェルの補完スクリプトとroff形式のmanページを作ります。
+//! 作ったものはリポジトリの`completions/`と`man/`に置き、定義とずれていないかをテストで確かめます。
+
+use crate::cli_arg_accepter;
+use crate::error::Error;
+use clap::Command;
+use clap_complete::Shell;
+use std::io::Write;
+
+/// 補完スクリプトやmanページを書き出すときのコマンド名
+const BIN_NAME: &str = "digest_tool";
+
+/// 作るもの
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+    /// roff形式のmanページ
+    Man,
+}
+
+impl Target {
+    /// CLI引数の名前から作るものを取得する
+    pub fn from_name(name: &str) -> Option<Target> {
+        match name {
+            "bash" => Some(Target::Bash),
+            "zsh" => Some(Target::Zsh),
+            "fish" => Some(Target::Fish),
+            "powershell" => Some(Target::PowerShell),
+            "man" => Some(Target::Man),
+            _ => None,
+        }
+    }
+}
+
+/// # 補完スクリプトかmanページを書き出す関数
+/// ## 引数
+/// - 作るもの
+/// - manページを作るサブコマンド `integrate-install`のように`-`でつなぎます。Noneのときはdigest_tool自体
+/// - 書き出し先
+/// ## 返り値
+/// Result型 サブコマンドが誤っていればInvalidArgument
+pub fn generate(
+    target: Target,
+    subcommand: Option<&str>,
+    output: &mut dyn Write,
+) -> Result<(), Error> {
+    let mut command = cli_arg_accepter::visible_command().bin_name(BIN_NAME);
+    let shell = match (target, subcommand) {
+        (Target::Man, subcommand) => return man_page(command, subcommand, output),
+        (_, Some(_)) => {
+            return Err(Error::InvalidArgument(t!(
+                "generate.subcommand_for_man_only"
+            )));
+        }
+        (Target::Bash, None) => Shell::Bash,
+        (Target::Zsh, None) => Shell::Zsh,
+        (Target::Fish, None) => Shell::Fish,
+        (Target::PowerShell, None) => Shell::PowerShell,
+    };
+    clap_complete::generate(shell, &mut command, BIN_NAME, output);
+    Ok(())
+}
+
+/// # manページを書き出す関数
+/// サブコマンドのmanページは`digest_tool-hash`のような名前にします。
+/// digest_toolのmanページのSUBCOMMANDSから、この名前で参照されます。
+fn man_page(
+    mut command: Command<'static>,
+    subcommand: Option<&str>,
+    output: &mut dyn Write,
+) -> Result<(), Error> {
+    // グローバルなオプションをサブコマンドにも加える
+    command.build();
+    let mut name = BIN_NAME.to_string();
+    if let Some(subcommand) = subcommand {
+        for part in subcommand.split('-') {
+            let sub_command = command
+                .get_subcommands()
+                .find(|sub_command| !sub_command.is_hide_set() && sub_command.get_name() == part)
+                .cloned()
+                .ok_or_else(|| {
+                    Error::InvalidArgument(t!(
+                        "generate.unknown_subcommand",
+                        subcommand = subcommand
+                    ))
+                })?;
+            command = sub_command;
+            name = format!("{}-{}", name, part);
+        }
+    }
+    clap_mangen::Man::new(command.name(name))
+        .source(format!("{} {}", BIN_NAME, env!("CARGO_PKG_VERSION")))
+        .render(output)
+        .map_err(|e| Error::io(&t!("io.write_file"), e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// # manページを作るサブコマンドの一覧
+    /// `integrate-install`のように`-`でつないだ名前を、親、子の順に返します。
+    fn man_page_subcommands() -> Vec<String> {
+        fn collect(command: &Command<'static>, prefix: Option<&str>, names: &mut Vec<String>) {
+            // helpはclapが加えるサブコマンドで、ヘルプを表示するだけなので除く
+            for sub_command in command.get_subcommands().filter(|sub_command| {
+                !sub_command.is_hide_set() && sub_command.get_name() != "help"
+            }) {
+                let name = match prefix {
+                    Some(prefix) => format!("{}-{}", prefix, sub_command.get_name()),
+                    None => sub_command.get_name().to_string(),
+                };
+                names.push(name.clone());
+                collect(sub_command, Some(&name), names);
+            }
+        }
+        let mut command = cli_arg_accepter::visible_command();
+        command.build();
+        let mut names = Vec::new();
+        collect(&command, None, &mut names);
+        names
+    }
+
+    /// 作るもの、CLI引数の名前、リポジトリに置くファイルのリポジトリのルートからのパス
+    const COMPLETIONS: [(Target, &str, &str); 4] = [
+        (Target::Bash, "bash", "completions/digest_tool.bash"),
+        (Target::Zsh, "zsh", "completions/_digest_tool"),
+        (Target::Fish, "fish", "completions/digest_tool.fish"),
+        (
+            Target::PowerShell,
+            "powershell",
+            "completions/_digest_tool.ps1",
+        ),
+    ];
+
+    /// リポジトリに置いたファイルと、CLI引数の定義から作ったものを比べる
+    fn assert_in_sync(target: Target, subcommand: Option<&str>, path: &str, args: &str) {
+        let mut generated = Vec::new();
+        generate(target, subcommand, &mut generated).unwrap();
+        let checked_in = std::fs::read(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(path))
+            .unwrap_or_default();
+        assert!(
+            generated == checked_in,
+            "{}がCLI引数の定義とずれています。`digest_tool --lang ja generate {} -o {}`で作り直してください。",
+            path,
+            args,
+            path
+        );
+    }
+
+    /// リポジトリに置いた補完スクリプトが、CLI引数の定義から作ったものと同じ
+    #[test]
+    fn ok_completions_in_sync() {
+        for (target, name, path) in COMPLETIONS.iter() {
+            assert_eq!(Some(*target), Target::from_name(name));
+            assert_in_sync(*target, None, path, name);
+        }
+    }
+
+    /// リポジトリに置いたmanページが、CLI引数の定義から作ったものと同じ
+    /// サブコマンドを加えたら、そのmanページも作ります。
+    #[test]
+    fn ok_man_pages_in_sync() {
+        assert_in_sync(Target::Man, None, "man/digest_tool.1", "man");
+        let subcommands = man_page_subcommands();
+        assert!(subcommands.contains(&"integrate-install".to_string()));
+        assert!(!subcommands.contains(&"generate".to_string()));
+        for subcommand in subcommands {
+            assert_in_sync(
+                Target::Man,
+                Some(&subcommand),
+                &format!("man/digest_tool-{}.1", subcommand),
+                &format!("man {}", subcommand),
+            );
+        }
+    }
+
+    /// manページ以外でサブコマンドを指定したり、ないサブコマンドを指定したりできない
+    #[test]
+    fn ng_generate() {
+        let mut generated = Vec::new();
+        assert!(generate(Target::Bash, Some("hash"), &mut generated).is_err());
+        assert!(generate(Target::Man, Some("generate"), &mut generated).is_err());
+        assert!(generate(Target::Man, Some("hash-install"), &mut generated).is_err());
+    }
+}
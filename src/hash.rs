@@ -3,7 +3,10 @@
 //! generic-array = "0.14.4"
 //! typenum = "1.14.0"
 
+use crate::cli_arg_accepter::DigestAlgorithm;
 use generic_array::GenericArray;
+use md5::Md5;
+use sha1::Sha1;
 use sha2::{Digest, Sha256, Sha512};
 use sha3::{Sha3_256, Sha3_512};
 use typenum::{
@@ -120,6 +123,166 @@ pub fn sha3_512(
     Ok((data_size, hash_value))
 }
 
+/// # ハッシュ計算器
+/// ハッシュアルゴリズムごとの計算器をまとめたものです。
+enum Hasher {
+    Sha2_256(Sha256),
+    Sha2_512(Sha512),
+    Sha3_256(Sha3_256),
+    Sha3_512(Sha3_512),
+    Crc32(crc32fast::Hasher),
+    Md5(Md5),
+    Sha1(Sha1),
+}
+
+impl Hasher {
+    fn new(digest_algorithm: &DigestAlgorithm) -> Hasher {
+        match digest_algorithm {
+            DigestAlgorithm::Sha2_256 => Hasher::Sha2_256(Sha256::new()),
+            DigestAlgorithm::Sha2_512 => Hasher::Sha2_512(Sha512::new()),
+            DigestAlgorithm::Sha3_256 => Hasher::Sha3_256(Sha3_256::new()),
+            DigestAlgorithm::Sha3_512 => Hasher::Sha3_512(Sha3_512::new()),
+            DigestAlgorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            DigestAlgorithm::Md5 => Hasher::Md5(Md5::new()),
+            DigestAlgorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha2_256(hasher) => hasher.update(data),
+            Hasher::Sha2_512(hasher) => hasher.update(data),
+            Hasher::Sha3_256(hasher) => hasher.update(data),
+            Hasher::Sha3_512(hasher) => hasher.update(data),
+            Hasher::Crc32(hasher) => hasher.update(data),
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Sha1(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha2_256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha2_512(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha3_256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha3_512(hasher) => hasher.finalize().to_vec(),
+            Hasher::Crc32(hasher) => hasher.finalize().to_be_bytes().to_vec(),
+            Hasher::Md5(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha1(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+/// # 複数のハッシュ値を同時に計算するライター
+/// 書き込まれたデータを、すべてのハッシュ計算器に渡します。
+pub struct MultiHasher {
+    hashers: Vec<(DigestAlgorithm, Hasher)>,
+}
+
+impl MultiHasher {
+    pub fn new(digest_algorithms: &[DigestAlgorithm]) -> MultiHasher {
+        let hashers = digest_algorithms
+            .iter()
+            .map(|digest_algorithm| (*digest_algorithm, Hasher::new(digest_algorithm)))
+            .collect();
+        MultiHasher { hashers }
+    }
+
+    /// 計算結果を(ハッシュアルゴリズム, ハッシュ値)の配列で返却します。
+    pub fn finalize(self) -> Vec<(DigestAlgorithm, Vec<u8>)> {
+        self.hashers
+            .into_iter()
+            .map(|(digest_algorithm, hasher)| (digest_algorithm, hasher.finalize()))
+            .collect()
+    }
+}
+
+impl std::io::Write for MultiHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for (_, hasher) in self.hashers.iter_mut() {
+            hasher.update(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// #複数アルゴリズムのハッシュ関数
+/// readerを一度だけ読み取り、指定されたすべてのアルゴリズムのハッシュ値を返却します。
+/// ## 引数
+/// - reader
+/// - ハッシュアルゴリズムの配列
+/// ## 返り値
+/// Result型 std::ioのError あるいは、(ファイルサイズ, (ハッシュアルゴリズム, ハッシュ値)の配列)
+#[allow(clippy::type_complexity)]
+pub fn multi_digest(
+    input_reader: &mut impl std::io::Read,
+    digest_algorithms: &[DigestAlgorithm],
+    progress_bar: indicatif::ProgressBar,
+) -> Result<(u64, Vec<(DigestAlgorithm, Vec<u8>)>), std::io::Error> {
+    let hasher = MultiHasher::new(digest_algorithms);
+    let mut hasher = std::io::BufWriter::new(hasher);
+
+    // リーダーを読み込んでハッシュ値を計算する
+    let data_size = std::io::copy(
+        &mut *input_reader,
+        &mut progress_bar.wrap_write(&mut hasher),
+    )?;
+    progress_bar.finish();
+
+    // 結果を取り出す
+    let hasher = hasher.into_inner()?;
+    Ok((data_size, hasher.finalize()))
+}
+
+/// #ハッシュ関数
+/// readerを受け取って、指定されたアルゴリズムのハッシュ値を返却します。
+/// ## 引数
+/// - reader
+/// - ハッシュアルゴリズム
+/// ## 返り値
+/// Result型 std::ioのError あるいは、(ファイルサイズ, ハッシュ値)
+pub fn digest(
+    input_reader: &mut impl std::io::Read,
+    digest_algorithm: DigestAlgorithm,
+    progress_bar: indicatif::ProgressBar,
+) -> Result<(u64, Vec<u8>), std::io::Error> {
+    let (data_size, mut hash_values) =
+        multi_digest(input_reader, &[digest_algorithm], progress_bar)?;
+    let (_, hash_value) = hash_values.remove(0);
+    Ok((data_size, hash_value))
+}
+
+/// メモリ上のバイト列のハッシュ値を返却します。
+pub fn digest_bytes(data: &[u8], digest_algorithm: DigestAlgorithm) -> Vec<u8> {
+    let mut hasher = Hasher::new(&digest_algorithm);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// ハッシュ値を小文字の16進数文字列に変換します。
+pub fn to_hex(hash_value: &[u8]) -> String {
+    hash_value
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 16進数文字列(大文字小文字は問わない)をハッシュ値に変換します。16進数でなければNoneを返却します。
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    // from_str_radixは先頭の`+`も受け付けるので、先に16進数の文字だけか確かめる
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -161,4 +324,44 @@ mod test {
         assert_eq!(expected_size, data_size);
         assert_ne!(expected_hash, hash_value[..]);
     }
+
+    /// 複数アルゴリズムを同時に計算しても単体の計算と同じ結果になる
+    #[test]
+    fn ok_multi_digest() {
+        let progress_bar = indicatif::ProgressBar::hidden();
+        let mut input_reader: &[u8] = b"abc";
+        let (data_size, hash_values) = multi_digest(
+            &mut input_reader,
+            &[DigestAlgorithm::Sha2_256, DigestAlgorithm::Sha3_256],
+            progress_bar,
+        )
+        .unwrap();
+
+        assert_eq!(3, data_size);
+        assert_eq!(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            to_hex(&hash_values[0].1)
+        );
+        assert_eq!(
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            to_hex(&hash_values[1].1)
+        );
+    }
+
+    /// 16進数文字列と相互に変換できる
+    #[test]
+    fn ok_hex() {
+        assert_eq!(Some(vec![0x0a, 0xbc]), from_hex("0ABc"));
+        assert_eq!("0abc", to_hex(&[0x0a, 0xbc]));
+        assert_eq!(None, from_hex("0ab"));
+        assert_eq!(None, from_hex("zz"));
+    }
+
+    /// 符号などの16進数でない文字を含めば誤り
+    #[test]
+    fn ng_hex() {
+        for hex in ["+a+b", "+0", "-1", " 0", "0x"].iter() {
+            assert_eq!(None, from_hex(hex), "{}", hex);
+        }
+    }
 }
@@ -0,0 +1,230 @@
+//! # hashdeepモジュール
+//! hashdeep(md5deep)のファイル形式を読み書きします。
+//! ```text
+//! %%%% HASHDEEP-1.0
+//! %%%% size,md5,sha256,filename
+//! ## Invoked from: /home/user
+//! ## $ hashdeep -r dir
+//! ##
+//! 5,<md5>,<sha256>,dir/a.txt
+//! ```
+//! 2行目の列名でハッシュアルゴリズムと並び順を表します。ファイル名は最後の列で、カンマを含むことがあります。
+
+use crate::checksum_file::{ChecksumEntry, ChecksumFile};
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+
+/// hashdeep形式の1行目
+pub const HASHDEEP_HEADER: &str = "%%%% HASHDEEP-1.0";
+
+/// hashdeepの列名からハッシュアルゴリズムを取得します。
+/// tigerやwhirlpoolのように計算できないアルゴリズムはNoneです。
+fn from_column_name(name: &str) -> Option<DigestAlgorithm> {
+    match name {
+        "md5" => Some(DigestAlgorithm::Md5),
+        "sha1" => Some(DigestAlgorithm::Sha1),
+        "sha256" => Some(DigestAlgorithm::Sha2_256),
+        _ => None,
+    }
+}
+
+/// hashdeepの列名 hashdeepが読めないアルゴリズムはNone
+pub fn column_name(digest_algorithm: DigestAlgorithm) -> Option<&'static str> {
+    match digest_algorithm {
+        DigestAlgorithm::Md5 => Some("md5"),
+        DigestAlgorithm::Sha1 => Some("sha1"),
+        DigestAlgorithm::Sha2_256 => Some("sha256"),
+        _ => None,
+    }
+}
+
+/// # hashdeep形式の読み込み関数
+/// 1行目が`%%%% HASHDEEP-1.0`でなければNoneを返します。
+/// 計算できないアルゴリズムの列は読み飛ばします。
+pub fn parse(content: &str) -> Option<ChecksumFile> {
+    let mut lines = content
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line));
+    if lines.next()?.trim_start_matches('\u{feff}') != HASHDEEP_HEADER {
+        return None;
+    }
+
+    let mut checksum_file = ChecksumFile::default();
+    let mut columns: Option<Vec<&str>> = None;
+    for line in lines {
+        if line.trim().is_empty() || line.starts_with("##") {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("%%%% ") {
+            columns = Some(header.split(',').collect());
+            continue;
+        }
+        match columns
+            .as_ref()
+            .and_then(|columns| parse_line(line, columns))
+        {
+            Some(entry) => checksum_file.entries.push(entry),
+            None => checksum_file.malformed_lines += 1,
+        }
+    }
+    Some(checksum_file)
+}
+
+/// 列名にしたがって1行を読み込みます。
+fn parse_line(line: &str, columns: &[&str]) -> Option<ChecksumEntry> {
+    if columns.first() != Some(&"size") || columns.last() != Some(&"filename") {
+        return None;
+    }
+    // ファイル名はカンマを含むことがあるので、列の数で区切る
+    let values: Vec<&str> = line.splitn(columns.len(), ',').collect();
+    if values.len() != columns.len() {
+        return None;
+    }
+
+    let size = values[0].parse::<u64>().ok()?;
+    let mut digests = Vec::new();
+    for (column, value) in columns[1..columns.len() - 1]
+        .iter()
+        .zip(values[1..values.len() - 1].iter())
+    {
+        if let Some(digest_algorithm) = from_column_name(column) {
+            let hash_value = hash::from_hex(value)?;
+            if hash_value.len() != digest_algorithm.digest_length() {
+                return None;
+            }
+            digests.push((digest_algorithm, hash_value));
+        }
+    }
+    let path = values[values.len() - 1];
+    if digests.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some(ChecksumEntry {
+        path: path.to_string(),
+        size: Some(size),
+        digests,
+    })
+}
+
+/// # hashdeep形式のヘッダーを作る関数
+/// ## 引数
+/// - ハッシュアルゴリズム 列の順番になります
+/// - 実行したディレクトリ
+/// - 実行したコマンドライン
+/// ## 返り値
+/// Result型 hashdeepが読めないアルゴリズムがあればエラーメッセージ あるいは、改行を含むヘッダー
+pub fn header(
+    digest_algorithms: &[DigestAlgorithm],
+    invoked_from: &str,
+    command_line: &str,
+) -> Result<String, String> {
+    let mut columns = vec!["size"];
+    for digest_algorithm in digest_algorithms {
+        match column_name(*digest_algorithm) {
+            Some(name) => columns.push(name),
+            None => {
+                return Err(t!(
+                    "hashdeep.unsupported_algorithm",
+                    algorithm = digest_algorithm.bsd_tag()
+                ))
+            }
+        }
+    }
+    columns.push("filename");
+    Ok(format!(
+        "{}\n%%%% {}\n## Invoked from: {}\n## $ {}\n##\n",
+        HASHDEEP_HEADER,
+        columns.join(","),
+        invoked_from,
+        command_line
+    ))
+}
+
+/// # hashdeep形式の行を作る関数
+/// ハッシュ値はヘッダーに書いたアルゴリズムの順に並んでいる必要があります。
+pub fn format_line(entry: &ChecksumEntry) -> Result<String, String> {
+    let size = match entry.size {
+        Some(size) => size,
+        None => return Err(t!("hashdeep.unknown_size", path = entry.path)),
+    };
+    if entry.path.contains(['\n', '\r']) {
+        return Err(t!(
+            "hashdeep.unrepresentable_path",
+            path = format!("{:?}", entry.path)
+        ));
+    }
+    let mut values = vec![size.to_string()];
+    values.extend(
+        entry
+            .digests
+            .iter()
+            .map(|(_, hash_value)| hash::to_hex(hash_value)),
+    );
+    values.push(entry.path.clone());
+    Ok(format!("{}\n", values.join(",")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// hashdeepが書き出したファイルを読み込める
+    #[test]
+    fn ok_parse() {
+        let content = "%%%% HASHDEEP-1.0\r\n\
+            %%%% size,md5,sha256,filename\r\n\
+            ## Invoked from: /home/user\r\n\
+            ## $ hashdeep -l -r dir\r\n\
+            ##\r\n\
+            0,d41d8cd98f00b204e9800998ecf8427e,e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855,dir/a,b.txt\r\n\
+            0,zz,zz,dir/broken.txt\r\n";
+        let checksum_file = parse(content).unwrap();
+        assert_eq!(1, checksum_file.entries.len());
+        assert_eq!(1, checksum_file.malformed_lines);
+        let entry = &checksum_file.entries[0];
+        assert_eq!("dir/a,b.txt", entry.path);
+        assert_eq!(Some(0), entry.size);
+        assert_eq!(
+            vec![
+                (
+                    DigestAlgorithm::Md5,
+                    hash::digest_bytes(b"", DigestAlgorithm::Md5)
+                ),
+                (
+                    DigestAlgorithm::Sha2_256,
+                    hash::digest_bytes(b"", DigestAlgorithm::Sha2_256)
+                ),
+            ],
+            entry.digests
+        );
+    }
+
+    /// 書き出したファイルを読み込むと元に戻る
+    #[test]
+    fn ok_round_trip() {
+        let digest_algorithms = [DigestAlgorithm::Sha1, DigestAlgorithm::Sha2_256];
+        let entry = ChecksumEntry {
+            path: "dir/a b.txt".to_string(),
+            size: Some(3),
+            digests: digest_algorithms
+                .iter()
+                .map(|digest_algorithm| {
+                    (
+                        *digest_algorithm,
+                        hash::digest_bytes(b"abc", *digest_algorithm),
+                    )
+                })
+                .collect(),
+        };
+        let mut content = header(&digest_algorithms, "/tmp", "digest_tool").unwrap();
+        content.push_str(&format_line(&entry).unwrap());
+        assert_eq!(vec![entry], parse(&content).unwrap().entries);
+    }
+
+    /// hashdeepが読めないアルゴリズムは書けない
+    #[test]
+    fn ng_header() {
+        assert!(header(&[DigestAlgorithm::Sha3_256], "/tmp", "digest_tool").is_err());
+        assert!(parse("5  a.txt").is_none());
+    }
+}
@@ -0,0 +1,229 @@
+//! # 多言語化モジュール
+//! 利用者に見せるメッセージを、言語ごとのカタログ(`locales/<言語>.json`)から取り出します。
+//! カタログはキーとメッセージの組で、メッセージの`{名前}`は`t!`マクロで渡した値に置き換えます。
+//!
+//! 言語は次の順に決めます。
+//! 1. `--lang`の指定
+//! 2. 環境変数`LC_ALL`、`LC_MESSAGES`、`LANG`のうち、最初に値が設定されているもの
+//!    `ja`で始まれば日本語、それ以外(`C`、`en_US.UTF-8`など)は英語
+//! 3. どれもなければ日本語
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// # メッセージ取得マクロ
+/// `t!("key")`、`t!("key", name = value, ...)`の形で使います。
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::message($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::message($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+/// メッセージの言語
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lang {
+    Ja,
+    En,
+}
+
+impl Lang {
+    /// `--lang`の値や、`ja_JP.UTF-8`のようなロケール名から言語を取得します。
+    pub fn from_name(name: &str) -> Option<Lang> {
+        let language = name
+            .split(['_', '.', '-', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "ja" => Some(Lang::Ja),
+            "en" | "c" | "posix" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    /// HTMLの`lang`属性などに使う言語タグ
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Lang::Ja => "ja",
+            Lang::En => "en",
+        }
+    }
+
+    fn catalog_source(&self) -> &'static str {
+        match self {
+            Lang::Ja => include_str!("../locales/ja.json"),
+            Lang::En => include_str!("../locales/en.json"),
+        }
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+static CATALOG_JA: OnceLock<HashMap<String, String>> = OnceLock::new();
+static CATALOG_EN: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// # 言語の初期化関数
+/// CLI引数と環境変数から言語を決めます。clapのヘルプも訳すので、CLI引数を読む前に呼びます。
+pub fn init() {
+    let lang = detect(
+        std::env::args(),
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty()),
+    );
+    LANG.set(lang).ok();
+}
+
+/// # 言語を決める関数
+/// ## 引数
+/// - CLI引数 `--lang <言語>`か`--lang=<言語>`を探します
+/// - ロケールの環境変数の値
+fn detect(args: impl Iterator<Item = String>, locale: Option<String>) -> Lang {
+    let args: Vec<String> = args.collect();
+    let specified = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--lang" {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix("--lang=").map(|value| value.to_string())
+        }
+    });
+    if let Some(lang) = specified.as_deref().and_then(Lang::from_name) {
+        return lang;
+    }
+    match locale {
+        Some(locale) => Lang::from_name(&locale).unwrap_or(Lang::En),
+        None => Lang::Ja,
+    }
+}
+
+/// 使っている言語
+pub fn current() -> Lang {
+    *LANG.get().unwrap_or(&Lang::Ja)
+}
+
+/// 言語のカタログ
+fn catalog(lang: Lang) -> &'static HashMap<String, String> {
+    let cell = match lang {
+        Lang::Ja => &CATALOG_JA,
+        Lang::En => &CATALOG_EN,
+    };
+    cell.get_or_init(|| serde_json::from_str(lang.catalog_source()).unwrap_or_default())
+}
+
+/// # メッセージ取得関数
+/// 使っている言語のカタログにキーがなければ、日本語、キーそのものの順に使います。
+/// 普段は`t!`マクロから呼びます。
+pub fn message(key: &str, args: &[(&str, String)]) -> String {
+    let template = catalog(current())
+        .get(key)
+        .or_else(|| catalog(Lang::Ja).get(key))
+        .map(|template| template.as_str())
+        .unwrap_or(key);
+    let mut message = template.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+    message
+}
+
+/// clapのヘルプのように、プログラムの終わりまで使うメッセージ
+pub fn static_message(key: &str) -> &'static str {
+    Box::leak(message(key, &[]).into_boxed_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ALL: [Lang; 2] = [Lang::Ja, Lang::En];
+
+    /// メッセージの`{名前}`を集める
+    fn placeholders(message: &str) -> Vec<String> {
+        let mut names: Vec<String> = message
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name.to_string()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// すべてのキーがすべてのカタログにあり、置き換える名前も同じ
+    #[test]
+    fn ok_all_keys_in_all_catalogs() {
+        for lang in ALL.iter() {
+            assert!(!catalog(*lang).is_empty(), "{:?}", lang);
+            for other in ALL.iter() {
+                for (key, message) in catalog(*lang) {
+                    let translated = catalog(*other)
+                        .get(key)
+                        .unwrap_or_else(|| panic!("{:?}に{}がありません", other, key));
+                    assert_eq!(placeholders(message), placeholders(translated), "{}", key);
+                }
+            }
+        }
+    }
+
+    /// ソースで使っているキーがカタログにある
+    #[test]
+    fn ok_used_keys_in_catalog() {
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in std::fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let content = std::fs::read_to_string(&path).unwrap();
+            for pattern in ["t!(\"", "static_message(\""].iter() {
+                for (index, _) in content.match_indices(pattern) {
+                    // format!(のように、名前の一部が一致したものは除く
+                    let previous = content[..index].chars().last().unwrap_or(' ');
+                    if previous.is_alphanumeric() || previous == '_' {
+                        continue;
+                    }
+                    let key = content[index + pattern.len()..].split('"').next().unwrap();
+                    if key == "key" {
+                        continue;
+                    }
+                    assert!(
+                        catalog(Lang::Ja).contains_key(key),
+                        "{}: {}",
+                        path.display(),
+                        key
+                    );
+                }
+            }
+        }
+    }
+
+    /// `--lang`、環境変数、デフォルトの順に決める
+    #[test]
+    fn ok_detect() {
+        let args = |args: &[&str]| {
+            args.iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<String>>()
+                .into_iter()
+        };
+        assert_eq!(
+            Lang::En,
+            detect(
+                args(&["digest_tool", "--lang", "en"]),
+                Some("ja_JP.UTF-8".to_string())
+            )
+        );
+        assert_eq!(
+            Lang::Ja,
+            detect(args(&["digest_tool", "--lang=ja"]), Some("C".to_string()))
+        );
+        assert_eq!(
+            Lang::En,
+            detect(args(&["digest_tool"]), Some("fr_FR.UTF-8".to_string()))
+        );
+        assert_eq!(
+            Lang::Ja,
+            detect(args(&["digest_tool"]), Some("ja_JP.UTF-8".to_string()))
+        );
+        assert_eq!(Lang::Ja, detect(args(&["digest_tool"]), None));
+    }
+}
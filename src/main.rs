@@ -1,70 +1,414 @@
 //! # ハッシュ値計算ツール
+use error::Error;
 use log::debug;
 use std::str::FromStr;
+// t!マクロをほかのモジュールで使うので、最初に宣言する
+#[macro_use]
+mod i18n;
+mod archive;
+mod audit;
+mod baseline;
+mod cache;
+mod check;
+mod checksum_file;
 mod cli_arg_accepter;
+mod config;
+mod console;
 #[cfg(target_os = "windows")]
 mod context_menu;
+mod decompress;
+mod error;
+mod expect;
+mod generate;
 mod hash;
+mod hashdeep;
+mod multi_file;
+mod output_format;
+mod report;
+mod sidecar;
+mod tee;
+mod text_input;
+mod text_normalize;
+mod tree_digest;
+mod watch;
+mod xattr_digest;
 
 fn main() {
     // std::env::set_var("RUST_LOG", "debug");
     env_logger::init();
 
     debug!("アプリ開始");
-    let (input_file_path, digest_algorithm, mode) = cli_arg_accepter::accept_cli_arg();
+    i18n::init();
+    let cli_arg = match cli_arg_accepter::accept_cli_arg() {
+        Ok(cli_arg) => cli_arg,
+        Err(e) => {
+            debug!("{:?}", e);
+            eprintln!("{}", e);
+            // 引数を読めなかったので、--pauseは引数の中から探す
+            if console::should_pause(std::env::args().any(|arg| arg == "--pause")) {
+                console::wait_enter();
+            }
+            std::process::exit(e.exit_code());
+        }
+    };
+
+    multi_file::init_buffer_size(cli_arg.buffer_size);
+
+    // teeモードは標準入力をデータに使い、GUIは画面を閉じれば、監視はCtrl+Cで終わるので、終了待ちをしない
+    let wait_enter = console::should_pause(cli_arg.pause)
+        && !matches!(
+            cli_arg.mode,
+            cli_arg_accepter::Mode::Tee { .. }
+                | cli_arg_accepter::Mode::Gui
+                | cli_arg_accepter::Mode::Watch { .. }
+        );
+
+    // チェックサムファイルを作るときだけ、変わっていないファイルのハッシュ値をキャッシュから読む
+    // 検証するモードは、ビット腐敗や改ざんを見逃さないように、いつもファイルを読む
+    let use_cache = matches!(cli_arg.mode, cli_arg_accepter::Mode::Manifest { .. });
+    if let Some(cache_file) = cli_arg.cache_file.clone().filter(|_| use_cache) {
+        cache::init(cache_file, cli_arg.verify_cache);
+    }
+
+    let result = run(cli_arg);
+    cache::finish();
+    if let Err(e) = result.as_ref() {
+        debug!("{:?}", e);
+        eprintln!("{}", e);
+    }
 
-    match mode {
-        cli_arg_accepter::Mode::Digest => digest(input_file_path, digest_algorithm),
+    if wait_enter {
+        console::wait_enter();
+    }
+    debug!("アプリ終了");
+    if let Err(e) = result {
+        std::process::exit(e.exit_code());
+    }
+}
+
+/// # モードごとの処理を実行する関数
+/// ## 返り値
+/// Result型 失敗した理由 終了コードはErrorの種類で決まります
+fn run(cli_arg: cli_arg_accepter::CliArg) -> Result<(), Error> {
+    match cli_arg.mode {
+        cli_arg_accepter::Mode::Digest => match cli_arg.expected_digest {
+            Some(expected_digest) => expect(
+                cli_arg.inputs,
+                &expected_digest,
+                cli_arg.digest_algorithm,
+                cli_arg.tree_digest_option,
+                cli_arg.decompress,
+                cli_arg.text_normalization,
+            ),
+            None => digest_inputs(
+                cli_arg.inputs,
+                cli_arg
+                    .digest_algorithm
+                    .unwrap_or(cli_arg.default_digest_algorithm),
+                cli_arg.tree_digest_option,
+                cli_arg.decompress,
+                cli_arg.text_normalization,
+                cli_arg.output_format,
+            ),
+        },
+        cli_arg_accepter::Mode::Archive { archive_format } => {
+            if cli_arg.inputs.is_empty() {
+                digest_archive(
+                    None,
+                    cli_arg
+                        .digest_algorithm
+                        .unwrap_or(cli_arg.default_digest_algorithm),
+                    archive_format,
+                )?;
+            }
+            for input in cli_arg.inputs {
+                digest_archive(
+                    Some(input),
+                    cli_arg
+                        .digest_algorithm
+                        .unwrap_or(cli_arg.default_digest_algorithm),
+                    archive_format,
+                )?;
+            }
+            Ok(())
+        }
+        cli_arg_accepter::Mode::Manifest {
+            format,
+            base_dir,
+            output_file_path,
+        } => manifest(
+            cli_arg.inputs,
+            format,
+            base_dir,
+            output_file_path,
+            cli_arg.digest_algorithms,
+            cli_arg.default_digest_algorithm,
+            cli_arg.text_normalization,
+            &cli_arg.exclude,
+            cli_arg.report_file.as_deref(),
+        ),
+        cli_arg_accepter::Mode::Convert {
+            checksum_file_path,
+            format,
+            output_file_path,
+        } => convert(
+            &checksum_file_path,
+            format,
+            output_file_path,
+            cli_arg.digest_algorithm,
+        ),
+        cli_arg_accepter::Mode::Verify => verify(
+            cli_arg.inputs,
+            cli_arg.text_normalization,
+            &cli_arg.exclude,
+            cli_arg.report_file.as_deref(),
+        ),
+        cli_arg_accepter::Mode::Audit {
+            known_file_path,
+            base_dir,
+        } => audit(
+            cli_arg.inputs,
+            &known_file_path,
+            base_dir,
+            &cli_arg.exclude,
+            cli_arg.report_file.as_deref(),
+        ),
+        cli_arg_accepter::Mode::Check { checksum_file_path } => check(
+            &checksum_file_path,
+            cli_arg.digest_algorithm,
+            cli_arg.text_normalization,
+            cli_arg.report_file.as_deref(),
+        ),
         cli_arg_accepter::Mode::SetUp => setup(),
         cli_arg_accepter::Mode::CleanUp => cleanup(),
         cli_arg_accepter::Mode::Gui => gui(),
+        cli_arg_accepter::Mode::Tee {
+            output_file_path,
+            digest_file_path,
+            digest_algorithms,
+        } => tee(output_file_path, digest_file_path, digest_algorithms),
+        cli_arg_accepter::Mode::Config { config } => {
+            print!("{}", config.to_toml());
+            Ok(())
+        }
+        cli_arg_accepter::Mode::BaselineCreate {
+            output_file_path,
+            key_file_path,
+        } => baseline_create(
+            cli_arg.inputs,
+            cli_arg
+                .digest_algorithm
+                .unwrap_or(cli_arg.default_digest_algorithm),
+            &cli_arg.exclude,
+            output_file_path,
+            key_file_path,
+        ),
+        cli_arg_accepter::Mode::BaselineCompare {
+            baseline_file_path,
+            key_file_path,
+        } => baseline_compare(&baseline_file_path, key_file_path),
+        cli_arg_accepter::Mode::XattrWrite => xattr_write(
+            cli_arg.inputs,
+            cli_arg.digest_algorithms,
+            cli_arg.default_digest_algorithm,
+            &cli_arg.exclude,
+        ),
+        cli_arg_accepter::Mode::XattrRead => {
+            xattr_read(cli_arg.inputs, &cli_arg.digest_algorithms, &cli_arg.exclude)
+        }
+        cli_arg_accepter::Mode::XattrScrub => {
+            xattr_scrub(cli_arg.inputs, &cli_arg.digest_algorithms, &cli_arg.exclude)
+        }
+        cli_arg_accepter::Mode::Watch { debounce } => {
+            let paths: Vec<std::path::PathBuf> = cli_arg
+                .inputs
+                .iter()
+                .filter_map(|input| match input {
+                    cli_arg_accepter::Input::File(path) => Some(std::path::PathBuf::from(path)),
+                    cli_arg_accepter::Input::Text { .. } => None,
+                })
+                .collect();
+            watch::watch(
+                &paths,
+                cli_arg
+                    .digest_algorithm
+                    .unwrap_or(cli_arg.default_digest_algorithm),
+                cli_arg.output_format,
+                &cli_arg.exclude,
+                debounce,
+            )
+        }
+        cli_arg_accepter::Mode::Generate {
+            target,
+            subcommand,
+            output_file_path,
+        } => generate(target, subcommand, output_file_path),
     }
+}
 
-    println!("Enterキーを押すと終了します");
-    let mut word = String::new();
-    std::io::stdin().read_line(&mut word).ok();
-    debug!("アプリ終了");
+/// # 入力ごとにハッシュ値を計算する関数
+/// 人が読む形式では、失敗したところで止めます。
+/// スクリプト向けの形式では、失敗も1件の結果として書き出して続け、最初の失敗を返します。
+fn digest_inputs(
+    inputs: Vec<cli_arg_accepter::Input>,
+    digest_algorithm: cli_arg_accepter::DigestAlgorithm,
+    tree_digest_option: tree_digest::TreeDigestOption,
+    decompress: Option<decompress::Compression>,
+    text_normalization: Option<text_normalize::TextNormalization>,
+    output_format: output_format::OutputFormat,
+) -> Result<(), Error> {
+    if output_format == output_format::OutputFormat::Text || inputs.is_empty() {
+        if inputs.is_empty() {
+            digest(
+                None,
+                digest_algorithm,
+                tree_digest_option,
+                decompress,
+                text_normalization,
+                output_format,
+            )?;
+        }
+        for input in inputs {
+            digest(
+                Some(input),
+                digest_algorithm,
+                tree_digest_option,
+                decompress,
+                text_normalization,
+                output_format,
+            )?;
+        }
+        return Ok(());
+    }
+
+    if output_format == output_format::OutputFormat::Csv {
+        println!("{}", output_format::CSV_HEADER);
+    }
+    let mut records = Vec::new();
+    let mut first_error = None;
+    for input in inputs {
+        let path = match &input {
+            cli_arg_accepter::Input::File(path) => Some(path.clone()),
+            cli_arg_accepter::Input::Text { .. } => None,
+        };
+        // ディレクトリのツリーハッシュは正規化しない
+        let applied_normalization = match path.as_ref() {
+            Some(path) if std::path::Path::new(path).is_dir() => None,
+            _ => text_normalization.as_ref(),
+        }
+        .copied();
+        let pre_time = std::time::Instant::now();
+        let result = digest(
+            Some(input),
+            digest_algorithm,
+            tree_digest_option,
+            decompress,
+            text_normalization,
+            output_format,
+        );
+        let record = output_format::DigestRecord::new(
+            path,
+            digest_algorithm,
+            &result,
+            pre_time.elapsed(),
+            applied_normalization.as_ref(),
+        );
+        match output_format {
+            output_format::OutputFormat::Ndjson => println!("{}", record.to_ndjson()),
+            output_format::OutputFormat::Csv => println!("{}", record.to_csv()),
+            _ => records.push(record),
+        }
+        if let Err(e) = result {
+            first_error.get_or_insert(e);
+        }
+    }
+    if output_format == output_format::OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&records).unwrap_or_default()
+        );
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }
 
-fn digest(input_file_path: Option<String>, digest_algorithm: cli_arg_accepter::DigestAlgorithm) {
+/// # ハッシュ値計算関数
+/// 人が読む形式のときだけ、結果を標準出力に表示します。
+/// ## 返り値
+/// Result型 失敗した理由 あるいは、読み込んだバイト数とハッシュ値
+fn digest(
+    input: Option<cli_arg_accepter::Input>,
+    digest_algorithm: cli_arg_accepter::DigestAlgorithm,
+    tree_digest_option: tree_digest::TreeDigestOption,
+    decompress: Option<decompress::Compression>,
+    text_normalization: Option<text_normalize::TextNormalization>,
+    output_format: output_format::OutputFormat,
+) -> Result<(u64, Vec<u8>), Error> {
+    let text_output = output_format == output_format::OutputFormat::Text;
     // 計測開始
     let pre_time = chrono::Local::now();
 
-    // ファイルパス入力を取得する
-    let input_file_path = match input_file_path {
-        Some(s) => s,
+    // 入力を取得する
+    let input = match input {
+        Some(input) => input,
         None => {
             debug!("ファイルパスが入力されていませんでした。");
-            println!("ファイルパスが入力されていませんでした。");
-            return;
-        }
-    };
-    // ファイルバッファリーダーの取得
-    let input_path = match std::path::PathBuf::from_str(&input_file_path) {
-        Ok(p) => p,
-        Err(_) => {
-            debug!("入力されたファイルパスが誤っています。");
-            println!("入力されたファイルパスが誤っています。");
-            return;
+            return Err(Error::MissingInput(t!("input.no_file_path")));
         }
     };
-    let input_file = match std::fs::File::open(input_path) {
-        Ok(f) => f,
-        Err(_) => {
-            debug!("ファイルにアクセスできませんでした。ここか");
-            println!("ファイルにアクセスできませんでした。");
-            return;
+    let (input_file_reader, input_file_size): (Box<dyn std::io::Read>, u64) = match input {
+        cli_arg_accepter::Input::File(input_file_path) => {
+            // ファイルバッファリーダーの取得
+            let input_path = match std::path::PathBuf::from_str(&input_file_path) {
+                Ok(p) => p,
+                Err(_) => {
+                    debug!("入力されたファイルパスが誤っています。");
+                    return Err(Error::InvalidArgument(t!("input.invalid_file_path")));
+                }
+            };
+            // ディレクトリならツリーハッシュを計算する
+            if input_path.is_dir() {
+                return digest_tree(
+                    &input_path,
+                    digest_algorithm,
+                    tree_digest_option,
+                    text_output,
+                );
+            }
+            let input_file =
+                std::fs::File::open(input_path).map_err(|e| Error::io(&t!("io.open_file"), e))?;
+            let input_file_size = input_file
+                .metadata()
+                .map_err(|e| Error::io(&t!("io.file_size"), e))?
+                .len();
+            (
+                Box::new(std::io::BufReader::with_capacity(
+                    multi_file::buffer_size(),
+                    input_file,
+                )),
+                input_file_size,
+            )
         }
-    };
-    let input_file_size = match input_file.metadata() {
-        Ok(meta) => meta.len(),
-        Err(_) => {
-            debug!("ファイルサイズを取得できませんでした。");
-            println!("ファイルサイズを取得できませんでした。");
-            return;
+        cli_arg_accepter::Input::Text {
+            text,
+            encoding,
+            newline,
+        } => {
+            // エンコーディングで表せない文字は、入力の誤り
+            let bytes = text_input::encode_text(&text, encoding, newline)
+                .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+            if text_output {
+                println!(
+                    "{}",
+                    t!("digest.text_encoding", encoding = encoding.display_name())
+                );
+            }
+            let input_size = bytes.len() as u64;
+            (Box::new(std::io::Cursor::new(bytes)), input_size)
         }
     };
-    let mut input_file_reader = std::io::BufReader::new(input_file);
 
     // プログレスバーのセットアップ
     let progress_bar_style = indicatif::ProgressStyle::default_bar()
@@ -74,112 +418,1506 @@ fn digest(input_file_path: Option<String>, digest_algorithm: cli_arg_accepter::D
     // 1秒に4回プログレスバーを更新すると、少しパフォーマンスに影響出てきそう(2.5GHz 4core)
     progress_bar.set_draw_rate(4);
 
+    // 展開する場合は、圧縮されたデータを読み込んだバイト数でプログレスバーを進める
+    let (input_file_reader, hash_progress_bar): (Box<dyn std::io::Read>, indicatif::ProgressBar) =
+        match decompress {
+            None => (input_file_reader, progress_bar.clone()),
+            Some(compression) => {
+                let compressed_reader =
+                    std::io::BufReader::new(progress_bar.wrap_read(input_file_reader));
+                match decompress::decoder(compressed_reader, compression) {
+                    Ok((decoder, compression)) => {
+                        eprintln!(
+                            "{}",
+                            t!(
+                                "digest.decompress",
+                                compression = compression
+                                    .map(|compression| compression.name().to_string())
+                                    .unwrap_or_else(|| t!("digest.not_compressed"))
+                            )
+                        );
+                        (decoder, indicatif::ProgressBar::hidden())
+                    }
+                    Err(e) => return Err(Error::io(&t!("io.decompress"), e)),
+                }
+            }
+        };
+
+    // テキスト正規化する場合は、正規化しながら読み込んだバイト列のハッシュ値を計算する
+    let (mut input_file_reader, hash_progress_bar) = match text_normalization {
+        None => (input_file_reader, hash_progress_bar),
+        Some(text_normalization) => {
+            // 検証するときに同じ正規化を指定できるように、指定の文字列を表示する
+            if text_output {
+                println!(
+                    "{}",
+                    t!(
+                        "digest.text_normalization",
+                        normalization = text_normalization
+                    )
+                );
+            }
+            let normalized_reader: Box<dyn std::io::Read> =
+                Box::new(text_normalize::NormalizeReader::new(
+                    hash_progress_bar.wrap_read(input_file_reader),
+                    text_normalization,
+                ));
+            (normalized_reader, indicatif::ProgressBar::hidden())
+        }
+    };
+
+    // 正規化できなかったのか、読み込めなかったのかを分けて伝える
+    let read_error = |e: std::io::Error| {
+        if text_normalization.is_some() && e.kind() == std::io::ErrorKind::InvalidData {
+            Error::io(&t!("io.normalize"), e)
+        } else {
+            Error::io(&t!("io.read_file"), e)
+        }
+    };
     debug!("ハッシュ値計算開始");
-    println!("ハッシュ値を計算しています。");
+    eprintln!("{}", t!("digest.computing"));
 
     let (file_size, hash_value) = match digest_algorithm {
         cli_arg_accepter::DigestAlgorithm::Sha2_256 => {
             debug!("ハッシュアルゴリズム: Sha2 256");
-            println!("ハッシュアルゴリズム: Sha2 256");
-            let hash_result = hash::sha2_256(&mut input_file_reader, progress_bar);
-            let (file_size, hash_value) = match hash_result {
-                Err(e) => {
-                    debug!("{:?}", e);
-                    println!("ファイルを読み込みできませんでした。");
-                    std::process::exit(0)
-                }
-                Ok(result) => result,
-            };
+            if text_output {
+                println!("{}", t!("digest.algorithm", algorithm = "Sha2 256"));
+            }
+            let (file_size, hash_value) =
+                hash::sha2_256(&mut input_file_reader, hash_progress_bar).map_err(read_error)?;
             (file_size, hash_value.to_vec())
         }
         cli_arg_accepter::DigestAlgorithm::Sha2_512 => {
-            println!("ハッシュアルゴリズム: Sha2 512");
-            let hash_result = hash::sha2_512(&mut input_file_reader, progress_bar);
-            let (file_size, hash_value) = match hash_result {
-                Err(e) => {
-                    debug!("{:?}", e);
-                    println!("ファイルを読み込みできませんでした。");
-                    std::process::exit(0)
-                }
-                Ok(result) => result,
-            };
+            if text_output {
+                println!("{}", t!("digest.algorithm", algorithm = "Sha2 512"));
+            }
+            let (file_size, hash_value) =
+                hash::sha2_512(&mut input_file_reader, hash_progress_bar).map_err(read_error)?;
             (file_size, hash_value.to_vec())
         }
         cli_arg_accepter::DigestAlgorithm::Sha3_256 => {
-            println!("ハッシュアルゴリズム: Sha3 256");
-            let hash_result = hash::sha3_256(&mut input_file_reader, progress_bar);
-            let (file_size, hash_value) = match hash_result {
-                Err(e) => {
-                    debug!("{:?}", e);
-                    println!("ファイルを読み込みできませんでした。");
-                    std::process::exit(0)
-                }
-                Ok(result) => result,
-            };
+            if text_output {
+                println!("{}", t!("digest.algorithm", algorithm = "Sha3 256"));
+            }
+            let (file_size, hash_value) =
+                hash::sha3_256(&mut input_file_reader, hash_progress_bar).map_err(read_error)?;
             (file_size, hash_value.to_vec())
         }
         cli_arg_accepter::DigestAlgorithm::Sha3_512 => {
-            println!("ハッシュアルゴリズム: Sha3 512");
-            let hash_result = hash::sha3_512(&mut input_file_reader, progress_bar);
-            let (file_size, hash_value) = match hash_result {
-                Err(e) => {
-                    debug!("{:?}", e);
-                    println!("ファイルを読み込みできませんでした。");
-                    std::process::exit(0)
-                }
-                Ok(result) => result,
-            };
+            if text_output {
+                println!("{}", t!("digest.algorithm", algorithm = "Sha3 512"));
+            }
+            let (file_size, hash_value) =
+                hash::sha3_512(&mut input_file_reader, hash_progress_bar).map_err(read_error)?;
             (file_size, hash_value.to_vec())
         }
+        cli_arg_accepter::DigestAlgorithm::Crc32
+        | cli_arg_accepter::DigestAlgorithm::Md5
+        | cli_arg_accepter::DigestAlgorithm::Sha1 => {
+            if text_output {
+                println!(
+                    "{}",
+                    t!("digest.algorithm", algorithm = digest_algorithm.bsd_tag())
+                );
+            }
+            hash::digest(&mut input_file_reader, digest_algorithm, hash_progress_bar)
+                .map_err(read_error)?
+        }
     };
+    progress_bar.finish();
 
     let post_time = chrono::Local::now();
-    println!("ファイルサイズ: {}MB", file_size / 1_000_000);
-    print!("ハッシュ値: [");
-    for i in 0..hash_value.len() {
-        print!("{:x}", hash_value[i]);
-        if i != hash_value.len() {
-            print!(", ");
+    if text_output {
+        println!("{}", t!("digest.file_size", size = file_size / 1_000_000));
+        let mut digest = "[".to_string();
+        for i in 0..hash_value.len() {
+            digest.push_str(&format!("{:x}", hash_value[i]));
+            if i != hash_value.len() {
+                digest.push_str(", ");
+            }
         }
+        digest.push(']');
+        println!("{}", t!("digest.hash_value", digest = digest));
     }
-    println!("]");
-    println!("所要時間: {:?}", post_time - pre_time);
+    eprintln!(
+        "{}",
+        t!(
+            "digest.elapsed",
+            elapsed = format!("{:?}", post_time - pre_time)
+        )
+    );
+    Ok((file_size, hash_value))
 }
 
-fn setup() {
-    println!("コンテクストメニューにコマンドを追加しています。");
-    if context_menu::set_to_context_menu().is_ok() {
-        println!("コンテクストメニューに追加出来ました。")
-    } else {
-        println!("コンテクストメニューに追加できませんでした。");
+fn expect(
+    inputs: Vec<cli_arg_accepter::Input>,
+    expected_digest: &expect::ExpectedDigest,
+    digest_algorithm: Option<cli_arg_accepter::DigestAlgorithm>,
+    tree_digest_option: tree_digest::TreeDigestOption,
+    decompress: Option<decompress::Compression>,
+    text_normalization: Option<text_normalize::TextNormalization>,
+) -> Result<(), Error> {
+    let (digest_algorithm, others) = expect::resolve_algorithm(expected_digest, digest_algorithm)
+        .map_err(Error::InvalidArgument)?;
+    if !others.is_empty() {
+        let others: Vec<&str> = others.iter().map(|other| other.name()).collect();
+        eprintln!(
+            "{}",
+            t!(
+                "expect.inferred",
+                algorithm = digest_algorithm.bsd_tag(),
+                others = others.join(", ")
+            )
+        );
+    }
+    if inputs.is_empty() {
+        return Err(Error::MissingInput(t!("input.no_file_path")));
     }
+
+    let mut mismatches = 0;
+    for input in inputs {
+        let (_, actual) = digest(
+            Some(input),
+            digest_algorithm,
+            tree_digest_option,
+            decompress,
+            text_normalization,
+            output_format::OutputFormat::Text,
+        )?;
+        if expect::constant_time_eq(&expected_digest.hash_value, &actual) {
+            println!("MATCH");
+            println!(
+                "    {}: {}",
+                digest_algorithm.bsd_tag(),
+                hash::to_hex(&actual)
+            );
+        } else {
+            println!("MISMATCH");
+            println!(
+                "    {}",
+                t!(
+                    "check.expected",
+                    digest = hash::to_hex(&expected_digest.hash_value)
+                )
+            );
+            println!("    {}", t!("check.actual", digest = hash::to_hex(&actual)));
+            // 印をハッシュ値の位置にそろえる
+            println!(
+                "    {}{}",
+                " ".repeat(display_width(&t!("check.expected", digest = ""))),
+                expect::diff_markers(&expected_digest.hash_value, &actual)
+            );
+            mismatches += 1;
+        }
+    }
+    if mismatches > 0 {
+        return Err(Error::Mismatch(t!("expect.mismatch", count = mismatches)));
+    }
+    Ok(())
+}
+
+/// 端末での表示幅 全角文字は2文字分として数えます
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c as u32 >= 0x1100 { 2 } else { 1 })
+        .sum()
 }
 
-fn cleanup() {
-    println!("コンテクストメニューにコマンドを削除しています。");
-    if context_menu::remove_from_context_menu().is_ok() {
-        println!("コンテクストメニューから削除しました。");
+fn digest_tree(
+    input_path: &std::path::Path,
+    digest_algorithm: cli_arg_accepter::DigestAlgorithm,
+    tree_digest_option: tree_digest::TreeDigestOption,
+    text_output: bool,
+) -> Result<(u64, Vec<u8>), Error> {
+    // 計測開始
+    let pre_time = chrono::Local::now();
+
+    let tree_size =
+        tree_digest::tree_size(input_path).map_err(|e| Error::io(&t!("io.read_dir"), e))?;
+    let progress_bar_style = indicatif::ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} ({eta})");
+    let progress_bar = indicatif::ProgressBar::new(tree_size);
+    progress_bar.set_style(progress_bar_style);
+    progress_bar.set_draw_rate(4);
+
+    debug!("ツリーハッシュ計算開始");
+    eprintln!("{}", t!("tree.computing"));
+    if text_output {
+        println!(
+            "{}",
+            t!("digest.algorithm", algorithm = digest_algorithm.bsd_tag())
+        );
+        println!(
+            "{}",
+            t!(
+                "tree.metadata",
+                metadata = tree_digest_option.display_name()
+            )
+        );
+    }
+    let hash_value = tree_digest::tree_digest(
+        input_path,
+        digest_algorithm,
+        tree_digest_option,
+        &progress_bar,
+    )
+    .map_err(|e| Error::io(&t!("io.read_dir"), e))?;
+    progress_bar.finish();
+
+    let post_time = chrono::Local::now();
+    if text_output {
+        println!("{}", t!("digest.file_size", size = tree_size / 1_000_000));
+        println!("{}", t!("tree.digest", digest = hash::to_hex(&hash_value)));
+    }
+    eprintln!(
+        "{}",
+        t!(
+            "digest.elapsed",
+            elapsed = format!("{:?}", post_time - pre_time)
+        )
+    );
+    Ok((tree_size, hash_value))
+}
+
+fn digest_archive(
+    input: Option<cli_arg_accepter::Input>,
+    digest_algorithm: cli_arg_accepter::DigestAlgorithm,
+    archive_format: Option<archive::ArchiveFormat>,
+) -> Result<(), Error> {
+    // 計測開始
+    let pre_time = chrono::Local::now();
+
+    let archive_path = match input {
+        Some(cli_arg_accepter::Input::File(path)) => std::path::PathBuf::from(path),
+        _ => {
+            debug!("ファイルパスが入力されていませんでした。");
+            return Err(Error::MissingInput(t!("input.no_file_path")));
+        }
+    };
+    let archive_format =
+        match archive_format.or_else(|| archive::ArchiveFormat::detect(&archive_path)) {
+            Some(archive_format) => archive_format,
+            None => return Err(Error::InvalidArgument(t!("archive.unknown_format"))),
+        };
+    let archive_size = std::fs::metadata(&archive_path)
+        .map_err(|e| Error::io(&t!("io.open_file"), e))?
+        .len();
+
+    let progress_bar_style = indicatif::ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} ({eta})");
+    let progress_bar = indicatif::ProgressBar::new(archive_size);
+    progress_bar.set_style(progress_bar_style);
+    progress_bar.set_draw_rate(4);
+
+    debug!("アーカイブのハッシュ値計算開始");
+    eprintln!("{}", t!("archive.computing"));
+    println!("{}", t!("archive.format", format = archive_format.name()));
+    println!(
+        "{}",
+        t!("digest.algorithm", algorithm = digest_algorithm.bsd_tag())
+    );
+    let members = archive::digest_archive(
+        &archive_path,
+        archive_format,
+        digest_algorithm,
+        &progress_bar,
+    )
+    .map_err(|e| Error::io(&t!("io.read_archive"), e))?;
+    progress_bar.finish_and_clear();
+
+    for member in members.iter() {
+        println!(
+            "{}  {:>12}  {}",
+            hash::to_hex(&member.hash_value),
+            member.size,
+            member.path
+        );
+    }
+    let post_time = chrono::Local::now();
+    let total_size: u64 = members.iter().map(|member| member.size).sum();
+    println!("{}", t!("archive.member_count", count = members.len()));
+    println!(
+        "{}",
+        t!("archive.total_size", size = total_size / 1_000_000)
+    );
+    println!(
+        "{}",
+        t!(
+            "archive.content_digest",
+            digest = hash::to_hex(&archive::content_digest(&members, digest_algorithm))
+        )
+    );
+    eprintln!(
+        "{}",
+        t!(
+            "digest.elapsed",
+            elapsed = format!("{:?}", post_time - pre_time)
+        )
+    );
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn manifest(
+    inputs: Vec<cli_arg_accepter::Input>,
+    format: checksum_file::ChecksumFormat,
+    base_dir: Option<String>,
+    output_file_path: Option<String>,
+    digest_algorithms: Vec<cli_arg_accepter::DigestAlgorithm>,
+    default_digest_algorithm: cli_arg_accepter::DigestAlgorithm,
+    text_normalization: Option<text_normalize::TextNormalization>,
+    exclude: &[glob::Pattern],
+    report_file: Option<&std::path::Path>,
+) -> Result<(), Error> {
+    let mut report = report::Report::new("manifest");
+    // 形式ごとのデフォルトのアルゴリズム SFV形式はCRC32だけ
+    let digest_algorithms = match (format, digest_algorithms.is_empty()) {
+        (checksum_file::ChecksumFormat::Sfv, true) => {
+            vec![cli_arg_accepter::DigestAlgorithm::Crc32]
+        }
+        (checksum_file::ChecksumFormat::Sfv, false)
+            if digest_algorithms != [cli_arg_accepter::DigestAlgorithm::Crc32] =>
+        {
+            return Err(Error::InvalidArgument(t!("manifest.sfv_crc32_only")));
+        }
+        // ハッシュ値を計算する前に、GNU形式で書けないことを伝える
+        (checksum_file::ChecksumFormat::Gnu, false) if digest_algorithms.len() > 1 => {
+            return Err(Error::InvalidArgument(t!("manifest.gnu_single_algorithm")));
+        }
+        (checksum_file::ChecksumFormat::Hashdeep, true) => vec![
+            cli_arg_accepter::DigestAlgorithm::Md5,
+            cli_arg_accepter::DigestAlgorithm::Sha2_256,
+        ],
+        (_, true) => vec![default_digest_algorithm],
+        (_, false) => digest_algorithms,
+    };
+    if format == checksum_file::ChecksumFormat::Gnu {
+        for digest_algorithm in digest_algorithms.iter() {
+            checksum_file::gnu_algorithm(*digest_algorithm).map_err(Error::InvalidArgument)?;
+        }
+    }
+    let base_dir = match base_dir {
+        Some(base_dir) => std::path::PathBuf::from(base_dir),
+        None => std::path::PathBuf::from("."),
+    };
+
+    // ハッシュ値を計算する前に、書けない指定がないか確かめる
+    if text_normalization.is_some()
+        && !matches!(
+            format,
+            checksum_file::ChecksumFormat::Gnu | checksum_file::ChecksumFormat::Bsd
+        )
+    {
+        return Err(Error::InvalidArgument(t!(
+            "manifest.normalization_unsupported"
+        )));
+    }
+    if format == checksum_file::ChecksumFormat::Hashdeep {
+        hashdeep::header(&digest_algorithms, "", "").map_err(Error::InvalidArgument)?;
+    }
+
+    let entries = hash_files(
+        inputs,
+        &base_dir,
+        &digest_algorithms,
+        text_normalization.as_ref(),
+        exclude,
+    )?;
+    let content = checksum_file::format_file(&entries, format, text_normalization.as_ref())
+        .map_err(Error::InvalidArgument)?;
+
+    match output_file_path {
+        Some(output_file_path) => {
+            std::fs::write(&output_file_path, content)
+                .map_err(|e| Error::io(&t!("io.write_file"), e))?;
+            eprintln!(
+                "{}",
+                t!(
+                    "manifest.written",
+                    count = entries.len(),
+                    path = output_file_path
+                )
+            );
+        }
+        None => print!("{}", content),
+    }
+
+    for digest_algorithm in digest_algorithms.iter() {
+        report.add_algorithm(*digest_algorithm);
+    }
+    for entry in entries.iter() {
+        report.add(report::ReportEntry {
+            path: entry.path.clone(),
+            digest_algorithm: None,
+            status: "OK",
+            success: true,
+            size: entry.size,
+            details: digest_details(&entry.digests),
+        });
+    }
+    write_report(&report, report_file)
+}
+
+/// ハッシュアルゴリズムごとのハッシュ値を、レポートに書く行にする
+fn digest_details(digests: &[(cli_arg_accepter::DigestAlgorithm, Vec<u8>)]) -> Vec<String> {
+    digests
+        .iter()
+        .map(|(digest_algorithm, hash_value)| {
+            format!(
+                "{} {}",
+                digest_algorithm.bsd_tag(),
+                hash::to_hex(hash_value)
+            )
+        })
+        .collect()
+}
+
+/// # 検証結果をレポートの1行にする関数
+/// ## 引数
+/// - レポートに書くパス
+/// - 検証結果
+/// - 期待値と計算値の前に書く行
+fn check_report_entry(
+    path: String,
+    result: &check::CheckResult,
+    mut details: Vec<String>,
+) -> report::ReportEntry {
+    let tag = result.digest_algorithm.bsd_tag();
+    // 失敗した場合は、ファイルを読み込めなかったときも期待値を書く
+    if result.status != check::CheckStatus::Ok {
+        details.push(format!(
+            "{} {}",
+            tag,
+            t!("check.expected", digest = hash::to_hex(&result.expected))
+        ));
+    }
+    if let Some(error) = result.error.as_ref() {
+        details.push(error.clone());
+    } else if let Some(actual) = result.actual.as_ref() {
+        details.push(format!(
+            "{} {}",
+            tag,
+            t!("check.actual", digest = hash::to_hex(actual))
+        ));
+    }
+    report::ReportEntry {
+        path,
+        digest_algorithm: Some(result.digest_algorithm),
+        status: result.status.label(),
+        success: result.status == check::CheckStatus::Ok,
+        size: result.size,
+        details,
+    }
+}
+
+/// # レポートを書き出す関数
+/// 検証で一致しなかったときも書き出すので、失敗を返す前に呼びます。
+/// ## 引数
+/// - レポート
+/// - 出力先ファイル Noneのときは何もしない
+fn write_report(
+    report: &report::Report,
+    report_file: Option<&std::path::Path>,
+) -> Result<(), Error> {
+    if let Some(report_file) = report_file {
+        report
+            .write(report_file)
+            .map_err(|e| Error::io(&t!("io.write_report"), e))?;
+        eprintln!("{}", t!("report.written", path = report_file.display()));
+    }
+    Ok(())
+}
+
+fn convert(
+    checksum_file_path: &str,
+    format: checksum_file::ChecksumFormat,
+    output_file_path: Option<String>,
+    digest_algorithm: Option<cli_arg_accepter::DigestAlgorithm>,
+) -> Result<(), Error> {
+    let checksum_file_path = std::path::Path::new(checksum_file_path);
+    let content = std::fs::read_to_string(checksum_file_path)
+        .map_err(|e| Error::io(&t!("io.read_checksum_file"), e))?;
+
+    // GNU形式の行のアルゴリズムは、指定、ファイル名、ハッシュ値の長さの順に決める
+    let digest_algorithm = digest_algorithm
+        .or_else(|| checksum_file::digest_algorithm_from_file_name(checksum_file_path));
+    let checksum_file = checksum_file::parse(&content, digest_algorithm);
+    if checksum_file.malformed_lines > 0 {
+        // 読み込めなかった行を黙って落とさない
+        return Err(Error::InvalidArgument(t!(
+            "convert.malformed",
+            count = checksum_file.malformed_lines
+        )));
+    }
+    let content = checksum_file::format_file(
+        &checksum_file.entries,
+        format,
+        checksum_file.text_normalization.as_ref(),
+    )
+    .map_err(Error::InvalidArgument)?;
+
+    match output_file_path {
+        Some(output_file_path) => {
+            std::fs::write(&output_file_path, content)
+                .map_err(|e| Error::io(&t!("io.write_file"), e))?;
+            eprintln!(
+                "{}",
+                t!(
+                    "convert.written",
+                    count = checksum_file.entries.len(),
+                    path = output_file_path
+                )
+            );
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// # 補完スクリプトかmanページを作る関数
+/// ## 引数
+/// - 作るもの
+/// - manページを作るサブコマンド Noneのときはdigest_tool自体
+/// - 出力先ファイル Noneのときは標準出力
+fn generate(
+    target: generate::Target,
+    subcommand: Option<String>,
+    output_file_path: Option<String>,
+) -> Result<(), Error> {
+    let mut content = Vec::new();
+    generate::generate(target, subcommand.as_deref(), &mut content)?;
+
+    match output_file_path {
+        Some(output_file_path) => {
+            std::fs::write(&output_file_path, content)
+                .map_err(|e| Error::io(&t!("io.write_file"), e))?;
+            eprintln!("{}", t!("generate.written", path = output_file_path));
+        }
+        None => print!("{}", String::from_utf8_lossy(&content)),
+    }
+    Ok(())
+}
+
+/// 入力されたファイルとディレクトリの下のファイルのハッシュ値を計算します。
+/// パスはbase_dirからの相対パスにします。
+fn hash_files(
+    inputs: Vec<cli_arg_accepter::Input>,
+    base_dir: &std::path::Path,
+    digest_algorithms: &[cli_arg_accepter::DigestAlgorithm],
+    text_normalization: Option<&text_normalize::TextNormalization>,
+    exclude: &[glob::Pattern],
+) -> Result<Vec<checksum_file::ChecksumEntry>, Error> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        match input {
+            cli_arg_accepter::Input::File(path) => paths.push(std::path::PathBuf::from(path)),
+            cli_arg_accepter::Input::Text { .. } => {
+                return Err(Error::InvalidArgument(t!("input.text_not_supported")));
+            }
+        }
+    }
+    if paths.is_empty() {
+        return Err(Error::MissingInput(t!("input.no_file_path")));
+    }
+    let files = multi_file::collect_files(&paths, exclude)
+        .map_err(|e| Error::io(&t!("io.open_file"), e))?;
+
+    let progress_bar_style = indicatif::ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} ({eta}) {msg}");
+    let mut entries = Vec::new();
+    for file in files.iter() {
+        let path = multi_file::relative_path(file, base_dir)
+            .map_err(|e| Error::io(&t!("io.open_file"), e))?;
+        // 結果を標準出力に書くこともあるので、プログレスバーは標準エラー出力に表示する
+        let progress_bar = indicatif::ProgressBar::new(0);
+        progress_bar.set_style(progress_bar_style.clone());
+        progress_bar.set_draw_rate(4);
+        progress_bar.set_message(path.clone());
+        if let Ok(metadata) = std::fs::metadata(file) {
+            progress_bar.set_length(metadata.len());
+        }
+        let hash_result = multi_file::hash_file(
+            file,
+            digest_algorithms,
+            text_normalization,
+            progress_bar.clone(),
+        );
+        progress_bar.finish_and_clear();
+        let (size, digests) =
+            hash_result.map_err(|e| Error::io(&t!("io.read_file_at", path = file.display()), e))?;
+        entries.push(checksum_file::ChecksumEntry {
+            path,
+            size: Some(size),
+            digests,
+        });
+    }
+    Ok(entries)
+}
+
+fn audit(
+    inputs: Vec<cli_arg_accepter::Input>,
+    known_file_path: &str,
+    base_dir: Option<String>,
+    exclude: &[glob::Pattern],
+    report_file: Option<&std::path::Path>,
+) -> Result<(), Error> {
+    let mut report = report::Report::new("audit");
+    let content = std::fs::read_to_string(known_file_path)
+        .map_err(|e| Error::io(&t!("io.read_known_file"), e))?;
+    let known_file = checksum_file::parse(&content, None);
+    if known_file.entries.is_empty() {
+        return Err(Error::InvalidArgument(t!("audit.no_valid_lines")));
+    }
+
+    // 既知のパスは絶対パスのこともあるので、入力ファイルと同じ基準の相対パスにそろえる
+    let base_dir = match base_dir {
+        Some(base_dir) => std::path::PathBuf::from(base_dir),
+        None => std::path::PathBuf::from("."),
+    };
+    let mut known = Vec::new();
+    for entry in known_file.entries {
+        let path = multi_file::relative_path(&base_dir.join(&entry.path), &base_dir)
+            .map_err(|e| Error::io(&t!("io.open_file"), e))?;
+        known.push(checksum_file::ChecksumEntry { path, ..entry });
+    }
+
+    // 既知の一覧にあるすべてのアルゴリズムで計算する
+    let mut digest_algorithms = Vec::new();
+    for entry in known.iter() {
+        for (digest_algorithm, _) in entry.digests.iter() {
+            if !digest_algorithms.contains(digest_algorithm) {
+                digest_algorithms.push(*digest_algorithm);
+            }
+        }
+    }
+    println!(
+        "{}",
+        t!(
+            "digest.algorithm",
+            algorithm = digest_algorithms
+                .iter()
+                .map(|digest_algorithm| digest_algorithm.bsd_tag())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    );
+    let files = hash_files(inputs, &base_dir, &digest_algorithms, None, exclude)?;
+    for digest_algorithm in digest_algorithms.iter() {
+        report.add_algorithm(*digest_algorithm);
+    }
+
+    let mut summary = audit::AuditSummary::default();
+    for result in audit::audit(&known, &files) {
+        report.add(audit_report_entry(&result, &known, &files));
+        match result.known_path.as_ref() {
+            Some(known_path) => println!(
+                "{}",
+                t!(
+                    "audit.moved_from",
+                    path = checksum_file::escape_path(&result.path),
+                    status = result.status.label(),
+                    known_path = checksum_file::escape_path(known_path)
+                )
+            ),
+            None => println!(
+                "{}: {}",
+                checksum_file::escape_path(&result.path),
+                result.status.label()
+            ),
+        }
+        summary.add(result.status);
+    }
+    println!(
+        "{}",
+        t!(
+            "audit.summary",
+            matched = summary.matched,
+            moved = summary.moved,
+            new = summary.new,
+            changed = summary.changed,
+            missing = summary.missing
+        )
+    );
+    if known_file.malformed_lines > 0 {
+        eprintln!(
+            "{}",
+            t!(
+                "warning.malformed_lines",
+                count = known_file.malformed_lines
+            )
+        );
+    }
+    write_report(&report, report_file)?;
+    if !summary.is_success() {
+        return Err(Error::Mismatch(t!("audit.mismatch")));
+    }
+    Ok(())
+}
+
+/// # 監査結果をレポートの1行にする関数
+/// 既知のハッシュ値と計算したハッシュ値のうち、結果に関係するものを書きます。
+fn audit_report_entry(
+    result: &audit::AuditResult,
+    known: &[checksum_file::ChecksumEntry],
+    files: &[checksum_file::ChecksumEntry],
+) -> report::ReportEntry {
+    let find = |entries: &[checksum_file::ChecksumEntry], path: &str| {
+        entries.iter().find(|entry| entry.path == path).cloned()
+    };
+    let known_entry = find(known, result.known_path.as_ref().unwrap_or(&result.path));
+    // 欠落の場合は、同じパスの入力ファイルがあっても別のファイルとして扱われている
+    let file = match result.status {
+        audit::AuditStatus::Missing => None,
+        _ => find(files, &result.path),
+    };
+
+    let mut details = Vec::new();
+    if let Some(known_path) = result.known_path.as_ref() {
+        details.push(t!("report.moved_from", path = known_path));
+    }
+    match (result.status, known_entry.as_ref(), file.as_ref()) {
+        (audit::AuditStatus::Matched, _, Some(file)) | (audit::AuditStatus::New, _, Some(file)) => {
+            details.extend(digest_details(&file.digests));
+        }
+        (_, Some(known_entry), file) => {
+            for (digest_algorithm, expected) in known_entry.digests.iter() {
+                let tag = digest_algorithm.bsd_tag();
+                details.push(format!(
+                    "{} {}",
+                    tag,
+                    t!("check.expected", digest = hash::to_hex(expected))
+                ));
+                let actual = file.and_then(|file| {
+                    file.digests
+                        .iter()
+                        .find(|(algorithm, _)| algorithm == digest_algorithm)
+                });
+                if let Some((_, actual)) = actual {
+                    details.push(format!(
+                        "{} {}",
+                        tag,
+                        t!("check.actual", digest = hash::to_hex(actual))
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+    report::ReportEntry {
+        path: result.path.clone(),
+        digest_algorithm: None,
+        status: result.status.label(),
+        success: result.status == audit::AuditStatus::Matched,
+        size: file.and_then(|file| file.size),
+        details,
+    }
+}
+
+/// ベースラインファイルのMACの鍵を取り出す環境変数
+const BASELINE_KEY_ENV: &str = "DIGEST_TOOL_BASELINE_KEY";
+
+/// # ベースラインファイルのMACの鍵を読む関数
+/// 鍵のファイルは、`echo`で作っても同じ鍵になるように、末尾の改行を除きます。
+/// ## 引数
+/// - 鍵のファイル Noneのときは環境変数から読む
+fn baseline_key(key_file_path: Option<String>) -> Result<Vec<u8>, Error> {
+    let mut key = match key_file_path {
+        Some(key_file_path) => {
+            std::fs::read(key_file_path).map_err(|e| Error::io(&t!("io.read_key_file"), e))?
+        }
+        None => match std::env::var_os(BASELINE_KEY_ENV) {
+            Some(key) => key.to_string_lossy().into_owned().into_bytes(),
+            None => return Err(Error::InvalidArgument(t!("baseline.no_key"))),
+        },
+    };
+    while key
+        .last()
+        .is_some_and(|byte| *byte == b'\n' || *byte == b'\r')
+    {
+        key.pop();
+    }
+    if key.is_empty() {
+        return Err(Error::InvalidArgument(t!("baseline.empty_key")));
+    }
+    Ok(key)
+}
+
+/// # ベースラインファイルを作る関数
+/// ## 引数
+/// - 入力されたファイルとディレクトリ
+/// - ハッシュアルゴリズム
+/// - 除外するパターン
+/// - 出力先ファイル Noneのときは標準出力
+/// - MACの鍵のファイル Noneのときは環境変数から読む
+fn baseline_create(
+    inputs: Vec<cli_arg_accepter::Input>,
+    digest_algorithm: cli_arg_accepter::DigestAlgorithm,
+    exclude: &[glob::Pattern],
+    output_file_path: Option<String>,
+    key_file_path: Option<String>,
+) -> Result<(), Error> {
+    // ファイルを読む前に、鍵があるか確かめる
+    let key = baseline_key(key_file_path)?;
+    let roots: Vec<String> = inputs
+        .into_iter()
+        .filter_map(|input| match input {
+            cli_arg_accepter::Input::File(path) => Some(path),
+            cli_arg_accepter::Input::Text { .. } => None,
+        })
+        .collect();
+    let root_paths: Vec<std::path::PathBuf> = roots.iter().map(std::path::PathBuf::from).collect();
+    let snapshot = baseline::snapshot(&root_paths, digest_algorithm, exclude);
+    // 読めないファイルがあるまま記録すると、後から比べられないので止める
+    if let Some((path, e)) = snapshot.unreadable.into_iter().next() {
+        return Err(Error::io(&t!("io.read_file_at", path = path), e));
+    }
+    let entries = snapshot.entries;
+    let count = entries.len();
+    let baseline_file = baseline::BaselineFile::sign(
+        baseline::Baseline {
+            version: baseline::BASELINE_VERSION,
+            created: chrono::Local::now().to_rfc3339(),
+            algorithm: digest_algorithm.name().to_string(),
+            roots,
+            exclude: exclude
+                .iter()
+                .map(|pattern| pattern.as_str().to_string())
+                .collect(),
+            entries,
+        },
+        &key,
+    );
+    let content = format!(
+        "{}\n",
+        serde_json::to_string_pretty(&baseline_file).unwrap_or_default()
+    );
+
+    match output_file_path {
+        Some(output_file_path) => {
+            std::fs::write(&output_file_path, content)
+                .map_err(|e| Error::io(&t!("io.write_file"), e))?;
+            eprintln!(
+                "{}",
+                t!("baseline.written", count = count, path = output_file_path)
+            );
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// # ベースラインファイルと実際のファイルを比べる関数
+/// MACを確かめてから、ベースラインを作ったときと同じパス、ハッシュアルゴリズム、除外するパターンでファイルを集めて比べます。
+/// ## 返り値
+/// Result型 MACが一致しないか、変化があればMismatch
+fn baseline_compare(baseline_file_path: &str, key_file_path: Option<String>) -> Result<(), Error> {
+    let key = baseline_key(key_file_path)?;
+    let content = std::fs::read_to_string(baseline_file_path)
+        .map_err(|e| Error::io(&t!("io.read_baseline"), e))?;
+    let baseline_file: baseline::BaselineFile = serde_json::from_str(&content)
+        .map_err(|e| Error::InvalidArgument(t!("baseline.invalid", error = e)))?;
+    // 書き換えられたベースラインの中身は信用しない
+    if !baseline_file.verify(&key) {
+        return Err(Error::Mismatch(t!("baseline.tampered")));
+    }
+    let baseline = baseline_file.baseline;
+    if baseline.version != baseline::BASELINE_VERSION {
+        return Err(Error::InvalidArgument(t!(
+            "baseline.unsupported_version",
+            version = baseline.version
+        )));
+    }
+    let digest_algorithm = cli_arg_accepter::DigestAlgorithm::from_name(&baseline.algorithm)
+        .ok_or_else(|| {
+            Error::InvalidArgument(t!("baseline.invalid", error = baseline.algorithm))
+        })?;
+    let exclude = baseline
+        .exclude
+        .iter()
+        .map(|pattern| glob::Pattern::new(pattern))
+        .collect::<Result<Vec<glob::Pattern>, glob::PatternError>>()
+        .map_err(|e| Error::InvalidArgument(t!("baseline.invalid", error = e)))?;
+    println!(
+        "{}",
+        t!("digest.algorithm", algorithm = digest_algorithm.bsd_tag())
+    );
+
+    // なくなった入力の下のファイルは、削除として数える
+    let roots: Vec<std::path::PathBuf> = baseline
+        .roots
+        .iter()
+        .map(std::path::PathBuf::from)
+        .filter(|root| root.exists())
+        .collect();
+    // 読めないファイルがあっても止めずに、UNREADABLEとして数える
+    let live = baseline::snapshot(&roots, digest_algorithm, &exclude);
+
+    let (drifts, summary) = baseline::compare(&baseline.entries, &live.entries, &live.unreadable);
+    for drift in drifts {
+        let path = checksum_file::escape_path(&drift.path);
+        if let Some(error) = &drift.error {
+            println!("{}: {} ({})", path, drift.kind.label(), error);
+        } else if drift.changes.is_empty() {
+            println!("{}: {}", path, drift.kind.label());
+        } else {
+            let changes: Vec<String> = drift
+                .changes
+                .iter()
+                .map(|(name, old, new)| t!("baseline.change", name = name, old = old, new = new))
+                .collect();
+            println!("{}: {} ({})", path, drift.kind.label(), changes.join(", "));
+        }
+    }
+    println!(
+        "{}",
+        t!(
+            "baseline.summary",
+            unchanged = summary.unchanged,
+            added = summary.added,
+            removed = summary.removed,
+            modified = summary.modified,
+            metadata = summary.metadata,
+            unreadable = summary.unreadable
+        )
+    );
+    if !summary.is_success() {
+        return Err(Error::Mismatch(t!("baseline.drift")));
+    }
+    Ok(())
+}
+
+/// # 拡張属性を読み書きするファイルを集める関数
+/// ## 返り値
+/// Result型 拡張属性を使えない環境ではPlatform あるいは、入力されたファイルとディレクトリの下のファイル
+fn xattr_files(
+    inputs: Vec<cli_arg_accepter::Input>,
+    exclude: &[glob::Pattern],
+) -> Result<Vec<std::path::PathBuf>, Error> {
+    if !xattr_digest::is_supported_platform() {
+        return Err(Error::Platform(t!("xattr.unsupported_platform")));
+    }
+    let paths: Vec<std::path::PathBuf> = inputs
+        .into_iter()
+        .filter_map(|input| match input {
+            cli_arg_accepter::Input::File(path) => Some(std::path::PathBuf::from(path)),
+            cli_arg_accepter::Input::Text { .. } => None,
+        })
+        .collect();
+    multi_file::collect_files(&paths, exclude).map_err(|e| Error::io(&t!("io.open_file"), e))
+}
+
+/// # ハッシュ値を計算して拡張属性に書く関数
+/// 拡張属性に対応していないファイルシステムのファイルは、警告して飛ばします。
+/// ## 返り値
+/// Result型 書けなかったファイルがあれば、最初の理由を付けたIo
+fn xattr_write(
+    inputs: Vec<cli_arg_accepter::Input>,
+    digest_algorithms: Vec<cli_arg_accepter::DigestAlgorithm>,
+    default_digest_algorithm: cli_arg_accepter::DigestAlgorithm,
+    exclude: &[glob::Pattern],
+) -> Result<(), Error> {
+    let files = xattr_files(inputs, exclude)?;
+    let digest_algorithms = if digest_algorithms.is_empty() {
+        vec![default_digest_algorithm]
     } else {
-        println!("コンテクストメニューから削除できませんでした。");
+        digest_algorithms
+    };
+
+    let mut written = 0;
+    let mut unsupported = 0;
+    let mut errors = Vec::new();
+    for file in files {
+        let path = checksum_file::escape_path(&file.to_string_lossy());
+        match xattr_digest::write(&file, &digest_algorithms) {
+            Ok(stored) => {
+                written += 1;
+                for (digest_algorithm, stored_digest) in stored {
+                    println!(
+                        "{}: {} {}",
+                        path,
+                        digest_algorithm.name(),
+                        hash::to_hex(&stored_digest.hash_value)
+                    );
+                }
+            }
+            Err(e) if xattr_digest::is_unsupported(&e) => {
+                unsupported += 1;
+                eprintln!("{}", t!("xattr.unsupported_fs", path = path));
+            }
+            Err(e) => {
+                eprintln!("{}", t!("xattr.failed", path = path, error = e));
+                errors.push(e);
+            }
+        }
+    }
+    eprintln!(
+        "{}",
+        t!(
+            "xattr.write_summary",
+            written = written,
+            unsupported = unsupported,
+            failed = errors.len()
+        )
+    );
+    let count = errors.len();
+    match errors.into_iter().next() {
+        Some(e) => Err(Error::io(&t!("xattr.write_failed", count = count), e)),
+        None => Ok(()),
+    }
+}
+
+/// # 拡張属性に保存したハッシュ値を表示する関数
+/// ## 返り値
+/// Result型 読めなかったファイルがあれば、最初の理由を付けたIo
+fn xattr_read(
+    inputs: Vec<cli_arg_accepter::Input>,
+    digest_algorithms: &[cli_arg_accepter::DigestAlgorithm],
+    exclude: &[glob::Pattern],
+) -> Result<(), Error> {
+    let files = xattr_files(inputs, exclude)?;
+    let mut errors = Vec::new();
+    for file in files {
+        let path = checksum_file::escape_path(&file.to_string_lossy());
+        match xattr_digest::read(&file, digest_algorithms) {
+            Ok(stored) if stored.is_empty() => {
+                println!("{}: {}", path, xattr_digest::ScrubStatus::Missing.label());
+            }
+            Ok(stored) => {
+                for (digest_algorithm, stored_digest) in stored {
+                    println!(
+                        "{}: {} {} {}",
+                        path,
+                        digest_algorithm.name(),
+                        hash::to_hex(&stored_digest.hash_value),
+                        stored_digest
+                            .computed
+                            .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+                    );
+                }
+            }
+            Err(e) if xattr_digest::is_unsupported(&e) => {
+                println!(
+                    "{}: {}",
+                    path,
+                    xattr_digest::ScrubStatus::Unsupported.label()
+                );
+            }
+            Err(e) => {
+                println!(
+                    "{}: {} ({})",
+                    path,
+                    xattr_digest::ScrubStatus::Failed.label(),
+                    e
+                );
+                errors.push(e);
+            }
+        }
+    }
+    let count = errors.len();
+    match errors.into_iter().next() {
+        Some(e) => Err(Error::io(&t!("xattr.read_failed", count = count), e)),
+        None => Ok(()),
+    }
+}
+
+/// # 拡張属性に保存したハッシュ値で、ファイルが壊れていないか確かめる関数
+/// 保存した後に更新されたファイルは、壊れたのではなく書き換えられたものとして分けます。
+/// ## 返り値
+/// Result型 壊れたファイルか、確かめられなかったファイルがあればMismatch
+fn xattr_scrub(
+    inputs: Vec<cli_arg_accepter::Input>,
+    digest_algorithms: &[cli_arg_accepter::DigestAlgorithm],
+    exclude: &[glob::Pattern],
+) -> Result<(), Error> {
+    let files = xattr_files(inputs, exclude)?;
+    let mut summary = xattr_digest::ScrubSummary::default();
+    for file in files {
+        let path = checksum_file::escape_path(&file.to_string_lossy());
+        let results = match xattr_digest::scrub(&file, digest_algorithms) {
+            Ok(results) => results,
+            Err(e) if xattr_digest::is_unsupported(&e) => {
+                let status = xattr_digest::ScrubStatus::Unsupported;
+                println!("{}: {}", path, status.label());
+                summary.add(status);
+                continue;
+            }
+            Err(e) => {
+                let status = xattr_digest::ScrubStatus::Failed;
+                println!("{}: {} ({})", path, status.label(), e);
+                summary.add(status);
+                continue;
+            }
+        };
+        if results.is_empty() {
+            println!("{}: {}", path, xattr_digest::ScrubStatus::Missing.label());
+            summary.add(xattr_digest::ScrubStatus::Missing);
+        }
+        for result in results {
+            println!(
+                "{}: {} ({})",
+                path,
+                result.status.label(),
+                result.digest_algorithm.name()
+            );
+            if result.status != xattr_digest::ScrubStatus::Ok {
+                println!(
+                    "    {}",
+                    t!(
+                        "check.expected",
+                        digest = hash::to_hex(&result.stored.hash_value)
+                    )
+                );
+                println!(
+                    "    {}",
+                    t!("check.actual", digest = hash::to_hex(&result.actual))
+                );
+                println!(
+                    "    {}",
+                    t!(
+                        "xattr.computed",
+                        time = result
+                            .stored
+                            .computed
+                            .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+                    )
+                );
+            }
+            summary.add(result.status);
+        }
+    }
+    println!(
+        "{}",
+        t!(
+            "xattr.scrub_summary",
+            ok = summary.ok,
+            corrupt = summary.corrupt,
+            outdated = summary.outdated,
+            missing = summary.missing,
+            unsupported = summary.unsupported,
+            failed = summary.failed
+        )
+    );
+    if !summary.is_success() {
+        return Err(Error::Mismatch(t!("xattr.scrub_failed")));
+    }
+    Ok(())
+}
+
+fn check(
+    checksum_file_path: &str,
+    digest_algorithm: Option<cli_arg_accepter::DigestAlgorithm>,
+    text_normalization: Option<text_normalize::TextNormalization>,
+    report_file: Option<&std::path::Path>,
+) -> Result<(), Error> {
+    let mut report = report::Report::new("check");
+    let checksum_file_path = std::path::Path::new(checksum_file_path);
+    let content = std::fs::read_to_string(checksum_file_path)
+        .map_err(|e| Error::io(&t!("io.read_checksum_file"), e))?;
+
+    // GNU形式の行のアルゴリズムは、指定、ファイル名、ハッシュ値の長さの順に決める
+    let digest_algorithm = digest_algorithm
+        .or_else(|| checksum_file::digest_algorithm_from_file_name(checksum_file_path));
+    let checksum_file = checksum_file::parse(&content, digest_algorithm);
+    let text_normalization = text_normalization.or(checksum_file.text_normalization);
+    if let Some(text_normalization) = text_normalization.as_ref() {
+        println!(
+            "{}",
+            t!(
+                "digest.text_normalization",
+                normalization = text_normalization
+            )
+        );
+    }
+    if checksum_file.entries.is_empty() {
+        return Err(Error::InvalidArgument(t!("check.no_valid_lines")));
+    }
+
+    // パスはチェックサムファイルのあるディレクトリからの相対パスとして扱う
+    let base_dir = checksum_file_path
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let progress_bar_style = indicatif::ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} ({eta})");
+    let mut summary = check::CheckSummary::default();
+    for entry in checksum_file.entries.iter() {
+        let progress_bar = indicatif::ProgressBar::new(0);
+        progress_bar.set_style(progress_bar_style.clone());
+        progress_bar.set_draw_rate(4);
+        let results = check::check_entry(
+            entry,
+            base_dir,
+            text_normalization.as_ref(),
+            progress_bar.clone(),
+        );
+        progress_bar.finish_and_clear();
+        for result in results {
+            println!(
+                "{}: {}",
+                checksum_file::escape_path(&result.path),
+                result.status.label()
+            );
+            if let Some(error) = result.error.as_ref() {
+                println!("    {}", error);
+            } else if let Some(actual) = result.actual.as_ref() {
+                if result.status != check::CheckStatus::Ok {
+                    let tag = result.digest_algorithm.bsd_tag();
+                    println!(
+                        "    {} {}",
+                        tag,
+                        t!("check.expected", digest = hash::to_hex(&result.expected))
+                    );
+                    println!(
+                        "    {} {}",
+                        tag,
+                        t!("check.actual", digest = hash::to_hex(actual))
+                    );
+                }
+            }
+            summary.add(result.status);
+            report.add_algorithm(result.digest_algorithm);
+            report.add(check_report_entry(result.path.clone(), &result, Vec::new()));
+        }
     }
+
+    println!(
+        "{}",
+        t!(
+            "check.summary",
+            ok = summary.ok,
+            failed = summary.failed,
+            missing = summary.missing
+        )
+    );
+    if checksum_file.malformed_lines > 0 {
+        eprintln!(
+            "{}",
+            t!(
+                "warning.malformed_lines",
+                count = checksum_file.malformed_lines
+            )
+        );
+    }
+    write_report(&report, report_file)?;
+    if !summary.is_success() {
+        return Err(Error::Mismatch(t!("check.mismatch")));
+    }
+    Ok(())
 }
 
+fn verify(
+    inputs: Vec<cli_arg_accepter::Input>,
+    text_normalization: Option<text_normalize::TextNormalization>,
+    exclude: &[glob::Pattern],
+    report_file: Option<&std::path::Path>,
+) -> Result<(), Error> {
+    let mut report = report::Report::new("check");
+    let progress_bar_style = indicatif::ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} ({eta})");
+    let mut summary = check::CheckSummary::default();
+    let mut no_evidence = 0;
+    let mut success = true;
+    for input in inputs {
+        let input_path = match input {
+            cli_arg_accepter::Input::File(path) => std::path::PathBuf::from(path),
+            cli_arg_accepter::Input::Text { .. } => continue,
+        };
+
+        // ディレクトリなら、その下のファイルのうち根拠のないものも表示する
+        let (evidences, files) = if input_path.is_dir() {
+            let files = multi_file::collect_files(std::slice::from_ref(&input_path), exclude).map(
+                |files| {
+                    files
+                        .into_iter()
+                        .filter(|file| !sidecar::is_checksum_file(file))
+                        .collect::<Vec<std::path::PathBuf>>()
+                },
+            );
+            (sidecar::find_in_dir(&input_path), files)
+        } else {
+            (
+                sidecar::find_for_file(&input_path),
+                Ok(vec![input_path.clone()]),
+            )
+        };
+        let (evidences, files) = match (evidences, files) {
+            (Ok(evidences), Ok(files)) => (evidences, files),
+            (Err(e), _) | (_, Err(e)) => {
+                debug!("{:?}", e);
+                eprintln!(
+                    "{}",
+                    t!(
+                        "verify.search_failed",
+                        path = input_path.display(),
+                        error = e
+                    )
+                );
+                report.add(report::ReportEntry {
+                    path: input_path.to_string_lossy().into_owned(),
+                    digest_algorithm: None,
+                    status: "ERROR",
+                    success: false,
+                    size: None,
+                    details: vec![e.to_string()],
+                });
+                success = false;
+                continue;
+            }
+        };
+
+        let mut verified = Vec::new();
+        for evidence in evidences.iter() {
+            let progress_bar = indicatif::ProgressBar::new(0);
+            progress_bar.set_style(progress_bar_style.clone());
+            progress_bar.set_draw_rate(4);
+            let results = check::check_entry(
+                &evidence.entry,
+                &evidence.base_dir,
+                text_normalization
+                    .as_ref()
+                    .or(evidence.text_normalization.as_ref()),
+                progress_bar.clone(),
+            );
+            progress_bar.finish_and_clear();
+            let target_path = evidence.target_path();
+            for result in results {
+                let evidence_line = t!(
+                    "verify.evidence",
+                    path = evidence.checksum_file_path.display(),
+                    algorithm = result.digest_algorithm.bsd_tag()
+                );
+                println!(
+                    "{}: {}",
+                    checksum_file::escape_path(&target_path.to_string_lossy()),
+                    result.status.label()
+                );
+                println!("    {}", evidence_line);
+                if let Some(error) = result.error.as_ref() {
+                    println!("    {}", error);
+                } else if let Some(actual) = result.actual.as_ref() {
+                    if result.status != check::CheckStatus::Ok {
+                        println!(
+                            "    {}",
+                            t!("check.expected", digest = hash::to_hex(&result.expected))
+                        );
+                        println!("    {}", t!("check.actual", digest = hash::to_hex(actual)));
+                    }
+                }
+                summary.add(result.status);
+                report.add_algorithm(result.digest_algorithm);
+                report.add(check_report_entry(
+                    target_path.to_string_lossy().into_owned(),
+                    &result,
+                    vec![evidence_line],
+                ));
+            }
+            if let Ok(target_path) = multi_file::absolute_path(&target_path) {
+                verified.push(target_path);
+            }
+        }
+
+        for file in files {
+            let has_evidence = multi_file::absolute_path(&file)
+                .map(|file| verified.contains(&file))
+                .unwrap_or(false);
+            if !has_evidence {
+                println!(
+                    "{}",
+                    t!(
+                        "verify.no_evidence",
+                        path = checksum_file::escape_path(&file.to_string_lossy())
+                    )
+                );
+                no_evidence += 1;
+                // 指定されたファイルを検証できなければ失敗にする
+                if !input_path.is_dir() {
+                    success = false;
+                }
+                report.add(report::ReportEntry {
+                    path: file.to_string_lossy().into_owned(),
+                    digest_algorithm: None,
+                    status: "NO EVIDENCE",
+                    success: input_path.is_dir(),
+                    size: None,
+                    details: Vec::new(),
+                });
+            }
+        }
+    }
+
+    println!(
+        "{}",
+        t!(
+            "verify.summary",
+            ok = summary.ok,
+            failed = summary.failed,
+            missing = summary.missing,
+            no_evidence = no_evidence
+        )
+    );
+    write_report(&report, report_file)?;
+    if !(success && summary.is_success()) {
+        return Err(Error::Mismatch(t!("verify.failed")));
+    }
+    Ok(())
+}
+
+fn tee(
+    output_file_path: Option<String>,
+    digest_file_path: Option<String>,
+    digest_algorithms: Vec<cli_arg_accepter::DigestAlgorithm>,
+) -> Result<(), Error> {
+    debug!("teeモードで起動しました。");
+    tee::tee(
+        output_file_path.as_deref(),
+        digest_file_path.as_deref(),
+        &digest_algorithms,
+    )
+    .map_err(|e| Error::io(&t!("io.tee"), e))
+}
+
+#[cfg(target_os = "windows")]
+fn setup() -> Result<(), Error> {
+    eprintln!("{}", t!("setup.adding"));
+    context_menu::set_to_context_menu()
+        .map_err(|e| Error::Platform(t!("setup.failed", error = e)))?;
+    println!("{}", t!("setup.done"));
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn cleanup() -> Result<(), Error> {
+    eprintln!("{}", t!("cleanup.removing"));
+    context_menu::remove_from_context_menu()
+        .map_err(|e| Error::Platform(t!("cleanup.failed", error = e)))?;
+    println!("{}", t!("cleanup.done"));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn setup() -> Result<(), Error> {
+    Err(Error::Platform(t!("platform.setup_windows_only")))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn cleanup() -> Result<(), Error> {
+    Err(Error::Platform(t!("platform.cleanup_windows_only")))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn gui() -> Result<(), Error> {
+    Err(Error::Platform(t!("platform.gui_windows_only")))
+}
+
+#[cfg(target_os = "windows")]
 extern crate native_windows_gui as nwg;
-use std::rc::Rc;
-fn gui() {
+#[cfg(target_os = "windows")]
+fn gui() -> Result<(), Error> {
     debug!("GUIモードで起動しました。");
 
-    nwg::init().unwrap_or_else(|e| {
-        debug!("Failed to init Native Windows GUI");
-        debug!("{:?}", e);
-        std::process::exit(0);
-    });
+    nwg::init().map_err(|e| Error::Platform(t!("gui.init_failed", error = e)))?;
 
-    nwg::Font::set_global_family("Segoe UI").unwrap_or_else(|e| {
-        debug!("Failed to set default font");
-        debug!("{:?}", e);
-        std::process::exit(0);
-    });
+    nwg::Font::set_global_family("Segoe UI")
+        .map_err(|e| Error::Platform(t!("gui.font_failed", error = e)))?;
 
     // ウェジットのオブジェクトを作成
     let mut window = Default::default();
@@ -196,13 +1934,13 @@ fn gui() {
         .unwrap();
 
     nwg::Button::builder()
-        .text("右クリックメニューにDigtestToolを追加します。")
+        .text(&t!("gui.add_button"))
         .parent(&window)
         .build(&mut button_set_context_menu)
         .unwrap();
 
     nwg::Button::builder()
-        .text("右クリックメニューからDigest Toolを削除します。")
+        .text(&t!("gui.remove_button"))
         .parent(&window)
         .build(&mut button_remove_context_menu)
         .unwrap();
@@ -227,7 +1965,7 @@ fn gui() {
         .build(&layout)
         .unwrap();
 
-    let window = Rc::new(window);
+    let window = std::rc::Rc::new(window);
     let events_window = window.clone();
 
     // イベントをバインドさせてる。handlerイベントハンドラー(イベントを受け取ってくれるオブジェクト)
@@ -245,13 +1983,13 @@ fn gui() {
                         nwg::modal_info_message(
                             &events_window.handle,
                             "Digest Tool",
-                            "右クリックメニューに追加しました。",
+                            &t!("gui.added"),
                         );
                     } else {
                         nwg::modal_info_message(
                             &events_window.handle,
                             "Digest Tool",
-                            "右クリックメニューに追加できませんでした。",
+                            &t!("gui.add_failed"),
                         );
                     }
                     // ファイルダイアログ
@@ -261,13 +1999,13 @@ fn gui() {
                         nwg::modal_info_message(
                             &events_window.handle,
                             "Digest Tool",
-                            "右クリックメニューから削除しました。",
+                            &t!("gui.removed"),
                         );
                     } else {
                         nwg::modal_info_message(
                             &events_window.handle,
                             "Digest Tool",
-                            "右クリックメニューから削除できませんでした。",
+                            &t!("gui.remove_failed"),
                         );
                     }
                 }
@@ -279,4 +2017,41 @@ fn gui() {
     // これは何をしているかわからない、、
     nwg::dispatch_thread_events();
     nwg::unbind_event_handler(&handler);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checksum_entry(path: &str, byte: u8) -> checksum_file::ChecksumEntry {
+        checksum_file::ChecksumEntry {
+            path: path.to_string(),
+            size: Some(3),
+            digests: vec![(cli_arg_accepter::DigestAlgorithm::Sha2_256, vec![byte; 32])],
+        }
+    }
+
+    /// 監査で変更されたファイルは、期待値と計算値の両方を付けた失敗になる
+    #[test]
+    fn ok_audit_report_entry() {
+        let known = vec![checksum_entry("a.txt", 0xaa)];
+        let files = vec![checksum_entry("a.txt", 0xbb)];
+        let results = audit::audit(&known, &files);
+        assert_eq!(audit::AuditStatus::Changed, results[0].status);
+
+        let mut report = report::Report::new("audit");
+        report.add_algorithm(cli_arg_accepter::DigestAlgorithm::Sha2_256);
+        report.add(audit_report_entry(&results[0], &known, &files));
+        let junit = report.render(
+            report::ReportFormat::Junit,
+            std::time::Duration::from_secs(1),
+        );
+        assert!(junit.contains("<testsuite name=\"digest_tool audit\" tests=\"1\" failures=\"1\""));
+        assert!(junit.contains("classname=\"digest_tool.audit\""));
+        let failure = &junit[junit.find("<failure message=\"CHANGED\">").unwrap()..];
+        let failure = &failure[..failure.find("</failure>").unwrap()];
+        assert!(failure.contains(&"aa".repeat(32)));
+        assert!(failure.contains(&"bb".repeat(32)));
+    }
 }
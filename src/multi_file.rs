@@ -0,0 +1,298 @@
+//! # 複数ファイルモジュール
+//! 複数のファイルやディレクトリをまとめて扱うための関数です。
+
+use crate::cache;
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use crate::text_normalize::{self, TextNormalization};
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+/// ファイルを読み込むバッファのバイト数 設定ファイルと`--buffer_size`から起動時に決めます
+static BUFFER_SIZE: OnceLock<usize> = OnceLock::new();
+
+/// # バッファの大きさを決める関数
+/// 起動時に一度だけ呼びます。呼ばなければ`config::DEFAULT_BUFFER_SIZE`を使います。
+pub fn init_buffer_size(buffer_size: usize) {
+    BUFFER_SIZE.set(buffer_size).ok();
+}
+
+/// ファイルを読み込むバッファのバイト数
+pub fn buffer_size() -> usize {
+    *BUFFER_SIZE
+        .get()
+        .unwrap_or(&crate::config::DEFAULT_BUFFER_SIZE)
+}
+
+/// # ファイル収集関数
+/// 入力されたパスのうち、ディレクトリはその下のファイルを再帰的に集めます。
+/// ディレクトリの中身はパス順に並べます。シンボリックリンクのディレクトリはたどりません。
+/// ## 引数
+/// - 入力されたパス
+/// - 除外するパターン ディレクトリの下のファイルとディレクトリだけに使い、入力されたパスそのものは除きません
+/// ## 返り値
+/// Result型 std::ioのError あるいは、ファイルのパス
+pub fn collect_files(
+    paths: &[PathBuf],
+    exclude: &[glob::Pattern],
+) -> Result<Vec<PathBuf>, std::io::Error> {
+    collect_files_with(paths, exclude, &mut |_, e| Err(e))
+}
+
+/// # 読めない場所を飛ばすファイル収集関数
+/// `collect_files`と同じように集めますが、読めないパスは止まらずに、パスとエラーを`unreadable`に入れます。
+/// 読めないディレクトリは、ディレクトリのパスを入れて、その下は集めません。
+pub fn collect_files_skipping(
+    paths: &[PathBuf],
+    exclude: &[glob::Pattern],
+    unreadable: &mut Vec<(PathBuf, std::io::Error)>,
+) -> Vec<PathBuf> {
+    collect_files_with(paths, exclude, &mut |path, e| {
+        unreadable.push((path.to_path_buf(), e));
+        Ok(())
+    })
+    .unwrap_or_default()
+}
+
+/// 読めないパスをどうするかを`on_error`で決めるファイル収集関数 Errを返せば、そこで止めます
+fn collect_files_with(
+    paths: &[PathBuf],
+    exclude: &[glob::Pattern],
+    on_error: &mut dyn FnMut(&Path, std::io::Error) -> Result<(), std::io::Error>,
+) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = Vec::new();
+    for path in paths {
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => {
+                collect_dir(path, path, exclude, &mut files, on_error)?
+            }
+            Ok(_) => files.push(path.clone()),
+            Err(e) => on_error(path, e)?,
+        }
+    }
+    Ok(files)
+}
+
+fn collect_dir(
+    root: &Path,
+    dir: &Path,
+    exclude: &[glob::Pattern],
+    files: &mut Vec<PathBuf>,
+    on_error: &mut dyn FnMut(&Path, std::io::Error) -> Result<(), std::io::Error>,
+) -> Result<(), std::io::Error> {
+    let entries = std::fs::read_dir(dir).and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, std::io::Error>>()
+    });
+    let mut entries = match entries {
+        Ok(entries) => entries,
+        Err(e) => return on_error(dir, e),
+    };
+    entries.sort();
+    for path in entries {
+        if is_excluded(root, &path, exclude) {
+            continue;
+        }
+        let file_type = match std::fs::symlink_metadata(&path) {
+            Ok(metadata) => metadata.file_type(),
+            Err(e) => {
+                on_error(&path, e)?;
+                continue;
+            }
+        };
+        if file_type.is_dir() {
+            collect_dir(root, &path, exclude, files, on_error)?;
+        } else if file_type.is_file() || std::fs::metadata(&path).is_ok_and(|m| m.is_file()) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 除外するパターンを、名前と、入力されたディレクトリからの`/`区切りの相対パスの両方と比べます。
+/// `*`は`/`に一致しないので、`*.tmp`はどの階層の名前にも、`build/*`は`build`の直下だけに一致します。
+pub fn is_excluded(root: &Path, path: &Path, exclude: &[glob::Pattern]) -> bool {
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let relative = path
+        .strip_prefix(root)
+        .map(|relative| {
+            relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<String>>()
+                .join("/")
+        })
+        .unwrap_or_default();
+    exclude.iter().any(|pattern| {
+        pattern.matches_with(&name, options) || pattern.matches_with(&relative, options)
+    })
+}
+
+/// # 相対パス関数
+/// base_dirから見たpathの相対パスを、`/`区切りの文字列で返します。
+/// どちらも絶対パスにしてから`.`と`..`を取り除いて比べます。
+pub fn relative_path(path: &Path, base_dir: &Path) -> Result<String, std::io::Error> {
+    let path = absolute_path(path)?;
+    let base_dir = absolute_path(base_dir)?;
+    let path_components: Vec<Component> = path.components().collect();
+    let base_components: Vec<Component> = base_dir.components().collect();
+    let common = path_components
+        .iter()
+        .zip(base_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        // ドライブが違うなど、相対パスで表せない
+        return Ok(path.to_string_lossy().replace('\\', "/"));
+    }
+
+    let mut parts: Vec<String> = base_components[common..]
+        .iter()
+        .map(|_| "..".to_string())
+        .collect();
+    parts.extend(
+        path_components[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+    Ok(parts.join("/"))
+}
+
+/// カレントディレクトリを基準に絶対パスにし、`.`と`..`を取り除きます。
+pub fn absolute_path(path: &Path) -> Result<PathBuf, std::io::Error> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    Ok(normalized)
+}
+
+/// # ファイルハッシュ関数
+/// ファイルを一度だけ読み込んで、指定されたすべてのアルゴリズムのハッシュ値を計算します。
+/// テキスト正規化が指定されていれば、正規化してから計算します。
+/// 正規化しないときは、キャッシュを開いていれば、変わっていないファイルのハッシュ値をキャッシュから読みます。
+/// ## 返り値
+/// Result型 std::ioのError あるいは、読み込んだバイト数とアルゴリズムごとのハッシュ値
+#[allow(clippy::type_complexity)]
+pub fn hash_file(
+    path: &Path,
+    digest_algorithms: &[DigestAlgorithm],
+    text_normalization: Option<&TextNormalization>,
+    progress_bar: indicatif::ProgressBar,
+) -> Result<(u64, Vec<(DigestAlgorithm, Vec<u8>)>), std::io::Error> {
+    let open = || -> Result<std::io::BufReader<std::fs::File>, std::io::Error> {
+        Ok(std::io::BufReader::with_capacity(
+            buffer_size(),
+            std::fs::File::open(path)?,
+        ))
+    };
+    match text_normalization {
+        Some(text_normalization) => {
+            // プログレスバーは、正規化する前のファイルを読み込んだバイト数で進める
+            let mut normalized_reader = text_normalize::NormalizeReader::new(
+                progress_bar.wrap_read(open()?),
+                *text_normalization,
+            );
+            let result = hash::multi_digest(
+                &mut normalized_reader,
+                digest_algorithms,
+                indicatif::ProgressBar::hidden(),
+            );
+            progress_bar.finish();
+            result
+        }
+        None => cache::hash_file(path, digest_algorithms, &progress_bar.clone(), || {
+            hash::multi_digest(&mut open()?, digest_algorithms, progress_bar)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 基準ディレクトリの外のパスは`..`で表す
+    #[test]
+    fn ok_relative_path() {
+        let base_dir = Path::new("/data/base");
+        assert_eq!(
+            "a/b.txt",
+            relative_path(Path::new("/data/base/./a/b.txt"), base_dir).unwrap()
+        );
+        assert_eq!(
+            "../other/c.txt",
+            relative_path(Path::new("/data/base/../other/c.txt"), base_dir).unwrap()
+        );
+    }
+
+    /// ディレクトリの中のファイルをパス順に集める
+    #[test]
+    fn ok_collect_files() {
+        let root =
+            std::env::temp_dir().join(format!("digest_tool_multi_file_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        std::fs::write(root.join("c.txt"), b"c").unwrap();
+        std::fs::write(root.join("b").join("a.txt"), b"a").unwrap();
+
+        let files = collect_files(std::slice::from_ref(&root), &[]).unwrap();
+        assert_eq!(
+            vec![root.join("b").join("a.txt"), root.join("c.txt")],
+            files
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// 除外するパターンに一致する名前と相対パスを除き、一致したディレクトリの下はたどらない
+    #[test]
+    fn ok_collect_files_exclude() {
+        let root = std::env::temp_dir().join(format!(
+            "digest_tool_multi_file_exclude_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("build").join("sub")).unwrap();
+        std::fs::write(root.join(".git").join("HEAD"), b"ref").unwrap();
+        std::fs::write(root.join("build").join("a.o"), b"o").unwrap();
+        std::fs::write(root.join("build").join("sub").join("b.txt"), b"b").unwrap();
+        std::fs::write(root.join("c.tmp"), b"c").unwrap();
+        std::fs::write(root.join("d.txt"), b"d").unwrap();
+
+        let exclude: Vec<glob::Pattern> = [".git", "*.tmp", "build/*.o"]
+            .iter()
+            .map(|pattern| glob::Pattern::new(pattern).unwrap())
+            .collect();
+        let files = collect_files(std::slice::from_ref(&root), &exclude).unwrap();
+        assert_eq!(
+            vec![
+                root.join("build").join("sub").join("b.txt"),
+                root.join("d.txt")
+            ],
+            files
+        );
+        // 入力されたパスそのものは除かない
+        let files = collect_files(&[root.join("c.tmp")], &exclude).unwrap();
+        assert_eq!(vec![root.join("c.tmp")], files);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+//! # 出力形式モジュール
+//! ハッシュ値の計算結果を、スクリプトから読みやすい形式(JSON、NDJSON、CSV)で書き出します。
+//! 項目の意味は`docs/output_format.md`にまとめています。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::error::Error;
+use crate::hash;
+use crate::text_normalize::TextNormalization;
+use serde::Serialize;
+
+/// CSV形式の1行目
+pub const CSV_HEADER: &str = "path,size,algorithm,digest,elapsed,throughput,error,normalize";
+
+/// 計算結果の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// これまでどおりの人が読むための表示
+    #[default]
+    Text,
+    /// すべての結果を1つの配列にまとめたJSON
+    Json,
+    /// 1ファイル1行のJSON ファイルごとに書き出します
+    Ndjson,
+    Csv,
+}
+
+impl OutputFormat {
+    /// CLI引数の名前から出力形式を取得する
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "ndjson" => Some(OutputFormat::Ndjson),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// CLI引数で指定する名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Csv => "csv",
+        }
+    }
+}
+
+/// # 1ファイル分の計算結果
+/// 失敗したときも、size、digest、throughputをnullにして書き出します。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DigestRecord {
+    /// 入力されたパス `--text`の場合はnull
+    pub path: Option<String>,
+    /// 読み込んだバイト数 展開やテキスト正規化をした場合は、その後のバイト数
+    pub size: Option<u64>,
+    /// ハッシュアルゴリズムの名前 `-d`と同じ
+    pub algorithm: String,
+    /// 小文字の16進数のハッシュ値
+    pub digest: Option<String>,
+    /// 所要時間(秒)
+    pub elapsed: f64,
+    /// 1秒あたりのバイト数
+    pub throughput: Option<f64>,
+    /// 失敗した理由
+    pub error: Option<String>,
+    /// ハッシュ値を計算する前のテキスト正規化 `--normalize`と同じ文字列 正規化しなかった場合はnull
+    pub normalize: Option<String>,
+}
+
+impl DigestRecord {
+    /// # 計算結果から記録を作る関数
+    /// ## 引数
+    /// - 入力されたパス
+    /// - ハッシュアルゴリズム
+    /// - 読み込んだバイト数とハッシュ値、あるいは失敗した理由
+    /// - 所要時間
+    /// - テキスト正規化 検証するときに同じ正規化を指定できるように書きます
+    pub fn new(
+        path: Option<String>,
+        digest_algorithm: DigestAlgorithm,
+        result: &Result<(u64, Vec<u8>), Error>,
+        elapsed: std::time::Duration,
+        text_normalization: Option<&TextNormalization>,
+    ) -> DigestRecord {
+        let elapsed = elapsed.as_secs_f64();
+        let (size, digest, throughput, error) = match result {
+            Ok((size, hash_value)) => (
+                Some(*size),
+                Some(hash::to_hex(hash_value)),
+                if elapsed > 0.0 {
+                    Some(*size as f64 / elapsed)
+                } else {
+                    None
+                },
+                None,
+            ),
+            Err(e) => (None, None, None, Some(e.to_string())),
+        };
+        DigestRecord {
+            path,
+            size,
+            algorithm: digest_algorithm.name().to_string(),
+            digest,
+            elapsed,
+            throughput,
+            error,
+            normalize: text_normalization.map(|text_normalization| text_normalization.to_string()),
+        }
+    }
+
+    /// NDJSON形式の1行 改行は含みません
+    pub fn to_ndjson(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// CSV形式の1行 改行は含みません
+    pub fn to_csv(&self) -> String {
+        let fields = [
+            self.path.clone().unwrap_or_default(),
+            self.size.map(|size| size.to_string()).unwrap_or_default(),
+            self.algorithm.clone(),
+            self.digest.clone().unwrap_or_default(),
+            self.elapsed.to_string(),
+            self.throughput
+                .map(|throughput| throughput.to_string())
+                .unwrap_or_default(),
+            self.error.clone().unwrap_or_default(),
+            self.normalize.clone().unwrap_or_default(),
+        ];
+        fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+/// CSVの区切りや引用符、改行を含むフィールドは`"`で囲み、`"`を重ねます。
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record() -> DigestRecord {
+        DigestRecord::new(
+            Some("dir/a,\"b\".txt".to_string()),
+            DigestAlgorithm::Md5,
+            &Ok((3, hash::digest_bytes(b"abc", DigestAlgorithm::Md5))),
+            std::time::Duration::from_millis(500),
+            None,
+        )
+    }
+
+    /// NDJSONはスキーマのすべての項目を1行に書く
+    #[test]
+    fn ok_ndjson() {
+        assert_eq!(
+            r#"{"path":"dir/a,\"b\".txt","size":3,"algorithm":"md5","digest":"900150983cd24fb0d6963f7d28e17f72","elapsed":0.5,"throughput":6.0,"error":null,"normalize":null}"#,
+            record().to_ndjson()
+        );
+    }
+
+    /// CSVはカンマや引用符を含むパスを囲む
+    #[test]
+    fn ok_csv() {
+        assert_eq!(
+            r#""dir/a,""b"".txt",3,md5,900150983cd24fb0d6963f7d28e17f72,0.5,6,,"#,
+            record().to_csv()
+        );
+        let failed = DigestRecord::new(
+            None,
+            DigestAlgorithm::Sha2_256,
+            &Err(Error::MissingInput("見つかりません".to_string())),
+            std::time::Duration::from_secs(0),
+            Some(&"eol=lf,unicode=nfc".parse().unwrap()),
+        );
+        assert_eq!(
+            ",,sha2_256,,0,,見つかりません,\"eol=lf,unicode=nfc\"",
+            failed.to_csv()
+        );
+    }
+}
@@ -0,0 +1,685 @@
+//! # レポートモジュール
+//! ハッシュ値の計算や検証の結果を、1つのファイルにまとめたレポートにします。
+//! 監査の引き継ぎに使えるように、実行した環境、ツールのバージョン、ファイルごとの結果、失敗、合計と処理速度を書きます。
+//! CIで読めるように、ファイルごとの結果を1件のテストケースにしたJUnit XML形式でも書けます。
+//! 形式は出力先ファイルの拡張子で決めます。項目の意味は`docs/report.md`にまとめています。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+
+/// レポートの形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    /// CSSも含めて1ファイルで完結するHTML
+    Html,
+    Markdown,
+    /// 一致しなかったファイルを失敗したテストケースにするJUnit XML
+    Junit,
+}
+
+impl ReportFormat {
+    /// 出力先ファイルの拡張子から形式を取得する
+    pub fn from_path(path: &std::path::Path) -> Option<ReportFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "html" | "htm" => Some(ReportFormat::Html),
+            "md" | "markdown" => Some(ReportFormat::Markdown),
+            "xml" => Some(ReportFormat::Junit),
+            _ => None,
+        }
+    }
+}
+
+/// 1ファイル分の結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportEntry {
+    pub path: String,
+    /// 1ファイル、1ハッシュアルゴリズム分の結果の場合のハッシュアルゴリズム
+    pub digest_algorithm: Option<DigestAlgorithm>,
+    /// 結果の表示に使う名前 標準出力と同じ
+    pub status: &'static str,
+    pub success: bool,
+    /// 読み込んだバイト数 読み込めなかった場合はNone
+    pub size: Option<u64>,
+    /// 期待値と計算値、失敗した理由など
+    pub details: Vec<String>,
+}
+
+/// 実行した環境
+#[derive(Debug, Clone, PartialEq)]
+struct Environment {
+    version: String,
+    command: String,
+    os: String,
+    host: Option<String>,
+    user: Option<String>,
+    working_dir: Option<String>,
+}
+
+impl Environment {
+    fn current() -> Environment {
+        Environment {
+            version: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            command: std::env::args().collect::<Vec<String>>().join(" "),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            host: host_name(),
+            user: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok(),
+            working_dir: std::env::current_dir()
+                .ok()
+                .map(|path| path.display().to_string()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn host_name() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // 長すぎる名前は切り詰められるが、終端のNULは必ず残る
+    let result =
+        unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len() - 1) };
+    if result != 0 {
+        return None;
+    }
+    let length = buffer.iter().position(|byte| *byte == 0)?;
+    Some(String::from_utf8_lossy(&buffer[..length]).into_owned())
+}
+
+#[cfg(not(unix))]
+fn host_name() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+/// # レポート
+/// 結果を集めておき、最後に書き出します。経過時間は作ってから書き出すまでの時間です。
+pub struct Report {
+    /// 実行したサブコマンド
+    subcommand: String,
+    environment: Environment,
+    started: chrono::DateTime<chrono::Local>,
+    start_instant: std::time::Instant,
+    digest_algorithms: Vec<DigestAlgorithm>,
+    entries: Vec<ReportEntry>,
+}
+
+impl Report {
+    pub fn new(subcommand: &str) -> Report {
+        Report {
+            subcommand: subcommand.to_string(),
+            environment: Environment::current(),
+            started: chrono::Local::now(),
+            start_instant: std::time::Instant::now(),
+            digest_algorithms: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// 使ったハッシュアルゴリズムを記録する 同じものは一度だけ
+    pub fn add_algorithm(&mut self, digest_algorithm: DigestAlgorithm) {
+        if !self.digest_algorithms.contains(&digest_algorithm) {
+            self.digest_algorithms.push(digest_algorithm);
+        }
+    }
+
+    pub fn add(&mut self, entry: ReportEntry) {
+        self.entries.push(entry);
+    }
+
+    /// # レポートを書き出す関数
+    /// ## 引数
+    /// - 出力先ファイル 拡張子で形式を決める
+    pub fn write(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let format = ReportFormat::from_path(path).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                t!("cli.invalid_report", path = path.display()),
+            )
+        })?;
+        std::fs::write(path, self.render(format, self.start_instant.elapsed()))
+    }
+
+    /// # レポートの内容を作る関数
+    /// ## 引数
+    /// - 形式
+    /// - 経過時間
+    pub fn render(&self, format: ReportFormat, elapsed: std::time::Duration) -> String {
+        let totals = self.totals(elapsed);
+        let failures: Vec<&ReportEntry> =
+            self.entries.iter().filter(|entry| !entry.success).collect();
+        let results: Vec<&ReportEntry> = self.entries.iter().collect();
+        match format {
+            ReportFormat::Html => self.render_html(&totals, &failures, &results),
+            ReportFormat::Markdown => self.render_markdown(&totals, &failures, &results),
+            ReportFormat::Junit => self.render_junit(elapsed),
+        }
+    }
+
+    /// 環境の表の行
+    fn environment_rows(&self) -> Vec<(String, String)> {
+        let environment = &self.environment;
+        let unknown = || "-".to_string();
+        vec![
+            (t!("report.version"), environment.version.clone()),
+            (t!("report.command"), environment.command.clone()),
+            (t!("report.os"), environment.os.clone()),
+            (
+                t!("report.host"),
+                environment.host.clone().unwrap_or_else(unknown),
+            ),
+            (
+                t!("report.user"),
+                environment.user.clone().unwrap_or_else(unknown),
+            ),
+            (
+                t!("report.working_dir"),
+                environment.working_dir.clone().unwrap_or_else(unknown),
+            ),
+            (t!("report.started"), self.started.to_rfc3339()),
+            (
+                t!("report.algorithm"),
+                self.digest_algorithms
+                    .iter()
+                    .map(|digest_algorithm| digest_algorithm.bsd_tag())
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+            ),
+        ]
+    }
+
+    /// 読み込んだバイト数の合計
+    fn bytes(&self) -> u64 {
+        // 1ファイルを複数のアルゴリズムで検証した場合も、バイト数は一度だけ数える
+        let mut counted = std::collections::HashSet::new();
+        self.entries
+            .iter()
+            .filter(|entry| counted.insert(entry.path.as_str()))
+            .filter_map(|entry| entry.size)
+            .sum()
+    }
+
+    /// 失敗した結果の件数
+    fn failure_count(&self) -> usize {
+        self.entries.iter().filter(|entry| !entry.success).count()
+    }
+
+    /// 合計の表の行
+    fn totals(&self, elapsed: std::time::Duration) -> Vec<(String, String)> {
+        let bytes = self.bytes();
+        let mut statuses: Vec<(&str, usize)> = Vec::new();
+        for entry in self.entries.iter() {
+            match statuses
+                .iter_mut()
+                .find(|(status, _)| *status == entry.status)
+            {
+                Some((_, count)) => *count += 1,
+                None => statuses.push((entry.status, 1)),
+            }
+        }
+
+        let elapsed = elapsed.as_secs_f64();
+        let mut rows = vec![
+            (t!("report.results_count"), self.entries.len().to_string()),
+            (
+                t!("report.failures_count"),
+                self.failure_count().to_string(),
+            ),
+        ];
+        rows.extend(
+            statuses
+                .into_iter()
+                .map(|(status, count)| (status.to_string(), count.to_string())),
+        );
+        rows.push((t!("report.bytes"), bytes.to_string()));
+        rows.push((t!("report.elapsed"), format!("{:.3} s", elapsed)));
+        rows.push((
+            t!("report.throughput"),
+            if elapsed > 0.0 {
+                format!("{:.2} MB/s", bytes as f64 / elapsed / 1_000_000.0)
+            } else {
+                "-".to_string()
+            },
+        ));
+        rows
+    }
+
+    /// # JUnit XML形式の内容を作る関数
+    /// 1件の結果を1件のテストケースにし、一致しなかった結果を失敗にします。
+    /// 実行環境と合計は、機械で読めるように訳さない名前のプロパティにします。
+    fn render_junit(&self, elapsed: std::time::Duration) -> String {
+        let environment = &self.environment;
+        let suite_name = format!("{} {}", env!("CARGO_PKG_NAME"), self.subcommand);
+        let class_name = format!("{}.{}", env!("CARGO_PKG_NAME"), self.subcommand);
+        let elapsed = elapsed.as_secs_f64();
+        let tests = self.entries.len();
+        let failures = self.failure_count();
+
+        let mut content = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        content.push_str(&format!(
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{:.3}\">\n",
+            xml_escape(env!("CARGO_PKG_NAME")),
+            tests,
+            failures,
+            elapsed
+        ));
+        content.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\" time=\"{:.3}\" timestamp=\"{}\" hostname=\"{}\">\n",
+            xml_escape(&suite_name),
+            tests,
+            failures,
+            elapsed,
+            self.started.format("%Y-%m-%dT%H:%M:%S"),
+            xml_escape(environment.host.as_deref().unwrap_or("localhost"))
+        ));
+
+        content.push_str("    <properties>\n");
+        let properties = [
+            ("version", Some(environment.version.clone())),
+            ("command", Some(environment.command.clone())),
+            ("os", Some(environment.os.clone())),
+            ("user", environment.user.clone()),
+            ("working_dir", environment.working_dir.clone()),
+            ("started", Some(self.started.to_rfc3339())),
+            (
+                "algorithm",
+                Some(
+                    self.digest_algorithms
+                        .iter()
+                        .map(|digest_algorithm| digest_algorithm.bsd_tag())
+                        .collect::<Vec<&str>>()
+                        .join(", "),
+                ),
+            ),
+            ("bytes", Some(self.bytes().to_string())),
+        ];
+        for (name, value) in properties.iter() {
+            if let Some(value) = value {
+                content.push_str(&format!(
+                    "      <property name=\"{}\" value=\"{}\"/>\n",
+                    name,
+                    xml_escape(value)
+                ));
+            }
+        }
+        content.push_str("    </properties>\n");
+
+        for entry in self.entries.iter() {
+            // 1ファイルを複数のアルゴリズムで検証した場合も、テストケースの名前が重ならないようにする
+            let name = match entry.digest_algorithm {
+                Some(digest_algorithm) => {
+                    format!("{} ({})", entry.path, digest_algorithm.bsd_tag())
+                }
+                None => entry.path.clone(),
+            };
+            let open_tag = format!(
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                xml_escape(&name),
+                xml_escape(&class_name)
+            );
+            if entry.success {
+                content.push_str(&format!("{}/>\n", open_tag));
+            } else {
+                content.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                    open_tag,
+                    xml_escape(entry.status),
+                    xml_escape(&entry.details.join("\n"))
+                ));
+            }
+        }
+        content.push_str("  </testsuite>\n</testsuites>\n");
+        content
+    }
+
+    fn render_markdown(
+        &self,
+        totals: &[(String, String)],
+        failures: &[&ReportEntry],
+        results: &[&ReportEntry],
+    ) -> String {
+        let mut content = format!("# {}\n\n", t!("report.title"));
+
+        content.push_str(&format!("## {}\n\n", t!("report.environment")));
+        content.push_str("| | |\n| --- | --- |\n");
+        for (name, value) in self.environment_rows() {
+            content.push_str(&format!(
+                "| {} | {} |\n",
+                markdown_escape(&name),
+                markdown_escape(&value)
+            ));
+        }
+
+        content.push_str(&format!("\n## {}\n\n", t!("report.totals")));
+        content.push_str("| | |\n| --- | --- |\n");
+        for (name, value) in totals {
+            content.push_str(&format!(
+                "| {} | {} |\n",
+                markdown_escape(name),
+                markdown_escape(value)
+            ));
+        }
+
+        content.push_str(&format!("\n## {}\n\n", t!("report.failures")));
+        if failures.is_empty() {
+            content.push_str(&format!("{}\n", t!("report.no_failures")));
+        } else {
+            content.push_str(&markdown_table(failures));
+        }
+
+        content.push_str(&format!("\n## {}\n\n", t!("report.results")));
+        content.push_str(&markdown_table(results));
+        content
+    }
+
+    fn render_html(
+        &self,
+        totals: &[(String, String)],
+        failures: &[&ReportEntry],
+        results: &[&ReportEntry],
+    ) -> String {
+        let mut content = String::from("<!DOCTYPE html>\n");
+        content.push_str(&format!(
+            "<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n",
+            crate::i18n::current().tag(),
+            html_escape(&t!("report.title"))
+        ));
+        content.push_str(HTML_STYLE);
+        content.push_str("</head>\n<body>\n");
+        content.push_str(&format!("<h1>{}</h1>\n", html_escape(&t!("report.title"))));
+
+        content.push_str(&format!(
+            "<h2>{}</h2>\n",
+            html_escape(&t!("report.environment"))
+        ));
+        content.push_str(&html_key_value_table(&self.environment_rows()));
+
+        content.push_str(&format!("<h2>{}</h2>\n", html_escape(&t!("report.totals"))));
+        content.push_str(&html_key_value_table(totals));
+
+        content.push_str(&format!(
+            "<h2>{}</h2>\n",
+            html_escape(&t!("report.failures"))
+        ));
+        if failures.is_empty() {
+            content.push_str(&format!(
+                "<p class=\"success\">{}</p>\n",
+                html_escape(&t!("report.no_failures"))
+            ));
+        } else {
+            content.push_str(&html_table(failures));
+        }
+
+        content.push_str(&format!(
+            "<h2>{}</h2>\n",
+            html_escape(&t!("report.results"))
+        ));
+        content.push_str(&html_table(results));
+        content.push_str("</body>\n</html>\n");
+        content
+    }
+}
+
+/// 外部のファイルを読まずに表示できるように、CSSはHTMLに含める
+const HTML_STYLE: &str = "<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; }
+td.path, td.details { font-family: monospace; word-break: break-all; }
+tr.failure td { background: #fde2e2; }
+tr.failure td.status { color: #b00020; font-weight: bold; }
+p.success { color: #1b5e20; }
+</style>
+";
+
+/// 結果の表の見出し
+fn headers() -> [String; 4] {
+    [
+        t!("report.path"),
+        t!("report.status"),
+        t!("report.size"),
+        t!("report.details"),
+    ]
+}
+
+fn size_text(entry: &ReportEntry) -> String {
+    entry
+        .size
+        .map(|size| size.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// 表の中で表の区切りや書式として読まれないようにする
+fn markdown_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '|' | '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\r' | '\n' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn markdown_table(entries: &[&ReportEntry]) -> String {
+    let mut content = format!("| {} |\n", headers().join(" | "));
+    content.push_str("| --- | --- | ---: | --- |\n");
+    for entry in entries {
+        let status = if entry.success {
+            entry.status.to_string()
+        } else {
+            format!("**{}**", entry.status)
+        };
+        content.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            markdown_escape(&entry.path),
+            status,
+            size_text(entry),
+            entry
+                .details
+                .iter()
+                .map(|detail| markdown_escape(detail))
+                .collect::<Vec<String>>()
+                .join("<br>")
+        ));
+    }
+    content
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn html_key_value_table(rows: &[(String, String)]) -> String {
+    let mut content = String::from("<table>\n");
+    for (name, value) in rows {
+        content.push_str(&format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            html_escape(name),
+            html_escape(value)
+        ));
+    }
+    content.push_str("</table>\n");
+    content
+}
+
+/// # XMLの文字列にする関数
+/// XML 1.0で書けない制御文字は、U+FFFDに置き換えます。
+fn xml_escape(text: &str) -> String {
+    let text: String = text
+        .chars()
+        .map(|c| match c {
+            '\t' | '\n' | '\r' => c,
+            c if c < ' ' => char::REPLACEMENT_CHARACTER,
+            c => c,
+        })
+        .collect();
+    html_escape(&text)
+}
+
+fn html_table(entries: &[&ReportEntry]) -> String {
+    let mut content = String::from("<table>\n<tr>");
+    for header in headers().iter() {
+        content.push_str(&format!("<th>{}</th>", html_escape(header)));
+    }
+    content.push_str("</tr>\n");
+    for entry in entries {
+        content.push_str(if entry.success {
+            "<tr>"
+        } else {
+            "<tr class=\"failure\">"
+        });
+        content.push_str(&format!(
+            "<td class=\"path\">{}</td><td class=\"status\">{}</td><td>{}</td><td class=\"details\">{}</td></tr>\n",
+            html_escape(&entry.path),
+            html_escape(entry.status),
+            size_text(entry),
+            entry
+                .details
+                .iter()
+                .map(|detail| html_escape(detail))
+                .collect::<Vec<String>>()
+                .join("<br>")
+        ));
+    }
+    content.push_str("</table>\n");
+    content
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn report() -> Report {
+        let mut report = Report::new("check");
+        report.add_algorithm(DigestAlgorithm::Sha2_256);
+        report.add_algorithm(DigestAlgorithm::Sha2_256);
+        report.add(ReportEntry {
+            path: "a|b.txt".to_string(),
+            digest_algorithm: Some(DigestAlgorithm::Sha2_256),
+            status: "OK",
+            success: true,
+            size: Some(1_000_000),
+            details: Vec::new(),
+        });
+        report.add(ReportEntry {
+            path: "<c>.txt".to_string(),
+            digest_algorithm: Some(DigestAlgorithm::Sha2_256),
+            status: "FAILED",
+            success: false,
+            size: Some(1_000_000),
+            details: vec!["expected & actual".to_string()],
+        });
+        report.add(ReportEntry {
+            path: "d.txt".to_string(),
+            digest_algorithm: None,
+            status: "MISSING",
+            success: false,
+            size: None,
+            details: Vec::new(),
+        });
+        report
+    }
+
+    /// 拡張子から形式を決める
+    #[test]
+    fn ok_from_path() {
+        for (path, format) in [
+            ("report.html", Some(ReportFormat::Html)),
+            ("REPORT.HTM", Some(ReportFormat::Html)),
+            ("dir/report.md", Some(ReportFormat::Markdown)),
+            ("report.markdown", Some(ReportFormat::Markdown)),
+            ("junit.xml", Some(ReportFormat::Junit)),
+            ("report.txt", None),
+            ("report", None),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                *format,
+                ReportFormat::from_path(std::path::Path::new(path)),
+                "{}",
+                path
+            );
+        }
+    }
+
+    /// 件数、バイト数、処理速度を合計する
+    #[test]
+    fn ok_totals() {
+        let report = report();
+        assert_eq!(vec![DigestAlgorithm::Sha2_256], report.digest_algorithms);
+        let totals = report.totals(std::time::Duration::from_secs(2));
+        let value = |name: &str| {
+            totals
+                .iter()
+                .find(|(row_name, _)| row_name == name)
+                .map(|(_, value)| value.clone())
+        };
+        assert_eq!(Some("3".to_string()), value(&t!("report.results_count")));
+        assert_eq!(Some("2".to_string()), value(&t!("report.failures_count")));
+        assert_eq!(Some("1".to_string()), value("FAILED"));
+        assert_eq!(Some("2000000".to_string()), value(&t!("report.bytes")));
+        assert_eq!(
+            Some("1.00 MB/s".to_string()),
+            value(&t!("report.throughput"))
+        );
+    }
+
+    /// パスなどを書式として読まれないように書き出す
+    #[test]
+    fn ok_render() {
+        let report = report();
+        let html = report.render(ReportFormat::Html, std::time::Duration::from_secs(1));
+        assert!(html.contains("&lt;c&gt;.txt"));
+        assert!(html.contains("expected &amp; actual"));
+        assert!(html.contains("<tr class=\"failure\"><td class=\"path\">&lt;c&gt;.txt"));
+        assert!(!html.contains("<c>"));
+
+        let markdown = report.render(ReportFormat::Markdown, std::time::Duration::from_secs(1));
+        assert!(markdown.contains("| a\\|b.txt | OK | 1000000 |  |"));
+        assert!(markdown.contains("| \\<c\\>.txt | **FAILED** | 1000000 | expected & actual |"));
+    }
+
+    /// 1件の結果を1件のテストケースにし、一致しなかった結果を失敗にする
+    #[test]
+    fn ok_render_junit() {
+        let mut report = report();
+        report.add(ReportEntry {
+            path: "e\u{1}.txt".to_string(),
+            digest_algorithm: None,
+            status: "OK",
+            success: true,
+            size: None,
+            details: Vec::new(),
+        });
+        let junit = report.render(ReportFormat::Junit, std::time::Duration::from_secs(1));
+        assert!(junit.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(junit.contains("<testsuites name=\"digest_tool\" tests=\"4\" failures=\"2\""));
+        assert!(junit.contains("<testsuite name=\"digest_tool check\" tests=\"4\" failures=\"2\""));
+        assert!(junit.contains("<property name=\"algorithm\" value=\"SHA256\"/>"));
+        assert!(
+            junit.contains("<testcase name=\"a|b.txt (SHA256)\" classname=\"digest_tool.check\"/>")
+        );
+        assert!(junit.contains(
+            "<testcase name=\"&lt;c&gt;.txt (SHA256)\" classname=\"digest_tool.check\">\n      <failure message=\"FAILED\">expected &amp; actual</failure>\n    </testcase>"
+        ));
+        assert!(junit.contains("<testcase name=\"d.txt\" classname=\"digest_tool.check\">"));
+        assert!(junit.contains("<testcase name=\"e\u{fffd}.txt\""));
+        assert!(junit.ends_with("</testsuites>\n"));
+    }
+}
@@ -0,0 +1,250 @@
+//! # サイドカーモジュール
+//! `foo.iso`の隣にある`foo.iso.sha256`や`SHA256SUMS`のようなチェックサムファイルを探し、
+//! ファイルごとに、検証に使える記載(根拠)を集めます。
+//!
+//! `foo.iso.sha256`のようなファイルには、ファイル名がなくハッシュ値だけが書かれていることがあります。
+//! その場合は、拡張子を除いたファイル名(`foo.iso`)の記載として扱います。
+
+use crate::checksum_file::{self, ChecksumEntry};
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use crate::multi_file;
+use crate::text_normalize::TextNormalization;
+use std::path::{Path, PathBuf};
+
+/// サイドカーとして扱う拡張子
+const SIDECAR_EXTENSIONS: [&str; 14] = [
+    "sha256",
+    "sha512",
+    "sha1",
+    "md5",
+    "sha3-256",
+    "sha3-512",
+    "sha256sum",
+    "sha512sum",
+    "sha1sum",
+    "md5sum",
+    "sfv",
+    "hashdeep",
+    "digest",
+    "checksum",
+];
+
+/// チェックサムファイルとして扱う名前 大文字と小文字は区別せず、`.txt`が付いていてもかまいません
+const SUMS_FILE_NAMES: [&str; 7] = [
+    "sha256sums",
+    "sha512sums",
+    "sha1sums",
+    "md5sums",
+    "sha3-256sums",
+    "sha3-512sums",
+    "checksums",
+];
+
+/// # 根拠
+/// 1ファイル分の検証に使うチェックサムファイルの記載
+#[derive(Debug, Clone)]
+pub struct Evidence {
+    /// 記載のあったチェックサムファイル
+    pub checksum_file_path: PathBuf,
+    /// 記載 パスはbase_dirからの相対パス
+    pub entry: ChecksumEntry,
+    /// チェックサムファイルのあるディレクトリ
+    pub base_dir: PathBuf,
+    /// チェックサムファイルで指定されたテキスト正規化
+    pub text_normalization: Option<TextNormalization>,
+}
+
+impl Evidence {
+    /// 記載されたファイルのパス
+    pub fn target_path(&self) -> PathBuf {
+        self.base_dir.join(&self.entry.path)
+    }
+}
+
+/// チェックサムファイルらしい名前か
+pub fn is_checksum_file(path: &Path) -> bool {
+    let file_name = match path.file_name() {
+        Some(file_name) => file_name.to_string_lossy().to_ascii_lowercase(),
+        None => return false,
+    };
+    let extension = file_name.rsplit('.').next().unwrap_or_default();
+    // SHA256SUMS、MD5SUMS、CHECKSUMS、SHA256SUMS.txtなど
+    let stem = file_name.strip_suffix(".txt").unwrap_or(&file_name);
+    (file_name.contains('.') && SIDECAR_EXTENSIONS.contains(&extension))
+        || SUMS_FILE_NAMES.contains(&stem)
+}
+
+/// # チェックサムファイルの読み込み関数
+/// チェックサムファイルのすべての記載を根拠にします。
+/// ハッシュ値だけが書かれたサイドカーは、拡張子を除いたファイル名の記載にします。
+pub fn read_evidence(checksum_file_path: &Path) -> Result<Vec<Evidence>, std::io::Error> {
+    let content = std::fs::read_to_string(checksum_file_path)?;
+    let digest_algorithm = checksum_file::digest_algorithm_from_file_name(checksum_file_path);
+    let mut checksum_file = checksum_file::parse(&content, digest_algorithm);
+    if checksum_file.entries.is_empty() {
+        if let Some(entry) = parse_hash_only(checksum_file_path, &content, digest_algorithm) {
+            checksum_file.entries.push(entry);
+        }
+    }
+
+    let base_dir = checksum_file_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+    let text_normalization = checksum_file.text_normalization;
+    Ok(checksum_file
+        .entries
+        .into_iter()
+        .map(|entry| Evidence {
+            checksum_file_path: checksum_file_path.to_path_buf(),
+            entry,
+            base_dir: base_dir.clone(),
+            text_normalization,
+        })
+        .collect())
+}
+
+/// 名前だけがチェックサムファイルらしい、読めないファイルや文字列でないファイルは、警告して飛ばします。
+fn read_evidence_or_warn(checksum_file_path: &Path) -> Vec<Evidence> {
+    read_evidence(checksum_file_path).unwrap_or_else(|e| {
+        eprintln!(
+            "{}",
+            t!(
+                "sidecar.skipped",
+                path = checksum_file_path.display(),
+                error = e
+            )
+        );
+        Vec::new()
+    })
+}
+
+/// ハッシュ値だけが書かれたサイドカーを読み込みます。
+fn parse_hash_only(
+    checksum_file_path: &Path,
+    content: &str,
+    digest_algorithm: Option<DigestAlgorithm>,
+) -> Option<ChecksumEntry> {
+    let hash_value = hash::from_hex(content.trim())?;
+    let digest_algorithm =
+        digest_algorithm.or_else(|| DigestAlgorithm::from_digest_length(hash_value.len()))?;
+    if hash_value.len() != digest_algorithm.digest_length() {
+        return None;
+    }
+    let path = checksum_file_path
+        .file_stem()?
+        .to_string_lossy()
+        .into_owned();
+    Some(ChecksumEntry {
+        path,
+        size: None,
+        digests: vec![(digest_algorithm, hash_value)],
+    })
+}
+
+/// # ファイルの根拠を探す関数
+/// ファイルと同じディレクトリのチェックサムファイルから、そのファイルの記載を集めます。
+pub fn find_for_file(file: &Path) -> Result<Vec<Evidence>, std::io::Error> {
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let target = multi_file::absolute_path(file)?;
+    let mut checksum_file_paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && is_checksum_file(path))
+        .collect();
+    checksum_file_paths.sort();
+
+    let mut evidences = Vec::new();
+    for checksum_file_path in checksum_file_paths {
+        for evidence in read_evidence_or_warn(&checksum_file_path) {
+            if multi_file::absolute_path(&evidence.target_path())? == target {
+                evidences.push(evidence);
+            }
+        }
+    }
+    Ok(evidences)
+}
+
+/// # ディレクトリの根拠を探す関数
+/// ディレクトリの下のすべてのチェックサムファイルの、すべての記載を集めます。
+pub fn find_in_dir(dir: &Path) -> Result<Vec<Evidence>, std::io::Error> {
+    let mut evidences = Vec::new();
+    for path in multi_file::collect_files(&[dir.to_path_buf()], &[])? {
+        if is_checksum_file(&path) {
+            evidences.extend(read_evidence_or_warn(&path));
+        }
+    }
+    Ok(evidences)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// チェックサムファイルの名前を見分けられる
+    #[test]
+    fn ok_is_checksum_file() {
+        for name in [
+            "foo.iso.sha256",
+            "SHA256SUMS",
+            "MD5SUMS.txt",
+            "foo.md5",
+            "a.sfv",
+            "foo.iso.sha256sum",
+            "CHECKSUMS",
+        ]
+        .iter()
+        {
+            assert!(is_checksum_file(Path::new(name)), "{}", name);
+        }
+        for name in ["foo.iso", "sha256", "README.md", "albums", "my_albums.txt"].iter() {
+            assert!(!is_checksum_file(Path::new(name)), "{}", name);
+        }
+    }
+
+    /// サイドカーとマニフェストの両方から根拠を集める
+    #[test]
+    fn ok_find_for_file() {
+        let dir = std::env::temp_dir().join(format!("digest_tool_sidecar_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("foo.iso"), b"abc").unwrap();
+        let sha256 = hash::to_hex(&hash::digest_bytes(b"abc", DigestAlgorithm::Sha2_256));
+        let md5 = hash::to_hex(&hash::digest_bytes(b"abc", DigestAlgorithm::Md5));
+        std::fs::write(dir.join("foo.iso.sha256"), format!("{}\n", sha256)).unwrap();
+        std::fs::write(
+            dir.join("MD5SUMS"),
+            format!("{}  foo.iso\n{}  other.iso\n", md5, md5),
+        )
+        .unwrap();
+        // 文字列として読めないファイルは飛ばして、ほかのファイルから集め続ける
+        std::fs::write(dir.join("SHA1SUMS"), b"\xff\xfe\x00garbage").unwrap();
+
+        let evidences = find_for_file(&dir.join("foo.iso")).unwrap();
+        let found: Vec<(String, DigestAlgorithm)> = evidences
+            .iter()
+            .map(|evidence| {
+                (
+                    evidence
+                        .checksum_file_path
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned(),
+                    evidence.entry.digests[0].0,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("MD5SUMS".to_string(), DigestAlgorithm::Md5),
+                ("foo.iso.sha256".to_string(), DigestAlgorithm::Sha2_256),
+            ],
+            found
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+//! # teeモジュール
+//! 標準入力のデータをそのまま出力先に書き出しながら、ハッシュ値を計算します。
+//! データを二度読み込まずに済むので、ダウンロードやバックアップのパイプラインの途中に挟んで使えます。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use std::io::Write;
+
+/// # 読み込んだデータを書き出すリーダー
+/// readerから読み込んだデータを、そのままwriterにも書き出します。
+pub struct TeeReader<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: std::io::Read, W: std::io::Write> TeeReader<R, W> {
+    pub fn new(reader: R, writer: W) -> TeeReader<R, W> {
+        TeeReader { reader, writer }
+    }
+
+    /// writerを取り出します。
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<R: std::io::Read, W: std::io::Write> std::io::Read for TeeReader<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read_size = self.reader.read(buf)?;
+        self.writer.write_all(&buf[..read_size])?;
+        Ok(read_size)
+    }
+}
+
+/// # tee関数
+/// 標準入力を出力先に書き出しながらハッシュ値を計算し、ハッシュ値をBSD形式(`SHA256 (file) = hash`)で書き出します。
+/// ## 引数
+/// - データの出力先ファイルパス 省略すると標準出力
+/// - ハッシュ値の出力先ファイルパス 省略すると標準エラー出力
+/// - ハッシュアルゴリズムの配列
+pub fn tee(
+    output_file_path: Option<&str>,
+    digest_file_path: Option<&str>,
+    digest_algorithms: &[DigestAlgorithm],
+) -> Result<(), std::io::Error> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let output: Box<dyn std::io::Write> = match output_file_path {
+        Some(path) => Box::new(std::fs::File::create(path)?),
+        None => Box::new(stdout.lock()),
+    };
+    let output = std::io::BufWriter::new(output);
+
+    // 標準出力はデータに使うのでプログレスバーは表示しない
+    let mut tee_reader = TeeReader::new(stdin.lock(), output);
+    let (_, hash_values) = hash::multi_digest(
+        &mut tee_reader,
+        digest_algorithms,
+        indicatif::ProgressBar::hidden(),
+    )?;
+    tee_reader.into_writer().flush()?;
+
+    let mut digest_output: Box<dyn std::io::Write> = match digest_file_path {
+        Some(path) => Box::new(std::fs::File::create(path)?),
+        None => Box::new(std::io::stderr()),
+    };
+    let name = output_file_path.unwrap_or("-");
+    for (digest_algorithm, hash_value) in hash_values {
+        writeln!(
+            digest_output,
+            "{} ({}) = {}",
+            digest_algorithm.bsd_tag(),
+            name,
+            hash::to_hex(&hash_value)
+        )?;
+    }
+    digest_output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 読み込んだデータがそのまま書き出される
+    #[test]
+    fn ok_tee_reader() {
+        let input: &[u8] = b"digest tool";
+        let mut tee_reader = TeeReader::new(input, Vec::new());
+        let (data_size, hash_values) = hash::multi_digest(
+            &mut tee_reader,
+            &[DigestAlgorithm::Sha2_256],
+            indicatif::ProgressBar::hidden(),
+        )
+        .unwrap();
+
+        assert_eq!(11, data_size);
+        assert_eq!(b"digest tool".to_vec(), tee_reader.into_writer());
+        assert_eq!(32, hash_values[0].1.len());
+    }
+}
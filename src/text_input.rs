@@ -0,0 +1,145 @@
+//! # 文字列入力モジュール
+//! CLI引数で受け取った文字列を、指定したエンコーディングのバイト列に変換します。
+//! シェルによって改行や文字コードが変わらないように、バイト列の作り方を明示的に指定できます。
+
+/// 文字列をバイト列にするときのエンコーディング
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    ShiftJis,
+}
+
+impl TextEncoding {
+    /// CLI引数で指定する名前からエンコーディングを取得します。
+    pub fn from_name(name: &str) -> Option<TextEncoding> {
+        match name {
+            "utf8" => Some(TextEncoding::Utf8),
+            "utf16le" => Some(TextEncoding::Utf16Le),
+            "utf16be" => Some(TextEncoding::Utf16Be),
+            "shift_jis" => Some(TextEncoding::ShiftJis),
+            _ => None,
+        }
+    }
+
+    /// CLI引数で指定する名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf8",
+            TextEncoding::Utf16Le => "utf16le",
+            TextEncoding::Utf16Be => "utf16be",
+            TextEncoding::ShiftJis => "shift_jis",
+        }
+    }
+
+    /// 表示用の名前
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::ShiftJis => "Shift_JIS",
+        }
+    }
+}
+
+/// 末尾に付け足す改行
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Newline {
+    Lf,
+    CrLf,
+}
+
+impl Newline {
+    /// CLI引数で指定する名前から改行を取得します。
+    pub fn from_name(name: &str) -> Option<Newline> {
+        match name {
+            "lf" => Some(Newline::Lf),
+            "crlf" => Some(Newline::CrLf),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// # 文字列をバイト列に変換する関数
+/// ## 引数
+/// - 文字列
+/// - エンコーディング
+/// - 末尾に付け足す改行 Noneなら付け足さない
+/// ## 返り値
+/// Result型 std::ioのError(エンコーディングで表せない文字があった場合) あるいは、バイト列
+pub fn encode_text(
+    text: &str,
+    encoding: TextEncoding,
+    newline: Option<Newline>,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut text = text.to_string();
+    if let Some(newline) = newline {
+        text.push_str(newline.as_str());
+    }
+
+    let bytes = match encoding {
+        TextEncoding::Utf8 => text.into_bytes(),
+        TextEncoding::Utf16Le => text.encode_utf16().flat_map(|c| c.to_le_bytes()).collect(),
+        TextEncoding::Utf16Be => text.encode_utf16().flat_map(|c| c.to_be_bytes()).collect(),
+        TextEncoding::ShiftJis => {
+            let (bytes, _, had_errors) = encoding_rs::SHIFT_JIS.encode(&text);
+            if had_errors {
+                // 置き換え文字でごまかさず、表せない文字を知らせる
+                let unmappable = text
+                    .chars()
+                    .find(|c| encoding_rs::SHIFT_JIS.encode(&c.to_string()).2)
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    t!(
+                        "text.unmappable_shift_jis",
+                        char = format!("{:?}", unmappable)
+                    ),
+                ));
+            }
+            bytes.into_owned()
+        }
+    };
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 成功パターン
+    #[test]
+    fn ok_encode_text() {
+        assert_eq!(
+            b"abc\r\n".to_vec(),
+            encode_text("abc", TextEncoding::Utf8, Some(Newline::CrLf)).unwrap()
+        );
+        assert_eq!(
+            vec![0x42, 0x30, 0x0a, 0x00],
+            encode_text("あ", TextEncoding::Utf16Le, Some(Newline::Lf)).unwrap()
+        );
+        assert_eq!(
+            vec![0x30, 0x42],
+            encode_text("あ", TextEncoding::Utf16Be, None).unwrap()
+        );
+        assert_eq!(
+            vec![0x82, 0xa0],
+            encode_text("あ", TextEncoding::ShiftJis, None).unwrap()
+        );
+    }
+
+    /// 失敗パターン
+    #[test]
+    fn ng_encode_text() {
+        assert!(encode_text("😀", TextEncoding::ShiftJis, None).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! # テキスト正規化モジュール
+//! 同じテキストファイルでも、改行コード(CRLF/LF)、BOMの有無、Unicodeの正規化形式(NFC/NFD)の違いで
+//! ハッシュ値が変わってしまいます。ハッシュ値を計算する前にこれらをそろえます。
+//!
+//! 正規化の指定は`eol=lf,bom=strip,unicode=nfc`のような文字列で表します。
+//! 結果の出力にも同じ文字列を表示するので、検証するときはその文字列をそのまま`--normalize`に渡してください。
+
+use crate::text_input::Newline;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::{
+    is_nfc_quick, is_nfd_quick, is_nfkc_quick, is_nfkd_quick, IsNormalized, UnicodeNormalization,
+};
+
+/// Unicodeの正規化形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnicodeForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+/// # テキスト正規化の指定
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextNormalization {
+    /// 改行をそろえる 改行はCRLFとLFだけを扱い、単独のCRはそのまま残します
+    pub eol: Option<Newline>,
+    /// 先頭のBOMを取り除く
+    pub strip_bom: bool,
+    /// Unicodeの正規化形式をそろえる
+    pub unicode: Option<UnicodeForm>,
+}
+
+impl std::str::FromStr for TextNormalization {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<TextNormalization, String> {
+        let mut normalization = TextNormalization::default();
+        for item in spec.split(',').map(|item| item.trim()) {
+            let (key, value) = match item.find('=') {
+                Some(index) => (&item[..index], &item[index + 1..]),
+                None => return Err(t!("normalize.not_key_value", item = item)),
+            };
+            match (key, value) {
+                ("eol", "lf") => normalization.eol = Some(Newline::Lf),
+                ("eol", "crlf") => normalization.eol = Some(Newline::CrLf),
+                ("bom", "strip") => normalization.strip_bom = true,
+                ("bom", "keep") => normalization.strip_bom = false,
+                ("unicode", "nfc") => normalization.unicode = Some(UnicodeForm::Nfc),
+                ("unicode", "nfd") => normalization.unicode = Some(UnicodeForm::Nfd),
+                ("unicode", "nfkc") => normalization.unicode = Some(UnicodeForm::Nfkc),
+                ("unicode", "nfkd") => normalization.unicode = Some(UnicodeForm::Nfkd),
+                _ => return Err(t!("normalize.unknown", item = item)),
+            }
+        }
+        Ok(normalization)
+    }
+}
+
+impl std::fmt::Display for TextNormalization {
+    /// 指定された項目だけを、決まった順番で表示します。
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut items = Vec::new();
+        match self.eol {
+            Some(Newline::Lf) => items.push("eol=lf"),
+            Some(Newline::CrLf) => items.push("eol=crlf"),
+            None => {}
+        }
+        if self.strip_bom {
+            items.push("bom=strip");
+        }
+        match self.unicode {
+            Some(UnicodeForm::Nfc) => items.push("unicode=nfc"),
+            Some(UnicodeForm::Nfd) => items.push("unicode=nfd"),
+            Some(UnicodeForm::Nfkc) => items.push("unicode=nfkc"),
+            Some(UnicodeForm::Nfkd) => items.push("unicode=nfkd"),
+            None => {}
+        }
+        write!(f, "{}", items.join(","))
+    }
+}
+
+impl TextNormalization {
+    /// 何も変えない指定か `bom=keep`だけの場合など
+    pub fn is_noop(&self) -> bool {
+        self.eol.is_none() && !self.strip_bom && self.unicode.is_none()
+    }
+}
+
+/// 一度に読み込むバイト数
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Unicodeの正規化を区切れる文字が見つからないまま溜めておけるバイト数
+/// 結合文字がこれほど続くのはテキストではないので、エラーにします
+const MAX_UNNORMALIZED: usize = 1024 * 1024;
+
+/// # テキスト正規化リーダー
+/// 読み込んだバイト列をUTF-8のテキストとして正規化しながら返すリーダーです。
+/// 全体を読み込まずにチャンクごとに処理するので、大きなファイルでもメモリーを使いすぎません。
+/// BOMの除去、Unicodeの正規化、改行の変換の順に行います。
+///
+/// Unicodeの正規化は、前の文字と結びつかない文字(結合クラスが0で、クイックチェックがYes)の手前で区切ります。
+/// 区切った前後を別々に正規化しても、全体を正規化した結果と同じになります。
+/// UTF-8でない場合は、`InvalidData`のエラーを返します。
+pub struct NormalizeReader<R> {
+    inner: R,
+    normalization: TextNormalization,
+    buffer: Vec<u8>,
+    /// 文字の途中で切れていて、まだUTF-8として読めていないバイト列
+    undecoded: Vec<u8>,
+    /// UTF-8として読めたが、まだ正規化していないテキスト
+    unnormalized: String,
+    /// 先頭のBOMを確かめたか
+    started: bool,
+    /// 前のチャンクの末尾のCR 次のチャンクがLFで始まれば、CRLFとして扱う
+    pending_cr: bool,
+    /// 最後まで読み込んで正規化したか
+    finished: bool,
+    output: Vec<u8>,
+    position: usize,
+}
+
+impl<R: std::io::Read> NormalizeReader<R> {
+    pub fn new(inner: R, normalization: TextNormalization) -> NormalizeReader<R> {
+        NormalizeReader::with_chunk_size(inner, normalization, CHUNK_SIZE)
+    }
+
+    fn with_chunk_size(
+        inner: R,
+        normalization: TextNormalization,
+        chunk_size: usize,
+    ) -> NormalizeReader<R> {
+        NormalizeReader {
+            inner,
+            normalization,
+            buffer: vec![0; chunk_size],
+            undecoded: Vec::new(),
+            unnormalized: String::new(),
+            started: false,
+            pending_cr: false,
+            finished: false,
+            output: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// 次に返すバイト列を作る 最後まで返し終わったら空のまま
+    fn fill(&mut self) -> Result<(), std::io::Error> {
+        self.output.clear();
+        self.position = 0;
+        while self.output.is_empty() && !self.finished {
+            let read = loop {
+                match self.inner.read(&mut self.buffer) {
+                    Ok(read) => break read,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            };
+            let eof = read == 0;
+            self.undecoded.extend_from_slice(&self.buffer[..read]);
+
+            // 文字の途中で切れている分は、次のチャンクと合わせて読む
+            let valid = match std::str::from_utf8(&self.undecoded) {
+                Ok(text) => text.len(),
+                Err(e) if e.error_len().is_none() && !eof => e.valid_up_to(),
+                Err(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        t!("normalize.not_utf8"),
+                    ))
+                }
+            };
+            let rest = self.undecoded.split_off(valid);
+            let decoded = std::mem::replace(&mut self.undecoded, rest);
+            self.unnormalized
+                .push_str(&String::from_utf8(decoded).unwrap_or_default());
+
+            if !self.started && (!self.unnormalized.is_empty() || eof) {
+                self.started = true;
+                if self.normalization.strip_bom && self.unnormalized.starts_with('\u{feff}') {
+                    self.unnormalized.drain(..'\u{feff}'.len_utf8());
+                }
+            }
+
+            let end = match (eof, self.normalization.unicode) {
+                (false, Some(form)) => last_boundary(&self.unnormalized, form),
+                _ => self.unnormalized.len(),
+            };
+            if end == 0 && self.unnormalized.len() > MAX_UNNORMALIZED {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    t!("normalize.too_many_combining"),
+                ));
+            }
+            let rest = self.unnormalized.split_off(end);
+            let text = std::mem::replace(&mut self.unnormalized, rest);
+            let mut text: String = match self.normalization.unicode {
+                Some(UnicodeForm::Nfc) => text.nfc().collect(),
+                Some(UnicodeForm::Nfd) => text.nfd().collect(),
+                Some(UnicodeForm::Nfkc) => text.nfkc().collect(),
+                Some(UnicodeForm::Nfkd) => text.nfkd().collect(),
+                None => text,
+            };
+
+            if let Some(eol) = self.normalization.eol {
+                if self.pending_cr {
+                    text.insert(0, '\r');
+                    self.pending_cr = false;
+                }
+                if !eof && text.ends_with('\r') {
+                    text.pop();
+                    self.pending_cr = true;
+                }
+                text = match eol {
+                    Newline::Lf => text.replace("\r\n", "\n"),
+                    Newline::CrLf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+                };
+            }
+            self.output = text.into_bytes();
+            self.finished = eof;
+        }
+        Ok(())
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for NormalizeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        if self.position >= self.output.len() {
+            self.fill()?;
+        }
+        let length = buf.len().min(self.output.len() - self.position);
+        buf[..length].copy_from_slice(&self.output[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// # 正規化を区切れる位置を探す関数
+/// 前の文字と結びつかない最後の文字の位置を返します。見つからなければ0
+fn last_boundary(text: &str, form: UnicodeForm) -> usize {
+    text.char_indices()
+        .rev()
+        .find(|(_, c)| {
+            if canonical_combining_class(*c) != 0 {
+                return false;
+            }
+            let c = std::iter::once(*c);
+            let quick_check = match form {
+                UnicodeForm::Nfc => is_nfc_quick(c),
+                UnicodeForm::Nfd => is_nfd_quick(c),
+                UnicodeForm::Nfkc => is_nfkc_quick(c),
+                UnicodeForm::Nfkd => is_nfkd_quick(c),
+            };
+            quick_check == IsNormalized::Yes
+        })
+        .map(|(index, _)| index)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    /// 指定したバイト数ずつ読み込んで正規化する
+    fn normalize(
+        input: &[u8],
+        normalization: &TextNormalization,
+        chunk_size: usize,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        let mut normalized = Vec::new();
+        NormalizeReader::with_chunk_size(input, *normalization, chunk_size)
+            .read_to_end(&mut normalized)?;
+        Ok(normalized)
+    }
+
+    /// 改行、BOM、正規化形式が違っても同じバイト列になる
+    #[test]
+    fn ok_normalize() {
+        let normalization: TextNormalization = "eol=lf,bom=strip,unicode=nfc".parse().unwrap();
+        let windows = "\u{feff}ガ\r\nb\r\n".as_bytes();
+        let mac = "\u{30ab}\u{3099}\nb\n".as_bytes();
+
+        let expected = "ガ\nb\n".as_bytes().to_vec();
+        assert_eq!(
+            expected,
+            normalize(windows, &normalization, CHUNK_SIZE).unwrap()
+        );
+        assert_eq!(
+            expected,
+            normalize(mac, &normalization, CHUNK_SIZE).unwrap()
+        );
+    }
+
+    /// 文字、CRLF、結合文字の途中でチャンクが切れても、全体を一度に正規化した結果と同じになる
+    #[test]
+    fn ok_normalize_chunks() {
+        let text =
+            "\u{feff}a\r\n\u{30ab}\u{3099}\r\n\u{1100}\u{1161}\u{11a8}e\u{301}\u{323}\rx\u{fb01}\n";
+        for spec in [
+            "eol=lf,bom=strip,unicode=nfc",
+            "eol=crlf,unicode=nfd",
+            "unicode=nfkc",
+            "eol=lf,unicode=nfkd",
+        ]
+        .iter()
+        {
+            let normalization: TextNormalization = spec.parse().unwrap();
+            let expected = normalize(text.as_bytes(), &normalization, text.len() + 1).unwrap();
+            for chunk_size in 1..8 {
+                assert_eq!(
+                    expected,
+                    normalize(text.as_bytes(), &normalization, chunk_size).unwrap(),
+                    "{} {}",
+                    spec,
+                    chunk_size
+                );
+            }
+        }
+        let normalization: TextNormalization = "eol=lf,unicode=nfc".parse().unwrap();
+        assert_eq!(
+            "\u{feff}a\n\u{30ac}\n\u{ac01}\u{1eb9}\u{301}\rx\u{fb01}\n",
+            String::from_utf8(normalize(text.as_bytes(), &normalization, 1).unwrap()).unwrap()
+        );
+    }
+
+    /// 指定の文字列を読み込んで、同じ順番で表示できる
+    #[test]
+    fn ok_spec_round_trip() {
+        let normalization: TextNormalization = "unicode=nfd, eol=crlf".parse().unwrap();
+        assert_eq!("eol=crlf,unicode=nfd", normalization.to_string());
+        assert!("eol=cr".parse::<TextNormalization>().is_err());
+        assert!("bom=keep".parse::<TextNormalization>().unwrap().is_noop());
+    }
+
+    /// UTF-8でなければエラー
+    #[test]
+    fn ng_normalize() {
+        let input: &[u8] = &[b'a', 0x82, 0xa0];
+        assert!(normalize(input, &TextNormalization::default(), 1).is_err());
+        // 途中で終わった文字も誤り
+        let input: &[u8] = &[b'a', 0xe3, 0x81];
+        assert!(normalize(input, &TextNormalization::default(), 1).is_err());
+    }
+}
@@ -0,0 +1,270 @@
+//! # ツリーハッシュモジュール
+//! ディレクトリ全体を一つのハッシュ値で表します。
+//! 走査順やファイルシステムに左右されないように、エントリを名前順に並べて正規の形に直列化してからハッシュ値を求めます。
+//! 形式の詳細は`docs/tree_digest.md`を参照してください。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use crate::multi_file;
+
+/// 直列化の先頭に付ける形式名
+const TREE_DIGEST_HEADER: &[u8] = b"DTTREE1\n";
+
+/// # ツリーハッシュに含めるメタデータ
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TreeDigestOption {
+    /// ファイルの実行ビットを含める
+    pub include_executable: bool,
+    /// シンボリックリンクのリンク先を含める
+    pub include_symlink_target: bool,
+}
+
+impl TreeDigestOption {
+    /// 直列化の先頭に書き込むフラグ
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.include_executable {
+            flags |= 0b01;
+        }
+        if self.include_symlink_target {
+            flags |= 0b10;
+        }
+        flags
+    }
+
+    /// 表示用の名前
+    pub fn display_name(&self) -> String {
+        let mut names = Vec::new();
+        if self.include_executable {
+            names.push("exec");
+        }
+        if self.include_symlink_target {
+            names.push("symlink");
+        }
+        if names.is_empty() {
+            t!("tree.metadata_none")
+        } else {
+            names.join(",")
+        }
+    }
+}
+
+/// # ツリーハッシュ関数
+/// ディレクトリを再帰的にたどり、ディレクトリ全体のハッシュ値を返却します。
+/// ## 引数
+/// - ディレクトリのパス
+/// - ハッシュアルゴリズム
+/// - 含めるメタデータ
+/// - プログレスバー 読み込んだファイルのバイト数だけ進めます
+/// ## 返り値
+/// Result型 std::ioのError あるいは、ハッシュ値
+pub fn tree_digest(
+    root: &std::path::Path,
+    digest_algorithm: DigestAlgorithm,
+    option: TreeDigestOption,
+    progress_bar: &indicatif::ProgressBar,
+) -> Result<Vec<u8>, std::io::Error> {
+    if !std::fs::metadata(root)?.is_dir() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            t!("tree.not_directory"),
+        ));
+    }
+    directory_digest(root, digest_algorithm, option, progress_bar)
+}
+
+/// ディレクトリ以下のファイルサイズの合計を返却します。シンボリックリンクはたどりません。
+pub fn tree_size(root: &std::path::Path) -> Result<u64, std::io::Error> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += tree_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// ディレクトリの直列化のハッシュ値を返却します。
+fn directory_digest(
+    directory: &std::path::Path,
+    digest_algorithm: DigestAlgorithm,
+    option: TreeDigestOption,
+    progress_bar: &indicatif::ProgressBar,
+) -> Result<Vec<u8>, std::io::Error> {
+    // 名前のバイト列順に並べる
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name().into_string().map_err(|name| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                t!("tree.non_utf8_name", name = format!("{:?}", name)),
+            )
+        })?;
+        entries.push((name, entry.path()));
+    }
+    entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+
+    let mut serialized = TREE_DIGEST_HEADER.to_vec();
+    serialized.push(option.flags());
+    for (name, path) in entries {
+        let metadata = std::fs::symlink_metadata(&path)?;
+        let file_type = metadata.file_type();
+        let (type_byte, mode_byte, hash_value) = if file_type.is_symlink() {
+            let hash_value = if option.include_symlink_target {
+                let target = std::fs::read_link(&path)?;
+                let target = target.to_str().ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        t!("tree.non_utf8_target", target = format!("{:?}", target)),
+                    )
+                })?;
+                hash::digest_bytes(target.as_bytes(), digest_algorithm)
+            } else {
+                Vec::new()
+            };
+            (b'l', b'-', hash_value)
+        } else if file_type.is_dir() {
+            let hash_value = directory_digest(&path, digest_algorithm, option, progress_bar)?;
+            (b'd', b'-', hash_value)
+        } else if file_type.is_file() {
+            let mut file_reader = std::io::BufReader::with_capacity(
+                multi_file::buffer_size(),
+                std::fs::File::open(&path)?,
+            );
+            let (file_size, hash_value) = hash::digest(
+                &mut file_reader,
+                digest_algorithm,
+                indicatif::ProgressBar::hidden(),
+            )?;
+            progress_bar.inc(file_size);
+            let mode_byte = if is_executable(&metadata) { b'x' } else { b'-' };
+            (b'f', mode_byte, hash_value)
+        } else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                t!("tree.unsupported_entry", path = path.display()),
+            ));
+        };
+
+        serialized.push(type_byte);
+        if option.include_executable {
+            serialized.push(mode_byte);
+        }
+        serialized.extend_from_slice(&(name.len() as u32).to_be_bytes());
+        serialized.extend_from_slice(name.as_bytes());
+        serialized.extend_from_slice(&(hash_value.len() as u32).to_be_bytes());
+        serialized.extend_from_slice(&hash_value);
+    }
+
+    Ok(hash::digest_bytes(&serialized, digest_algorithm))
+}
+
+/// 所有者、グループ、その他のいずれかに実行ビットが立っているか
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+/// windowsには実行ビットがないので、常に実行ビットなしとして扱う
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `docs/tree_digest.md`のテストベクタのディレクトリを作成する
+    #[cfg(unix)]
+    fn create_test_tree(name: &str) -> std::path::PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("digest_tool_{}_{}", name, std::process::id()));
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::write(root.join("a.txt"), b"hello\n").unwrap();
+        std::fs::write(root.join("bin").join("run.sh"), b"#!/bin/sh\n").unwrap();
+
+        use std::os::unix::fs::PermissionsExt;
+        let run_sh = root.join("bin").join("run.sh");
+        std::fs::set_permissions(&run_sh, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let a_txt = root.join("a.txt");
+        std::fs::set_permissions(&a_txt, std::fs::Permissions::from_mode(0o644)).unwrap();
+        std::os::unix::fs::symlink("a.txt", root.join("link")).unwrap();
+        root
+    }
+
+    /// 空のディレクトリ
+    #[test]
+    fn ok_empty_tree() {
+        let root =
+            std::env::temp_dir().join(format!("digest_tool_empty_tree_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let hash_value = tree_digest(
+            &root,
+            DigestAlgorithm::Sha2_256,
+            TreeDigestOption::default(),
+            &indicatif::ProgressBar::hidden(),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            "958443d5b784624b0ce5bfa559ef40289e61f779b2309d378bf2553f5127433a",
+            hash::to_hex(&hash_value)
+        );
+    }
+
+    /// テストベクタ
+    #[cfg(unix)]
+    #[test]
+    fn ok_tree_digest_vectors() {
+        let root = create_test_tree("tree_vectors");
+        let cases = [
+            (
+                false,
+                false,
+                "2493dd9bceca88a67894b163fdbe1c18b414b04c3e94f7e01c14ced7226b7d02",
+            ),
+            (
+                true,
+                false,
+                "4a27fbe531531081222364e1020e8e52142979bbdb3b0152a7ab43b8d6f73e3a",
+            ),
+            (
+                false,
+                true,
+                "bf148d4ddcafe920020edaa47739a2c8e7b3ed5956d065a9d3fefede34befe93",
+            ),
+            (
+                true,
+                true,
+                "4c85c7a1ce25d5253e1cc0efda916922849ed0b11ad766d72ff5ed35d4803a07",
+            ),
+        ];
+        for (include_executable, include_symlink_target, expected) in cases.iter() {
+            let option = TreeDigestOption {
+                include_executable: *include_executable,
+                include_symlink_target: *include_symlink_target,
+            };
+            let hash_value = tree_digest(
+                &root,
+                DigestAlgorithm::Sha2_256,
+                option,
+                &indicatif::ProgressBar::hidden(),
+            )
+            .unwrap();
+            assert_eq!(*expected, hash::to_hex(&hash_value), "{:?}", option);
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
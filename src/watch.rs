@@ -0,0 +1,528 @@
+//! # 監視モジュール
+//! ファイルやディレクトリの変更を監視し、書き込みが落ち着いたらハッシュ値を計算し直します。
+//! 変更の通知にはOSの仕組み(Linuxはinotify、macOSはFSEvents、WindowsはReadDirectoryChangesW)を使います。
+//! 項目の意味は`docs/watch.md`にまとめています。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::error::Error;
+use crate::hash;
+use crate::multi_file;
+use crate::output_format::OutputFormat;
+use notify::Watcher;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 書き込みが落ち着いたとみなすまでの時間のデフォルト値(ミリ秒)
+pub const DEFAULT_DEBOUNCE_MILLIS: u64 = 500;
+
+/// 変更の種類
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    /// 監視を始めたときのハッシュ値
+    Initial,
+    Created,
+    Modified,
+    Removed,
+    /// ハッシュ値を計算できなかった
+    Error,
+}
+
+/// # 1ファイル分の変更
+/// NDJSON形式では1行のJSONにします。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchEvent {
+    pub event: EventKind,
+    /// カレントディレクトリからのパス
+    pub path: String,
+    /// ハッシュアルゴリズムの名前 `-d`と同じ
+    pub algorithm: String,
+    /// 変更前の小文字の16進数のハッシュ値 作成されたときはnull
+    pub old_digest: Option<String>,
+    /// 変更後の小文字の16進数のハッシュ値 削除されたときはnull
+    pub new_digest: Option<String>,
+    /// 変更後のバイト数
+    pub size: Option<u64>,
+    /// 計算し終えた時刻 RFC 3339形式
+    pub time: String,
+    /// 失敗した理由
+    pub error: Option<String>,
+}
+
+impl WatchEvent {
+    /// 人が読むための1行 改行は含みません
+    pub fn to_text(&self) -> String {
+        let digest = |digest: &Option<String>| digest.clone().unwrap_or_default();
+        let message = match self.event {
+            EventKind::Initial => t!(
+                "watch.initial",
+                path = self.path,
+                digest = digest(&self.new_digest)
+            ),
+            EventKind::Created => t!(
+                "watch.created",
+                path = self.path,
+                digest = digest(&self.new_digest)
+            ),
+            EventKind::Modified => t!(
+                "watch.modified",
+                path = self.path,
+                old = digest(&self.old_digest),
+                new = digest(&self.new_digest)
+            ),
+            EventKind::Removed => t!(
+                "watch.removed",
+                path = self.path,
+                digest = digest(&self.old_digest)
+            ),
+            EventKind::Error => t!(
+                "watch.error",
+                path = self.path,
+                error = self.error.clone().unwrap_or_default()
+            ),
+        };
+        let time = chrono::DateTime::parse_from_rfc3339(&self.time)
+            .map(|time| time.format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        format!("[{}] {}", time, message)
+    }
+}
+
+/// # 書き込みをまとめるもの
+/// パスごとに最後に変更を知らされた時刻を覚えておき、一定の時間変更がなければ落ち着いたとみなします。
+/// 書き込みが続く間はハッシュ値を計算しないので、ビルドの途中の中身を読まずに済みます。
+pub struct Debouncer {
+    delay: Duration,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Debouncer {
+        Debouncer {
+            delay,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// 変更を知らされたパスを覚えます。落ち着くまでの時間は最初からやり直します。
+    pub fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path, now);
+    }
+
+    /// 落ち着いたパスをパス順に取り出します。
+    pub fn take_settled(&mut self, now: Instant) -> Vec<PathBuf> {
+        let delay = self.delay;
+        let mut settled: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, touched)| now.duration_since(**touched) >= delay)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in settled.iter() {
+            self.pending.remove(path);
+        }
+        settled.sort();
+        settled
+    }
+
+    /// 次にパスが落ち着く時刻 待っているパスがなければNone
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending
+            .values()
+            .map(|touched| *touched + self.delay)
+            .min()
+    }
+}
+
+/// # 監視するもの
+/// ファイルは、書き換えで置き換えられても監視を続けられるように、親ディレクトリを監視します。
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    File(PathBuf),
+    /// ディレクトリの下のファイルを1つずつ監視します
+    Dir(PathBuf),
+}
+
+impl Target {
+    /// パスがこの監視対象に含まれるか 除外するパターンに一致するものは含めません
+    fn contains(&self, path: &Path, exclude: &[glob::Pattern]) -> bool {
+        match self {
+            Target::File(file) => file == path,
+            Target::Dir(root) => {
+                path != root
+                    && path.starts_with(root)
+                    && !path
+                        .ancestors()
+                        .take_while(|ancestor| ancestor != root)
+                        .any(|ancestor| multi_file::is_excluded(root, ancestor, exclude))
+            }
+        }
+    }
+}
+
+/// # 監視中のハッシュ値
+/// パスごとに最後に計算したハッシュ値を覚えておき、変わったときだけ変更として返します。
+pub struct WatchState {
+    digest_algorithm: DigestAlgorithm,
+    base_dir: PathBuf,
+    digests: BTreeMap<PathBuf, String>,
+}
+
+impl WatchState {
+    /// ## 引数
+    /// - ハッシュアルゴリズム
+    /// - 表示するパスの基準になるディレクトリ
+    pub fn new(digest_algorithm: DigestAlgorithm, base_dir: PathBuf) -> WatchState {
+        WatchState {
+            digest_algorithm,
+            base_dir,
+            digests: BTreeMap::new(),
+        }
+    }
+
+    /// # 落ち着いたパスのハッシュ値を計算し直す関数
+    /// ディレクトリなら、その下のファイルを計算し直し、なくなったファイルを削除として返します。
+    /// ## 引数
+    /// - 落ち着いたパス
+    /// - 初めて計算したファイルを、作成ではなく監視を始めたときのハッシュ値として返すか
+    /// - 含めるかどうかを決める関数
+    /// ## 返り値
+    /// ハッシュ値が変わったファイルの変更 パス順
+    pub fn update(
+        &mut self,
+        path: &Path,
+        initial: bool,
+        contains: &dyn Fn(&Path) -> bool,
+    ) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+        // なくなったファイル ディレクトリごと消えたときは、その下のファイルもなくなる
+        let removed: Vec<PathBuf> = self
+            .digests
+            .keys()
+            .filter(|known| known.starts_with(path) && !known.is_file())
+            .cloned()
+            .collect();
+        for known in removed {
+            let old_digest = self.digests.remove(&known);
+            events.push(self.event(EventKind::Removed, &known, old_digest, None, None));
+        }
+
+        let files = if path.is_dir() {
+            multi_file::collect_files(&[path.to_path_buf()], &[]).unwrap_or_default()
+        } else if path.is_file() {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        };
+        for file in files.into_iter().filter(|file| contains(file)) {
+            let result = multi_file::hash_file(
+                &file,
+                &[self.digest_algorithm],
+                None,
+                indicatif::ProgressBar::hidden(),
+            );
+            let old_digest = self.digests.get(&file).cloned();
+            match result {
+                Ok((size, hash_values)) => {
+                    let new_digest = hash_values
+                        .first()
+                        .map(|(_, hash_value)| hash::to_hex(hash_value))
+                        .unwrap_or_default();
+                    if old_digest.as_ref() == Some(&new_digest) {
+                        continue;
+                    }
+                    let kind = match (&old_digest, initial) {
+                        (Some(_), _) => EventKind::Modified,
+                        (None, true) => EventKind::Initial,
+                        (None, false) => EventKind::Created,
+                    };
+                    self.digests.insert(file.clone(), new_digest.clone());
+                    events.push(self.event(kind, &file, old_digest, Some(new_digest), Some(size)));
+                }
+                // 計算している間に消されたときは、次の通知で削除として扱う
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    let mut event = self.event(EventKind::Error, &file, old_digest, None, None);
+                    event.error = Some(e.to_string());
+                    events.push(event);
+                }
+            }
+        }
+        events.sort_by(|a, b| a.path.cmp(&b.path));
+        events
+    }
+
+    fn event(
+        &self,
+        kind: EventKind,
+        path: &Path,
+        old_digest: Option<String>,
+        new_digest: Option<String>,
+        size: Option<u64>,
+    ) -> WatchEvent {
+        WatchEvent {
+            event: kind,
+            path: multi_file::relative_path(path, &self.base_dir)
+                .unwrap_or_else(|_| path.display().to_string()),
+            algorithm: self.digest_algorithm.name().to_string(),
+            old_digest,
+            new_digest,
+            size,
+            time: chrono::Local::now().to_rfc3339(),
+            error: None,
+        }
+    }
+}
+
+/// 変更を書き出す
+fn emit(events: &[WatchEvent], output_format: OutputFormat) {
+    for event in events {
+        match output_format {
+            OutputFormat::Ndjson => {
+                println!("{}", serde_json::to_string(event).unwrap_or_default())
+            }
+            _ => println!("{}", event.to_text()),
+        }
+    }
+}
+
+/// 監視していることにしてよい通知か 読み込みだけの通知は、自分でハッシュ値を計算したときにも届くので除く
+fn is_change(kind: &notify::EventKind) -> bool {
+    match kind {
+        notify::EventKind::Access(notify::event::AccessKind::Close(
+            notify::event::AccessMode::Write,
+        )) => true,
+        notify::EventKind::Access(_) => false,
+        _ => true,
+    }
+}
+
+/// # 監視関数
+/// 監視を始めたときのハッシュ値を書き出してから、変更を待ち続けます。Ctrl+Cで終了します。
+/// ## 引数
+/// - 監視するファイルかディレクトリのパス まだないファイルは、親ディレクトリがあれば作成を待ちます
+/// - ハッシュアルゴリズム
+/// - 出力形式 textかndjson
+/// - ディレクトリの下のファイルから除外するパターン
+/// - 書き込みが落ち着いたとみなすまでの時間
+pub fn watch(
+    paths: &[PathBuf],
+    digest_algorithm: DigestAlgorithm,
+    output_format: OutputFormat,
+    exclude: &[glob::Pattern],
+    debounce: Duration,
+) -> Result<(), Error> {
+    // 設定ファイルのjsonとcsvは、黙ってtextにせずエラーにします
+    if !matches!(output_format, OutputFormat::Text | OutputFormat::Ndjson) {
+        return Err(Error::InvalidArgument(t!(
+            "watch.unsupported_format",
+            format = output_format.name()
+        )));
+    }
+    let mut targets = Vec::new();
+    for path in paths {
+        let path =
+            multi_file::absolute_path(path).map_err(|e| Error::io(&t!("io.current_dir"), e))?;
+        if path.is_dir() {
+            targets.push(Target::Dir(path));
+        } else {
+            targets.push(Target::File(path));
+        }
+    }
+    let contains = |path: &Path| targets.iter().any(|target| target.contains(path, exclude));
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)
+        .map_err(|e| Error::Platform(t!("watch.failed", error = e)))?;
+    for target in targets.iter() {
+        let (path, mode) = match target {
+            Target::Dir(root) => (root.as_path(), notify::RecursiveMode::Recursive),
+            Target::File(file) => (
+                file.parent().unwrap_or(file),
+                notify::RecursiveMode::NonRecursive,
+            ),
+        };
+        if !path.is_dir() {
+            return Err(Error::MissingInput(t!(
+                "watch.not_found",
+                path = path.display()
+            )));
+        }
+        watcher
+            .watch(path, mode)
+            .map_err(|e| Error::Platform(t!("watch.failed", error = e)))?;
+    }
+
+    let mut state = WatchState::new(digest_algorithm, PathBuf::from("."));
+    for target in targets.iter() {
+        let path = match target {
+            Target::Dir(root) => root,
+            Target::File(file) => file,
+        };
+        emit(&state.update(path, true, &contains), output_format);
+    }
+    eprintln!("{}", t!("watch.started", count = targets.len()));
+
+    let mut debouncer = Debouncer::new(debounce);
+    loop {
+        let received = match debouncer.next_deadline() {
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => receiver
+                .recv()
+                .map_err(|_| std::sync::mpsc::RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(Ok(event)) if is_change(&event.kind) => {
+                let now = Instant::now();
+                for path in event.paths {
+                    // ディレクトリの通知は、その下のファイルをまとめて計算し直すので、含めるかどうかは後で決める
+                    let watched = targets.iter().any(|target| match target {
+                        Target::File(file) => file == &path,
+                        Target::Dir(root) => path.starts_with(root),
+                    });
+                    if watched {
+                        debouncer.touch(path, now);
+                    }
+                }
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("{}", t!("watch.failed", error = e)),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(Error::Platform(t!("watch.stopped")));
+            }
+        }
+        for path in debouncer.take_settled(Instant::now()) {
+            emit(&state.update(&path, false, &contains), output_format);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 設定ファイルから来たjsonとcsvは、監視を始める前にエラーにする
+    #[test]
+    fn ng_watch_format() {
+        for output_format in [OutputFormat::Json, OutputFormat::Csv] {
+            let result = watch(
+                &[PathBuf::from("Cargo.toml")],
+                DigestAlgorithm::default(),
+                output_format,
+                &[],
+                Duration::from_millis(DEFAULT_DEBOUNCE_MILLIS),
+            );
+            assert!(matches!(result, Err(Error::InvalidArgument(_))));
+        }
+    }
+
+    /// 書き込みが続く間は取り出さず、落ち着いてから1度だけ取り出す
+    #[test]
+    fn ok_debouncer() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_millis(100));
+        assert_eq!(None, debouncer.next_deadline());
+        debouncer.touch(PathBuf::from("b"), start);
+        debouncer.touch(PathBuf::from("a"), start + Duration::from_millis(50));
+        debouncer.touch(PathBuf::from("b"), start + Duration::from_millis(80));
+        assert_eq!(
+            Some(start + Duration::from_millis(150)),
+            debouncer.next_deadline()
+        );
+        assert!(debouncer
+            .take_settled(start + Duration::from_millis(120))
+            .is_empty());
+        assert_eq!(
+            vec![PathBuf::from("a")],
+            debouncer.take_settled(start + Duration::from_millis(150))
+        );
+        assert_eq!(
+            vec![PathBuf::from("b")],
+            debouncer.take_settled(start + Duration::from_millis(200))
+        );
+        assert_eq!(None, debouncer.next_deadline());
+    }
+
+    /// ハッシュ値が変わったときだけ、変更前と変更後のハッシュ値を返す
+    #[test]
+    fn ok_watch_state() {
+        let root = std::env::temp_dir().join(format!("digest_tool_watch_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        std::fs::write(root.join("sub/b.txt"), "b").unwrap();
+        std::fs::write(root.join("c.tmp"), "c").unwrap();
+        let exclude = vec![glob::Pattern::new("*.tmp").unwrap()];
+        let target = Target::Dir(root.clone());
+        let contains = |path: &Path| target.contains(path, &exclude);
+        let mut state = WatchState::new(DigestAlgorithm::Sha2_256, root.clone());
+
+        let events = state.update(&root, true, &contains);
+        assert_eq!(
+            vec![
+                ("a.txt", EventKind::Initial),
+                ("sub/b.txt", EventKind::Initial)
+            ],
+            events
+                .iter()
+                .map(|event| (event.path.as_str(), event.event))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some("ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb".to_string()),
+            events[0].new_digest
+        );
+
+        // 中身が同じなら変更にしない
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        assert!(state
+            .update(&root.join("a.txt"), false, &contains)
+            .is_empty());
+
+        std::fs::write(root.join("a.txt"), "aa").unwrap();
+        let events = state.update(&root.join("a.txt"), false, &contains);
+        assert_eq!(1, events.len());
+        assert_eq!(EventKind::Modified, events[0].event);
+        assert_eq!(
+            Some("ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb".to_string()),
+            events[0].old_digest
+        );
+        assert_eq!(Some(2), events[0].size);
+
+        std::fs::remove_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("d.txt"), "d").unwrap();
+        let mut events = state.update(&root.join("sub"), false, &contains);
+        events.extend(state.update(&root.join("d.txt"), false, &contains));
+        assert_eq!(
+            vec![
+                ("sub/b.txt", EventKind::Removed),
+                ("d.txt", EventKind::Created)
+            ],
+            events
+                .iter()
+                .map(|event| (event.path.as_str(), event.event))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(None, events[0].new_digest);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// 監視するファイルだけを含める
+    #[test]
+    fn ok_target_contains() {
+        let exclude = vec![glob::Pattern::new(".git").unwrap()];
+        let dir = Target::Dir(PathBuf::from("/work/dist"));
+        assert!(dir.contains(Path::new("/work/dist/app.zip"), &exclude));
+        assert!(!dir.contains(Path::new("/work/dist"), &exclude));
+        assert!(!dir.contains(Path::new("/work/dist/.git/index"), &exclude));
+        assert!(!dir.contains(Path::new("/work/other/app.zip"), &exclude));
+        let file = Target::File(PathBuf::from("/work/app.zip"));
+        assert!(file.contains(Path::new("/work/app.zip"), &exclude));
+        assert!(!file.contains(Path::new("/work/app.zip.tmp"), &exclude));
+    }
+}
@@ -0,0 +1,374 @@
+//! # 拡張属性モジュール
+//! ファイルのハッシュ値と計算した時刻を、ファイル自身の拡張属性`user.checksum.<アルゴリズム>`に保存します。
+//! チェックサムファイルを別に持たなくても、後から計算し直して、内容が壊れていないか(ビット腐敗)を確かめられます。
+//!
+//! 属性の値は`<小文字の16進数のハッシュ値> <計算を始めた時刻>`です。時刻はUTCのRFC 3339形式で、ナノ秒まで書きます。
+//! 計算し直したハッシュ値が違っても、ファイルがその時刻より後に更新されていれば、書き換えられただけとみなします。
+
+use crate::cli_arg_accepter::DigestAlgorithm;
+use crate::hash;
+use crate::multi_file;
+use std::path::Path;
+
+/// 属性の名前の前半 後にアルゴリズムの名前を付けます
+const NAME_PREFIX: &str = "user.checksum.";
+
+/// アルゴリズムごとの属性の名前
+pub fn attribute_name(digest_algorithm: DigestAlgorithm) -> String {
+    format!("{}{}", NAME_PREFIX, digest_algorithm.name())
+}
+
+/// この環境で拡張属性を使えるか
+pub fn is_supported_platform() -> bool {
+    xattr::SUPPORTED_PLATFORM
+}
+
+/// # 拡張属性に対応していないファイルシステムのエラーか
+/// tmpfsの古いものやFAT、一部のネットワークファイルシステムは拡張属性を書けません。
+pub fn is_unsupported(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    if e.raw_os_error() == Some(libc::ENOTSUP) {
+        return true;
+    }
+    e.kind() == std::io::ErrorKind::Unsupported
+}
+
+/// # 保存したハッシュ値
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredDigest {
+    pub hash_value: Vec<u8>,
+    /// 計算を始めた時刻
+    pub computed: chrono::DateTime<chrono::Utc>,
+}
+
+impl StoredDigest {
+    /// 属性の値
+    fn to_value(&self) -> String {
+        format!(
+            "{} {}",
+            hash::to_hex(&self.hash_value),
+            self.computed
+                .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+        )
+    }
+
+    /// # 属性の値を読む関数
+    /// ハッシュ値の長さがアルゴリズムと合わなければNone
+    fn parse(value: &[u8], digest_algorithm: DigestAlgorithm) -> Option<StoredDigest> {
+        let value = std::str::from_utf8(value).ok()?.trim_end();
+        let (hash_value, computed) = value.split_once(' ')?;
+        let hash_value = hash::from_hex(hash_value)
+            .filter(|hash_value| hash_value.len() == digest_algorithm.digest_length())?;
+        let computed = chrono::DateTime::parse_from_rfc3339(computed)
+            .ok()?
+            .with_timezone(&chrono::Utc);
+        Some(StoredDigest {
+            hash_value,
+            computed,
+        })
+    }
+}
+
+/// ファイルを一度だけ読み込んで、指定されたすべてのアルゴリズムのハッシュ値を計算する
+/// 内容を確かめるためのものなので、ハッシュ値のキャッシュは使わない
+#[allow(clippy::type_complexity)]
+fn digest_file(
+    path: &Path,
+    digest_algorithms: &[DigestAlgorithm],
+) -> Result<Vec<(DigestAlgorithm, Vec<u8>)>, std::io::Error> {
+    let mut file_reader =
+        std::io::BufReader::with_capacity(multi_file::buffer_size(), std::fs::File::open(path)?);
+    let (_, hash_values) = hash::multi_digest(
+        &mut file_reader,
+        digest_algorithms,
+        indicatif::ProgressBar::hidden(),
+    )?;
+    Ok(hash_values)
+}
+
+/// # ハッシュ値を計算して拡張属性に書く関数
+/// ## 引数
+/// - ファイルのパス シンボリックリンクはたどります
+/// - ハッシュアルゴリズム
+/// ## 返り値
+/// Result型 std::ioのError 対応していないファイルシステムは`is_unsupported`で見分けます あるいは、書いた値
+pub fn write(
+    path: &Path,
+    digest_algorithms: &[DigestAlgorithm],
+) -> Result<Vec<(DigestAlgorithm, StoredDigest)>, std::io::Error> {
+    // 計算している間に書き換えられても、後から更新されたとわかるように、読み込む前の時刻にする
+    let computed = chrono::Utc::now();
+    let mut stored = Vec::new();
+    for (digest_algorithm, hash_value) in digest_file(path, digest_algorithms)? {
+        let stored_digest = StoredDigest {
+            hash_value,
+            computed,
+        };
+        xattr::set_deref(
+            path,
+            attribute_name(digest_algorithm),
+            stored_digest.to_value().as_bytes(),
+        )?;
+        stored.push((digest_algorithm, stored_digest));
+    }
+    Ok(stored)
+}
+
+/// # 拡張属性からハッシュ値を読む関数
+/// ## 引数
+/// - ファイルのパス シンボリックリンクはたどります
+/// - ハッシュアルゴリズム 空なら、保存されているすべてのアルゴリズム
+/// ## 返り値
+/// Result型 std::ioのError 値の形式が誤っていればInvalidData あるいは、保存されていたハッシュ値
+pub fn read(
+    path: &Path,
+    digest_algorithms: &[DigestAlgorithm],
+) -> Result<Vec<(DigestAlgorithm, StoredDigest)>, std::io::Error> {
+    let digest_algorithms = if digest_algorithms.is_empty() {
+        stored_algorithms(path)?
+    } else {
+        digest_algorithms.to_vec()
+    };
+    let mut stored = Vec::new();
+    for digest_algorithm in digest_algorithms {
+        let name = attribute_name(digest_algorithm);
+        if let Some(value) = xattr::get_deref(path, &name)? {
+            let stored_digest = StoredDigest::parse(&value, digest_algorithm).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    t!("xattr.malformed", name = name),
+                )
+            })?;
+            stored.push((digest_algorithm, stored_digest));
+        }
+    }
+    Ok(stored)
+}
+
+/// 保存されているアルゴリズム `DigestAlgorithm::ALL`の順
+fn stored_algorithms(path: &Path) -> Result<Vec<DigestAlgorithm>, std::io::Error> {
+    let names: Vec<String> = xattr::list_deref(path)?
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    Ok(DigestAlgorithm::ALL
+        .iter()
+        .copied()
+        .filter(|digest_algorithm| names.contains(&attribute_name(*digest_algorithm)))
+        .collect())
+}
+
+/// 確かめた結果の種類
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrubStatus {
+    /// 保存したハッシュ値と一致した
+    Ok,
+    /// 保存した後に更新されていないのに、ハッシュ値が一致しなかった
+    Corrupt,
+    /// 保存した後に更新されていて、ハッシュ値が一致しなかった
+    Outdated,
+    /// ハッシュ値が保存されていなかった
+    Missing,
+    /// ファイルシステムが拡張属性に対応していなかった
+    Unsupported,
+    /// ファイルや属性を読み込めなかった
+    Failed,
+}
+
+impl ScrubStatus {
+    /// 結果の表示に使う名前
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScrubStatus::Ok => "OK",
+            ScrubStatus::Corrupt => "CORRUPT",
+            ScrubStatus::Outdated => "OUTDATED",
+            ScrubStatus::Missing => "MISSING",
+            ScrubStatus::Unsupported => "UNSUPPORTED",
+            ScrubStatus::Failed => "FAILED",
+        }
+    }
+}
+
+/// 1ファイル、1ハッシュアルゴリズム分の確かめた結果
+#[derive(Debug, Clone)]
+pub struct ScrubResult {
+    pub digest_algorithm: DigestAlgorithm,
+    pub status: ScrubStatus,
+    pub stored: StoredDigest,
+    pub actual: Vec<u8>,
+}
+
+/// 確かめた結果の件数
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScrubSummary {
+    pub ok: usize,
+    pub corrupt: usize,
+    pub outdated: usize,
+    pub missing: usize,
+    pub unsupported: usize,
+    pub failed: usize,
+}
+
+impl ScrubSummary {
+    pub fn add(&mut self, status: ScrubStatus) {
+        match status {
+            ScrubStatus::Ok => self.ok += 1,
+            ScrubStatus::Corrupt => self.corrupt += 1,
+            ScrubStatus::Outdated => self.outdated += 1,
+            ScrubStatus::Missing => self.missing += 1,
+            ScrubStatus::Unsupported => self.unsupported += 1,
+            ScrubStatus::Failed => self.failed += 1,
+        }
+    }
+
+    /// 壊れたファイルも、確かめられなかったファイルもないか
+    /// 保存されていないファイルと、対応していないファイルシステムは失敗にしません
+    pub fn is_success(&self) -> bool {
+        self.corrupt == 0 && self.failed == 0
+    }
+}
+
+/// # ハッシュ値が一致しなかった理由を分ける関数
+/// ## 引数
+/// - 保存したハッシュ値
+/// - 計算し直したハッシュ値
+/// - ファイルの更新日時
+fn classify(
+    stored: &StoredDigest,
+    actual: &[u8],
+    modified: chrono::DateTime<chrono::Utc>,
+) -> ScrubStatus {
+    if stored.hash_value == actual {
+        ScrubStatus::Ok
+    } else if modified > stored.computed {
+        ScrubStatus::Outdated
+    } else {
+        ScrubStatus::Corrupt
+    }
+}
+
+/// # 保存したハッシュ値と計算し直したハッシュ値を比べる関数
+/// ## 引数
+/// - ファイルのパス シンボリックリンクはたどります
+/// - ハッシュアルゴリズム 空なら、保存されているすべてのアルゴリズム
+/// ## 返り値
+/// Result型 std::ioのError あるいは、アルゴリズムごとの結果 保存されていなければ空
+pub fn scrub(
+    path: &Path,
+    digest_algorithms: &[DigestAlgorithm],
+) -> Result<Vec<ScrubResult>, std::io::Error> {
+    let stored = read(path, digest_algorithms)?;
+    if stored.is_empty() {
+        return Ok(Vec::new());
+    }
+    let modified = chrono::DateTime::<chrono::Utc>::from(std::fs::metadata(path)?.modified()?);
+    let stored_algorithms: Vec<DigestAlgorithm> = stored
+        .iter()
+        .map(|(digest_algorithm, _)| *digest_algorithm)
+        .collect();
+    let actual = digest_file(path, &stored_algorithms)?;
+    Ok(stored
+        .into_iter()
+        .zip(actual)
+        .map(|((digest_algorithm, stored), (_, actual))| ScrubResult {
+            digest_algorithm,
+            status: classify(&stored, &actual, modified),
+            stored,
+            actual,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn stored() -> StoredDigest {
+        StoredDigest {
+            hash_value: vec![0xab; 32],
+            computed: chrono::Utc
+                .timestamp_opt(1_700_000_000, 123_456_789)
+                .unwrap(),
+        }
+    }
+
+    /// 書いた値をそのまま読める
+    #[test]
+    fn ok_parse() {
+        let value = stored().to_value();
+        assert_eq!(
+            format!("{} 2023-11-14T22:13:20.123456789Z", "ab".repeat(32)),
+            value
+        );
+        assert_eq!(
+            Some(stored()),
+            StoredDigest::parse(value.as_bytes(), DigestAlgorithm::Sha2_256)
+        );
+        assert_eq!(
+            "user.checksum.sha3_512",
+            attribute_name(DigestAlgorithm::Sha3_512)
+        );
+    }
+
+    /// ハッシュ値の長さが違う値や、時刻のない値は読めない
+    #[test]
+    fn ng_parse() {
+        let value = stored().to_value();
+        assert_eq!(
+            None,
+            StoredDigest::parse(value.as_bytes(), DigestAlgorithm::Md5)
+        );
+        assert_eq!(
+            None,
+            StoredDigest::parse("ab".repeat(32).as_bytes(), DigestAlgorithm::Sha2_256)
+        );
+        assert_eq!(
+            None,
+            StoredDigest::parse(b"\xff\xfe", DigestAlgorithm::Sha2_256)
+        );
+    }
+
+    /// 一致しないときは、保存した後に更新されたかで分ける
+    #[test]
+    fn ok_classify() {
+        let stored = stored();
+        let before = stored.computed - chrono::Duration::seconds(1);
+        let after = stored.computed + chrono::Duration::seconds(1);
+        assert_eq!(ScrubStatus::Ok, classify(&stored, &[0xab; 32], after));
+        assert_eq!(ScrubStatus::Corrupt, classify(&stored, &[0; 32], before));
+        assert_eq!(ScrubStatus::Outdated, classify(&stored, &[0; 32], after));
+    }
+
+    /// 書いて読み、書き換えると更新されたとわかる
+    /// 拡張属性に対応していないファイルシステムでは確かめない
+    #[test]
+    fn ok_write_scrub() {
+        let dir = std::env::temp_dir().join(format!("digest_tool_xattr_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.txt");
+        std::fs::write(&path, b"abc").unwrap();
+
+        match write(&path, &[DigestAlgorithm::Sha2_256, DigestAlgorithm::Md5]) {
+            Err(e) if is_unsupported(&e) || !is_supported_platform() => {}
+            written => {
+                let written = written.unwrap();
+                assert_eq!(written, read(&path, &[]).unwrap());
+                let results = scrub(&path, &[DigestAlgorithm::Md5]).unwrap();
+                assert_eq!(1, results.len());
+                assert_eq!(ScrubStatus::Ok, results[0].status);
+
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                std::fs::write(&path, b"abcd").unwrap();
+                let results = scrub(&path, &[]).unwrap();
+                assert_eq!(2, results.len());
+                assert!(results
+                    .iter()
+                    .all(|result| result.status == ScrubStatus::Outdated));
+            }
+        }
+        std::fs::write(dir.join("b.txt"), b"b").unwrap();
+        assert!(scrub(&dir.join("b.txt"), &[]).map_or(true, |results| results.is_empty()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}